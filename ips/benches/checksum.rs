@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ips::checksum::{self, Checksum};
+use ips::link::ethernet::fcs;
+
+fn compute(c: &mut Criterion) {
+    let data: Vec<u8> = (0..9000u32).map(|i| (i * 151 + 7) as u8).collect();
+
+    let mut group = c.benchmark_group("compute");
+    for &len in &[20, 64, 576, 1500, 9000] {
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &data[..len], |b, data| {
+            b.iter(|| checksum::compute(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+fn incremental(c: &mut Criterion) {
+    let data: Vec<u8> = (0..1500u32).map(|i| (i * 151 + 7) as u8).collect();
+
+    let mut group = c.benchmark_group("incremental");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("odd slices", |b| {
+        b.iter(|| {
+            let mut checksum = Checksum::new();
+            for chunk in black_box(&data[..]).chunks(101) {
+                checksum.add_bytes(chunk);
+            }
+            checksum.finish()
+        })
+    });
+    group.finish();
+
+    c.bench_function("update", |b| b.iter(|| checksum::update(black_box(0xB861), black_box(0x4011), black_box(0x3F11))));
+}
+
+fn crc32(c: &mut Criterion) {
+    let data: Vec<u8> = (0..9000u32).map(|i| (i * 151 + 7) as u8).collect();
+
+    let mut group = c.benchmark_group("fcs");
+    for &len in &[64, 1518, 9000] {
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &data[..len], |b, data| {
+            b.iter(|| fcs::compute(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compute, incremental, crc32);
+criterion_main!(benches);
@@ -0,0 +1,269 @@
+//! An implementation of the internet checksum used by IPv4, ICMP, UDP, and TCP
+//!
+//! The checksum is the ones' complement of the ones' complement sum of all 16-bit words in the data.
+//! The sum is computed 64 bits at a time, which gives the same result after folding the carries back into 16 bits.
+
+use crate::network::Protocol;
+
+use core::convert::TryFrom;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// An incremental internet checksum calculation
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Checksum {
+    sum: u64,
+    /// The last byte of an odd length slice, which forms the high byte of a word with the first byte of the next slice
+    pending: Option<u8>
+}
+
+impl Checksum {
+    /// Creates a new checksum calculation with no data
+    pub const fn new() -> Self {
+        Checksum { sum: 0, pending: None }
+    }
+
+    /// Creates a new checksum calculation containing an IPv4 pseudo-header,
+    /// used by upper-layer protocols to include the IP addresses in their checksums
+    pub fn ipv4_pseudo_header(source: Ipv4Addr, destination: Ipv4Addr, protocol: Protocol, length: u16) -> Self {
+        let mut checksum = Checksum::new();
+        checksum
+            .add_bytes(&source.octets())
+            .add_bytes(&destination.octets())
+            .add_u16(protocol.0 as u16)
+            .add_u16(length);
+        checksum
+    }
+
+    /// Creates a new checksum calculation containing an IPv6 pseudo-header,
+    /// used by upper-layer protocols to include the IP addresses in their checksums
+    pub fn ipv6_pseudo_header(source: Ipv6Addr, destination: Ipv6Addr, next_header: Protocol, length: u32) -> Self {
+        let mut checksum = Checksum::new();
+        checksum
+            .add_bytes(&source.octets())
+            .add_bytes(&destination.octets())
+            .add_u32(length)
+            .add_u16(next_header.0 as u16);
+        checksum
+    }
+
+    /// Adds a slice of bytes to the checksum. Slices may be any length, and consecutive slices are summed as if they were one slice.
+    pub fn add_bytes(&mut self, mut data: &[u8]) -> &mut Self {
+        if let Some(high) = self.pending.take() {
+            match data.split_first() {
+                Some((&low, rest)) => {
+                    self.sum = add(self.sum, u16::from_be_bytes([high, low]) as u64);
+                    data = rest;
+                },
+                None => {
+                    self.pending = Some(high);
+                    return self;
+                }
+            }
+        }
+
+        if let Some((&last, rest)) = data.split_last().filter(|_| data.len() % 2 == 1) {
+            self.pending = Some(last);
+            data = rest;
+        }
+
+        self.sum = add(self.sum, sum_words(data));
+        self
+    }
+
+    /// Adds a 16-bit word to the checksum
+    pub fn add_u16(&mut self, value: u16) -> &mut Self {
+        self.add_bytes(&value.to_be_bytes())
+    }
+
+    /// Adds a 32-bit value to the checksum as two 16-bit words
+    pub fn add_u32(&mut self, value: u32) -> &mut Self {
+        self.add_bytes(&value.to_be_bytes())
+    }
+
+    /// Gets the ones' complement sum of all data added to the checksum, without complementing it
+    pub fn sum(&self) -> u16 {
+        let sum = match self.pending {
+            Some(high) => add(self.sum, (high as u64) << 8),
+            None => self.sum
+        };
+        fold(sum)
+    }
+
+    /// Gets the checksum of all data added to the checksum
+    pub fn finish(&self) -> u16 {
+        !self.sum()
+    }
+}
+
+/// The addresses of an IPv4 or IPv6 pseudo-header, used by upper-layer protocols to include the IP addresses in their checksums
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PseudoHeader {
+    /// An IPv4 pseudo-header
+    V4 {
+        /// The source address of the IPv4 packet
+        source: Ipv4Addr,
+        /// The destination address of the IPv4 packet
+        destination: Ipv4Addr
+    },
+    /// An IPv6 pseudo-header
+    V6 {
+        /// The source address of the IPv6 packet
+        source: Ipv6Addr,
+        /// The final destination address of the IPv6 packet
+        destination: Ipv6Addr
+    }
+}
+
+impl PseudoHeader {
+    /// Creates a new checksum calculation containing this pseudo-header with the specified upper-layer protocol and length.
+    ///
+    /// For IPv4 pseudo-headers, the length is truncated to 16 bits.
+    pub fn checksum(&self, protocol: Protocol, length: u32) -> Checksum {
+        match *self {
+            PseudoHeader::V4 { source, destination } => Checksum::ipv4_pseudo_header(source, destination, protocol, length as u16),
+            PseudoHeader::V6 { source, destination } => Checksum::ipv6_pseudo_header(source, destination, protocol, length),
+        }
+    }
+}
+
+/// Adds two values using ones' complement addition
+#[inline]
+fn add(a: u64, b: u64) -> u64 {
+    let (sum, carry) = a.overflowing_add(b);
+    sum + carry as u64
+}
+
+/// Folds a 64-bit ones' complement sum into 16 bits
+#[inline]
+fn fold(mut sum: u64) -> u16 {
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum as u16
+}
+
+/// Sums an even length slice of big endian 16-bit words, 64 bits at a time
+fn sum_words(data: &[u8]) -> u64 {
+    let mut chunks = data.chunks_exact(8);
+    let mut sum = 0;
+    for chunk in chunks.by_ref() {
+        sum = add(sum, u64::from_be_bytes(<[u8; 8]>::try_from(chunk).unwrap()));
+    }
+
+    // the remainder starts on a word boundary, so padding it with zeros on the right doesn't change the sum
+    let remainder = chunks.remainder();
+    let mut last = [0; 8];
+    last[..remainder.len()].copy_from_slice(remainder);
+    add(sum, u64::from_be_bytes(last))
+}
+
+/// Computes the internet checksum of a slice of bytes
+pub fn compute(data: &[u8]) -> u16 {
+    Checksum::new().add_bytes(data).finish()
+}
+
+/// Verifies the internet checksum of a slice of bytes that contains its own checksum field
+pub fn verify(data: &[u8]) -> bool {
+    compute(data) == 0
+}
+
+/// Updates a checksum after a 16-bit word covered by the checksum changes from `old` to `new`, as described in RFC 1624
+pub fn update(checksum: u16, old: u16, new: u16) -> u16 {
+    // HC' = ~(~HC + ~m + m')
+    let sum = add(add(!checksum as u64, !old as u64), new as u64);
+    !fold(sum)
+}
+
+/// Updates a checksum after a field covered by the checksum changes from `old` to `new`.
+/// The field must start on a 16-bit boundary in the checksummed data.
+///
+/// # Panics
+///
+/// This function will panic if the old and new values have different lengths.
+pub fn update_bytes(checksum: u16, old: &[u8], new: &[u8]) -> u16 {
+    assert_eq!(old.len(), new.len(), "old and new values must be the same length");
+    let old = Checksum::new().add_bytes(old).sum();
+    let new = Checksum::new().add_bytes(new).sum();
+    update(checksum, old, new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the example IPv4 header from RFC 1071 implementations, with a checksum of 0xB861
+    const HEADER: [u8; 20] = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11,
+        0xB8, 0x61, 0xC0, 0xA8, 0x00, 0x01, 0xC0, 0xA8, 0x00, 0xC7
+    ];
+
+    fn naive(data: &[u8]) -> u16 {
+        let mut sum = 0u32;
+        for pair in data.chunks(2) {
+            sum += u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32;
+        }
+        while sum > 0xFFFF {
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+
+    #[test]
+    fn compute_header() {
+        let mut header = HEADER;
+        assert!(verify(&header));
+        header[10..12].copy_from_slice(&[0, 0]);
+        assert_eq!(compute(&header), 0xB861);
+    }
+
+    #[test]
+    fn compute_matches_naive() {
+        let data: [u8; 67] = core::array::from_fn(|i| (i as u8).wrapping_mul(151).wrapping_add(7));
+        for len in 0..data.len() {
+            assert_eq!(compute(&data[..len]), naive(&data[..len]), "length {}", len);
+        }
+        assert_eq!(compute(&[0xFF; 1024]), naive(&[0xFF; 1024]));
+    }
+
+    #[test]
+    fn incremental_odd_slices() {
+        let data: [u8; 31] = core::array::from_fn(|i| (i as u8).wrapping_mul(73));
+        for split in 0..data.len() {
+            let (a, b) = data.split_at(split);
+            let mut checksum = Checksum::new();
+            checksum.add_bytes(a).add_bytes(&[]).add_bytes(b);
+            assert_eq!(checksum.finish(), compute(&data), "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn update_field() {
+        let mut header = HEADER;
+        // decrement the ttl
+        let old = u16::from_be_bytes([header[8], header[9]]);
+        header[8] -= 1;
+        let new = u16::from_be_bytes([header[8], header[9]]);
+        let checksum = update(0xB861, old, new);
+        header[10..12].copy_from_slice(&checksum.to_be_bytes());
+        assert!(verify(&header));
+
+        // rewrite the source address
+        let checksum = update_bytes(checksum, &header[12..16], &[10, 0, 0, 1]);
+        header[12..16].copy_from_slice(&[10, 0, 0, 1]);
+        header[10..12].copy_from_slice(&checksum.to_be_bytes());
+        assert!(verify(&header));
+    }
+
+    #[test]
+    fn pseudo_headers() {
+        let v4 = Checksum::ipv4_pseudo_header(Ipv4Addr::new(192, 168, 0, 1), Ipv4Addr::new(192, 168, 0, 199), Protocol::UDP, 8);
+        let mut expected = Checksum::new();
+        expected.add_bytes(&[192, 168, 0, 1, 192, 168, 0, 199, 0, 17, 0, 8]);
+        assert_eq!(v4.finish(), expected.finish());
+
+        let v6 = Checksum::ipv6_pseudo_header(Ipv6Addr::LOCALHOST, Ipv6Addr::LOCALHOST, Protocol::TCP, 20);
+        let mut expected = Checksum::new();
+        expected.add_bytes(&Ipv6Addr::LOCALHOST.octets()).add_bytes(&Ipv6Addr::LOCALHOST.octets()).add_bytes(&[0, 0, 0, 20, 0, 0, 0, 6]);
+        assert_eq!(v6.finish(), expected.finish());
+    }
+}
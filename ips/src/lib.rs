@@ -9,8 +9,13 @@ mod internal {
 }
 
 pub mod physical;
+pub mod checksum;
 pub mod link;
+pub mod network;
+pub mod transport;
 pub mod payload;
+pub mod pcap;
+pub mod pcapng;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,361 @@
+//! Types for reading and writing ARP and RARP packets for IPv4 over ethernet
+
+use crate::internal::Sealed;
+use crate::link::ethernet::EtherType;
+use crate::payload;
+use crate::physical::{self, Cursor, Read, Size, Write};
+
+use core::fmt;
+use core::net::Ipv4Addr;
+use macress::{Address, Eui48};
+
+type MacAddr = Address<Eui48>;
+
+/// The length of an ARP packet for IPv4 over ethernet
+pub const PACKET_LEN: usize = 28;
+
+/// An ARP packet parsed from a slice of bytes, where any data after the packet is padding
+pub type Parsed<'a> = payload::Padded<&'a [u8], Arp>;
+
+/// An error that occurs while parsing an ARP packet
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the ARP packet
+    Truncated,
+    /// The hardware type wasn't ethernet
+    UnsupportedHardwareType(u16),
+    /// The protocol type wasn't IPv4
+    UnsupportedProtocolType(EtherType),
+    /// The hardware or protocol address lengths didn't match the lengths of ethernet and IPv4 addresses
+    InvalidAddressLength {
+        /// The length of the hardware addresses, which should be 6
+        hardware: u8,
+        /// The length of the protocol addresses, which should be 4
+        protocol: u8
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad ARP packet; ran out of data"),
+            ParseError::UnsupportedHardwareType(htype) => write!(f, "bad ARP packet; unsupported hardware type {}", htype),
+            ParseError::UnsupportedProtocolType(ptype) => write!(f, "bad ARP packet; unsupported protocol type {:#06X}", ptype.0),
+            ParseError::InvalidAddressLength { hardware, protocol } =>
+                write!(f, "bad ARP packet; invalid hardware and protocol address lengths {} and {}", hardware, protocol),
+        }
+    }
+}
+
+/// The operation of an ARP packet
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Operation(pub u16);
+
+impl Operation {
+    /// A request for the hardware address of the target protocol address
+    pub const REQUEST: Operation = Operation(1);
+    /// A reply containing the hardware address of the sender
+    pub const REPLY: Operation = Operation(2);
+    /// A RARP request for the protocol address of the target hardware address
+    pub const REVERSE_REQUEST: Operation = Operation(3);
+    /// A RARP reply containing the protocol address of the target hardware address
+    pub const REVERSE_REPLY: Operation = Operation(4);
+}
+
+impl Sealed for Operation { }
+impl Size for Operation {
+    fn size(&self) -> usize {
+        2
+    }
+}
+impl Write for Operation {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+impl Read<'_> for Operation {
+    fn read(input: &[u8]) -> Result<Self, physical::Error> {
+        u16::read(input).map(Operation)
+    }
+}
+
+/// An ARP packet mapping IPv4 addresses to ethernet hardware addresses
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Arp {
+    /// The operation of the packet
+    pub operation: Operation,
+    /// The hardware address of the sender
+    pub sender_hardware: MacAddr,
+    /// The protocol address of the sender
+    pub sender_protocol: Ipv4Addr,
+    /// The hardware address of the target, which is ignored in requests
+    pub target_hardware: MacAddr,
+    /// The protocol address of the target
+    pub target_protocol: Ipv4Addr
+}
+
+impl Arp {
+    /// The hardware type for ethernet
+    pub const HARDWARE_ETHERNET: u16 = 1;
+
+    /// Creates a request for the hardware address of the target address from the specified sender
+    pub fn request(sender_hardware: MacAddr, sender_protocol: Ipv4Addr, target_protocol: Ipv4Addr) -> Self {
+        Arp {
+            operation: Operation::REQUEST,
+            sender_hardware,
+            sender_protocol,
+            target_hardware: MacAddr::ZERO,
+            target_protocol
+        }
+    }
+
+    /// Creates a gratuitous ARP request announcing that the specified hardware address owns the protocol address
+    pub fn gratuitous(hardware: MacAddr, protocol: Ipv4Addr) -> Self {
+        Arp::request(hardware, protocol, protocol)
+    }
+
+    /// Creates an ARP probe, used to check whether the target address is in use before claiming it
+    pub fn probe(sender_hardware: MacAddr, target_protocol: Ipv4Addr) -> Self {
+        Arp::request(sender_hardware, Ipv4Addr::UNSPECIFIED, target_protocol)
+    }
+
+    /// Creates a RARP request for the protocol address of the specified hardware address
+    pub fn reverse_request(hardware: MacAddr) -> Self {
+        Arp {
+            operation: Operation::REVERSE_REQUEST,
+            sender_hardware: hardware,
+            sender_protocol: Ipv4Addr::UNSPECIFIED,
+            target_hardware: hardware,
+            target_protocol: Ipv4Addr::UNSPECIFIED
+        }
+    }
+
+    /// Creates a reply to this request, announcing that the specified hardware address owns the target protocol address.
+    /// Returns `None` if this packet isn't a request.
+    pub fn reply_to(&self, hardware: MacAddr) -> Option<Self> {
+        if self.operation != Operation::REQUEST {
+            return None;
+        }
+        Some(Arp {
+            operation: Operation::REPLY,
+            sender_hardware: hardware,
+            sender_protocol: self.target_protocol,
+            target_hardware: self.sender_hardware,
+            target_protocol: self.sender_protocol
+        })
+    }
+
+    /// Creates a reply to this RARP request from the specified server, assigning the protocol address to the target hardware address.
+    /// Returns `None` if this packet isn't a RARP request.
+    pub fn reverse_reply_to(&self, server_hardware: MacAddr, server_protocol: Ipv4Addr, assigned: Ipv4Addr) -> Option<Self> {
+        if self.operation != Operation::REVERSE_REQUEST {
+            return None;
+        }
+        Some(Arp {
+            operation: Operation::REVERSE_REPLY,
+            sender_hardware: server_hardware,
+            sender_protocol: server_protocol,
+            target_hardware: self.target_hardware,
+            target_protocol: assigned
+        })
+    }
+
+    /// Returns whether this packet is a gratuitous ARP request or reply, where the sender announces its own address
+    pub fn is_gratuitous(&self) -> bool {
+        (self.operation == Operation::REQUEST || self.operation == Operation::REPLY) &&
+            self.sender_protocol == self.target_protocol &&
+            !self.sender_protocol.is_unspecified()
+    }
+
+    /// Returns whether this packet is an ARP probe, a request with an unspecified sender protocol address
+    pub fn is_probe(&self) -> bool {
+        self.operation == Operation::REQUEST && self.sender_protocol.is_unspecified()
+    }
+
+    /// Gets the ethertype of frames carrying this packet, [`RARP`](../ethernet/struct.EtherType.html#associatedconstant.RARP)
+    /// for RARP operations and [`ARP`](../ethernet/struct.EtherType.html#associatedconstant.ARP) otherwise
+    pub fn ether_type(&self) -> EtherType {
+        match self.operation {
+            Operation::REVERSE_REQUEST | Operation::REVERSE_REPLY => EtherType::RARP,
+            _ => EtherType::ARP
+        }
+    }
+
+    /// Gets the destination address of frames carrying this packet.
+    /// Replies are sent to the target hardware address, and all other packets are broadcast.
+    pub fn destination(&self) -> MacAddr {
+        match self.operation {
+            Operation::REPLY | Operation::REVERSE_REPLY => self.target_hardware,
+            _ => MacAddr::BROADCAST
+        }
+    }
+
+    /// Parses an ARP packet from a slice of bytes. Any data after the packet is returned as padding.
+    pub fn parse<'a, P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Parsed<'a>, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < 8 {
+            return Err(ParseError::Truncated);
+        }
+
+        let htype = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let ptype = EtherType(u16::from_be_bytes([bytes[2], bytes[3]]));
+        let (hlen, plen) = (bytes[4], bytes[5]);
+        if htype != Arp::HARDWARE_ETHERNET {
+            return Err(ParseError::UnsupportedHardwareType(htype));
+        }
+        if ptype != EtherType::IPV4 {
+            return Err(ParseError::UnsupportedProtocolType(ptype));
+        }
+        if hlen != 6 || plen != 4 {
+            return Err(ParseError::InvalidAddressLength { hardware: hlen, protocol: plen });
+        }
+        if bytes.len() < PACKET_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let ipv4 = |start: usize| Ipv4Addr::read(&bytes[start..]).unwrap();
+        let mac = |start: usize| MacAddr::read(&bytes[start..]).unwrap();
+
+        Ok(payload::Padded {
+            payload: Arp {
+                operation: Operation(u16::from_be_bytes([bytes[6], bytes[7]])),
+                sender_hardware: mac(8),
+                sender_protocol: ipv4(14),
+                target_hardware: mac(18),
+                target_protocol: ipv4(24)
+            },
+            padding: &bytes.0[PACKET_LEN..]
+        })
+    }
+}
+
+impl Sealed for Arp { }
+impl Size for Arp {
+    fn size(&self) -> usize {
+        PACKET_LEN
+    }
+}
+impl Write for Arp {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&Arp::HARDWARE_ETHERNET)?;
+        cursor.write(&EtherType::IPV4)?;
+        cursor.write(&6u8)?;
+        cursor.write(&4u8)?;
+        cursor.write(&self.operation)?;
+        cursor.write(&self.sender_hardware)?;
+        cursor.write(&self.sender_protocol)?;
+        cursor.write(&self.target_hardware)?;
+        cursor.write(&self.target_protocol)?;
+        Ok(cursor.finish())
+    }
+}
+impl Read<'_> for Arp {
+    fn read(input: &[u8]) -> Result<Self, physical::Error> {
+        Arp::parse(input).map(|parsed| parsed.payload).map_err(|_| physical::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{Ethernet2, EthernetBase, PayloadError};
+    use crate::link::ethernet::vlan;
+
+    const ALICE: MacAddr = Address::new([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    const BOB: MacAddr = Address::new([0x02, 0x00, 0x00, 0x00, 0x00, 0x02]);
+
+    const REQUEST: [u8; 28] = [
+        0x00, 0x01, 0x08, 0x00, 0x06, 0x04, 0x00, 0x01,
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 192, 168, 0, 1,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 192, 168, 0, 2
+    ];
+
+    #[test]
+    fn parse() {
+        let parsed = Arp::parse(&REQUEST[..]).unwrap();
+        assert!(parsed.padding.is_empty());
+        let request = parsed.payload;
+        assert_eq!(request, Arp::request(ALICE, Ipv4Addr::new(192, 168, 0, 1), Ipv4Addr::new(192, 168, 0, 2)));
+        assert_eq!(request.destination(), MacAddr::BROADCAST);
+        assert!(!request.is_gratuitous() && !request.is_probe());
+
+        let mut buf = [0; 28];
+        assert_eq!(request.write(&mut buf), Ok(28));
+        assert_eq!(buf, REQUEST);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Arp::parse(&REQUEST[..7]), Err(ParseError::Truncated));
+        assert_eq!(Arp::parse(&REQUEST[..27]), Err(ParseError::Truncated));
+
+        let mut bad = REQUEST;
+        bad[1] = 6;
+        assert_eq!(Arp::parse(&bad[..]), Err(ParseError::UnsupportedHardwareType(6)));
+
+        let mut bad = REQUEST;
+        bad[2..4].copy_from_slice(&[0x86, 0xDD]);
+        assert_eq!(Arp::parse(&bad[..]), Err(ParseError::UnsupportedProtocolType(EtherType::IPV6)));
+
+        let mut bad = REQUEST;
+        bad[5] = 16;
+        assert_eq!(Arp::parse(&bad[..]), Err(ParseError::InvalidAddressLength { hardware: 6, protocol: 16 }));
+    }
+
+    #[test]
+    fn reply() {
+        let request = Arp::parse(&REQUEST[..]).unwrap().payload;
+        let reply = request.reply_to(BOB).unwrap();
+        assert_eq!(reply.operation, Operation::REPLY);
+        assert_eq!(reply.sender_hardware, BOB);
+        assert_eq!(reply.sender_protocol, Ipv4Addr::new(192, 168, 0, 2));
+        assert_eq!(reply.target_hardware, ALICE);
+        assert_eq!(reply.target_protocol, Ipv4Addr::new(192, 168, 0, 1));
+        assert_eq!(reply.destination(), ALICE);
+        assert_eq!(reply.reply_to(BOB), None);
+    }
+
+    #[test]
+    fn gratuitous_and_probe() {
+        let gratuitous = Arp::gratuitous(ALICE, Ipv4Addr::new(10, 0, 0, 1));
+        assert!(gratuitous.is_gratuitous());
+        assert!(!gratuitous.is_probe());
+
+        let probe = Arp::probe(ALICE, Ipv4Addr::new(10, 0, 0, 1));
+        assert!(probe.is_probe());
+        assert!(!probe.is_gratuitous());
+    }
+
+    #[test]
+    fn reverse() {
+        let request = Arp::reverse_request(ALICE);
+        assert_eq!(request.ether_type(), EtherType::RARP);
+        assert_eq!(request.reply_to(BOB), None);
+
+        let reply = request.reverse_reply_to(BOB, Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)).unwrap();
+        assert_eq!(reply.operation, Operation::REVERSE_REPLY);
+        assert_eq!(reply.target_hardware, ALICE);
+        assert_eq!(reply.target_protocol, Ipv4Addr::new(10, 0, 0, 2));
+        assert_eq!(reply.destination(), ALICE);
+    }
+
+    #[test]
+    fn in_frame() {
+        let arp = Arp::gratuitous(ALICE, Ipv4Addr::new(10, 0, 0, 1));
+        let frame = Ethernet2::new(arp.destination(), ALICE, vlan::Empty, arp.ether_type(),
+            payload::Padded { payload: arp, padding: payload::ValuePadding::zero(18) });
+
+        let mut buf = [0; 60];
+        assert_eq!(frame.write(&mut buf), Ok(60));
+
+        let parsed = EthernetBase::parse(&buf[..]).unwrap().try_parse_vlan().unwrap().unwrap_ethernet2().try_parse_arp().unwrap();
+        assert_eq!(parsed.source, parsed.payload().payload.sender_hardware);
+        assert_eq!(parsed.payload().payload, arp);
+        assert_eq!(parsed.payload().padding, &[0; 18][..]);
+
+        let mut ipv4 = EthernetBase::parse(&buf[..]).unwrap().try_parse_vlan().unwrap().unwrap_ethernet2();
+        ipv4.etype = EtherType::IPV4;
+        assert_eq!(ipv4.try_parse_arp(), Err(PayloadError::UnexpectedEtherType(EtherType::IPV4)));
+    }
+}
@@ -0,0 +1,207 @@
+//! Decoding of network-layer payloads by their ethertype
+//!
+//! Payloads of known ethertypes are parsed into a [`Payload`](enum.Payload.html) value. Downstream crates can decode their own
+//! ethertypes by implementing [`Extension`](trait.Extension.html) and using the `_with` variants of the dispatch functions.
+
+use crate::link::{arp, lldp, mpls, pppoe, slow};
+use crate::link::ethernet::EtherType;
+use crate::network::{ipv4, ipv6};
+use crate::payload;
+
+use core::convert::Infallible;
+use core::fmt;
+
+/// A parser for payloads of ethertypes unknown to this crate
+pub trait Extension<'a>: Sized {
+    /// The error returned when a payload of a handled ethertype is malformed
+    type Error;
+
+    /// Parses a payload with the specified ethertype, returning `None` if the ethertype isn't handled by this extension.
+    /// Extensions are only used for ethertypes that aren't handled by this crate.
+    fn parse(etype: EtherType, payload: payload::Unknown<'a>) -> Option<Result<Self, Self::Error>>;
+}
+
+/// No extension, which doesn't handle any ethertypes
+impl Extension<'_> for Infallible {
+    type Error = Infallible;
+
+    fn parse(_: EtherType, _: payload::Unknown) -> Option<Result<Self, Self::Error>> {
+        None
+    }
+}
+
+/// An error that occurs while parsing a payload identified by its ethertype
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError<E = Infallible> {
+    /// The payload was a malformed IPv4 packet
+    Ipv4(ipv4::ParseError),
+    /// The payload was a malformed IPv6 packet
+    Ipv6(ipv6::ParseError),
+    /// The payload was a malformed ARP or RARP packet
+    Arp(arp::ParseError),
+    /// The payload was a malformed LLDPDU
+    Lldp(lldp::ParseError),
+    /// The payload was a malformed MPLS packet
+    Mpls(mpls::ParseError),
+    /// The payload was a malformed PPPoE discovery or session stage frame
+    Pppoe(pppoe::ParseError),
+    /// The payload was a malformed Slow Protocols PDU
+    Slow(slow::ParseError),
+    /// The payload was rejected by an extension
+    Extension(E)
+}
+
+impl<E: fmt::Display> fmt::Display for ParseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Ipv4(e) => e.fmt(f),
+            ParseError::Ipv6(e) => e.fmt(f),
+            ParseError::Arp(e) => e.fmt(f),
+            ParseError::Lldp(e) => e.fmt(f),
+            ParseError::Mpls(e) => e.fmt(f),
+            ParseError::Pppoe(e) => e.fmt(f),
+            ParseError::Slow(e) => e.fmt(f),
+            ParseError::Extension(e) => e.fmt(f),
+        }
+    }
+}
+
+/// A network-layer payload decoded by its ethertype
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Payload<'a, X = Infallible> {
+    /// An IPv4 packet
+    Ipv4(ipv4::Parsed<'a>),
+    /// An IPv6 packet
+    Ipv6(ipv6::Parsed<'a>),
+    /// An ARP or RARP packet
+    Arp(arp::Parsed<'a>),
+    /// An MPLS packet with an unparsed label stack, using either the unicast or multicast ethertype
+    Mpls(EtherType, mpls::Parsed<'a>),
+    /// An LLDPDU with unparsed TLVs
+    Lldp(lldp::Parsed<'a>),
+    /// A PPPoE discovery stage frame with unparsed tags
+    PppoeDiscovery(pppoe::Discovery<'a>),
+    /// A PPPoE session stage frame carrying a PPP frame parsed by its protocol
+    PppoeSession(pppoe::Session<'a>),
+    /// A Slow Protocols PDU, such as an LACPDU
+    Slow(slow::Parsed<'a>),
+    /// A payload parsed by an extension
+    Extension(X),
+    /// A payload with an ethertype that isn't handled by this crate or the extension
+    Unknown(EtherType, payload::Unknown<'a>)
+}
+
+impl<'a> Payload<'a> {
+    /// Parses a payload with the specified ethertype
+    pub fn parse<P: Into<payload::Unknown<'a>>>(etype: EtherType, payload: P) -> Result<Self, ParseError> {
+        Self::parse_with(etype, payload)
+    }
+}
+
+impl<'a, X: Extension<'a>> Payload<'a, X> {
+    /// Parses a payload with the specified ethertype, using the extension for ethertypes that aren't handled by this crate
+    pub fn parse_with<P: Into<payload::Unknown<'a>>>(etype: EtherType, payload: P) -> Result<Self, ParseError<X::Error>> {
+        let payload = payload.into();
+        match etype {
+            EtherType::IPV4 => ipv4::Parsed::parse(payload).map(Payload::Ipv4).map_err(ParseError::Ipv4),
+            EtherType::IPV6 => ipv6::Parsed::parse(payload).map(Payload::Ipv6).map_err(ParseError::Ipv6),
+            EtherType::ARP | EtherType::RARP => arp::Arp::parse(payload).map(Payload::Arp).map_err(ParseError::Arp),
+            EtherType::MPLS | EtherType::MPLS_MULTICAST => mpls::Parsed::parse(payload).map(|mpls| Payload::Mpls(etype, mpls)).map_err(ParseError::Mpls),
+            EtherType::LLDP => lldp::Parsed::parse(payload).map(Payload::Lldp).map_err(ParseError::Lldp),
+            EtherType::PPPOE_DISCOVERY => pppoe::Parsed::parse(payload).and_then(pppoe::Parsed::try_parse_discovery)
+                .map(Payload::PppoeDiscovery).map_err(ParseError::Pppoe),
+            EtherType::PPPOE_SESSION => pppoe::Parsed::parse(payload).and_then(pppoe::Parsed::try_parse_session)
+                .map(Payload::PppoeSession).map_err(ParseError::Pppoe),
+            EtherType::SLOW_PROTOCOLS => slow::Parsed::parse(payload).map(Payload::Slow).map_err(ParseError::Slow),
+            _ => match X::parse(etype, payload) {
+                Some(result) => result.map(Payload::Extension).map_err(ParseError::Extension),
+                None => Ok(Payload::Unknown(etype, payload)),
+            },
+        }
+    }
+}
+
+impl<X> Payload<'_, X> {
+    /// Gets the ethertype of the payload, or `None` for payloads parsed by an extension
+    pub fn ether_type(&self) -> Option<EtherType> {
+        match self {
+            Payload::Ipv4(_) => Some(EtherType::IPV4),
+            Payload::Ipv6(_) => Some(EtherType::IPV6),
+            Payload::Arp(arp) => Some(arp.payload.ether_type()),
+            Payload::Mpls(etype, _) => Some(*etype),
+            Payload::Lldp(_) => Some(EtherType::LLDP),
+            Payload::PppoeDiscovery(_) => Some(EtherType::PPPOE_DISCOVERY),
+            Payload::PppoeSession(_) => Some(EtherType::PPPOE_SESSION),
+            Payload::Slow(_) => Some(EtherType::SLOW_PROTOCOLS),
+            Payload::Extension(_) => None,
+            Payload::Unknown(etype, _) => Some(*etype),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{vlan, Ethernet2, EthernetBase};
+    use crate::physical::Write;
+    use macress::{Address, Eui48};
+
+    const EXPERIMENTAL: EtherType = EtherType(0x88B5);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Experimental(u8);
+
+    impl<'a> Extension<'a> for Experimental {
+        type Error = ();
+
+        fn parse(etype: EtherType, payload: payload::Unknown<'a>) -> Option<Result<Self, ()>> {
+            if etype != EXPERIMENTAL {
+                return None;
+            }
+            Some(payload.first().map(|&b| Experimental(b)).ok_or(()))
+        }
+    }
+
+    fn frame(etype: EtherType, payload: &[u8], buf: &mut [u8]) -> usize {
+        let source: Address<Eui48> = Address::new([0x02, 0, 0, 0, 0, 1]);
+        Ethernet2::new(Address::<Eui48>::BROADCAST, source, vlan::Empty, etype, payload::Any(payload)).write(buf).unwrap()
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut buf = [0; 64];
+        let arp = arp::Arp::request(Address::new([0x02, 0, 0, 0, 0, 1]), [192, 0, 2, 1].into(), [192, 0, 2, 2].into());
+        let mut packet = [0; 28];
+        arp.write(&mut packet).unwrap();
+
+        let len = frame(EtherType::ARP, &packet, &mut buf);
+        let parsed = EthernetBase::parse(&buf[..len]).unwrap().parse_vlan().unwrap_ethernet2().parse_payload().unwrap();
+        match parsed.payload() {
+            Payload::Arp(parsed) => assert_eq!(parsed.payload, arp),
+            other => panic!("expected an ARP packet, got {:?}", other),
+        }
+        assert_eq!(parsed.payload().ether_type(), Some(EtherType::ARP));
+
+        let len = frame(EtherType::IPV4, &packet, &mut buf);
+        let error = EthernetBase::parse(&buf[..len]).unwrap().parse_vlan().unwrap_ethernet2().parse_payload().unwrap_err();
+        assert!(matches!(error, ParseError::Ipv4(_)));
+
+        let error = Payload::parse(EtherType::LLDP, &packet[..]).unwrap_err();
+        assert_eq!(error, ParseError::Lldp(lldp::ParseError::MissingTlv(lldp::Tlv::CHASSIS_ID)));
+        let payload = Payload::parse(EXPERIMENTAL, &packet[..]).unwrap();
+        assert_eq!(payload, Payload::Unknown(EXPERIMENTAL, payload::Unknown(&packet)));
+    }
+
+    #[test]
+    fn extension() {
+        let mut buf = [0; 64];
+        let len = frame(EXPERIMENTAL, &[7, 8, 9], &mut buf);
+        let parsed = EthernetBase::parse(&buf[..len]).unwrap().parse_vlan().unwrap_ethernet2().parse_payload_with::<Experimental>().unwrap();
+        assert_eq!(parsed.payload(), &Payload::Extension(Experimental(7)));
+        assert_eq!(parsed.payload().ether_type(), None);
+
+        assert_eq!(Payload::<Experimental>::parse_with(EXPERIMENTAL, &[][..]), Err(ParseError::Extension(())));
+        let unknown = Payload::<Experimental>::parse_with(EtherType(0x1234), &[1][..]).unwrap();
+        assert_eq!(unknown, Payload::Unknown(EtherType(0x1234), payload::Unknown(&[1])));
+    }
+}
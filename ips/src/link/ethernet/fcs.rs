@@ -0,0 +1,239 @@
+//! The frame check sequence of ethernet frames, a CRC-32 of the entire frame excluding the preamble
+//!
+//! The CRC is computed 8 bytes at a time using slicing-by-8 lookup tables built at compile time.
+
+use crate::internal::Sealed;
+use crate::payload;
+use crate::physical::{self, Size, Write};
+
+use core::convert::TryFrom;
+
+/// The length of a frame check sequence
+pub const FCS_LEN: usize = 4;
+
+/// The reversed CRC-32 polynomial used by IEEE 802.3
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// The CRC of any frame followed by its frame check sequence, before the final complement
+pub const RESIDUE: u32 = 0xDEBB_20E3;
+
+static TABLES: [[u32; 256]; 8] = tables();
+
+const fn tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < 256 {
+        let mut table = 1;
+        while table < 8 {
+            let previous = tables[table - 1][i];
+            tables[table][i] = (previous >> 8) ^ tables[0][(previous & 0xFF) as usize];
+            table += 1;
+        }
+        i += 1;
+    }
+    tables
+}
+
+/// An incremental CRC-32 calculation
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Crc32 {
+    state: u32
+}
+
+impl Crc32 {
+    /// Creates a new CRC calculation with no data
+    pub const fn new() -> Self {
+        Crc32 { state: !0 }
+    }
+
+    /// Adds a slice of bytes to the CRC
+    pub fn add_bytes(&mut self, data: &[u8]) -> &mut Self {
+        let mut crc = self.state;
+        let mut chunks = data.chunks_exact(8);
+        for chunk in chunks.by_ref() {
+            let [a, b, c, d, e, f, g, h] = <[u8; 8]>::try_from(chunk).unwrap();
+            let [a, b, c, d] = (crc ^ u32::from_le_bytes([a, b, c, d])).to_le_bytes();
+            crc = TABLES[7][a as usize] ^ TABLES[6][b as usize] ^ TABLES[5][c as usize] ^ TABLES[4][d as usize] ^
+                TABLES[3][e as usize] ^ TABLES[2][f as usize] ^ TABLES[1][g as usize] ^ TABLES[0][h as usize];
+        }
+        for &byte in chunks.remainder() {
+            crc = (crc >> 8) ^ TABLES[0][((crc ^ byte as u32) & 0xFF) as usize];
+        }
+        self.state = crc;
+        self
+    }
+
+    /// Gets the CRC of all data added to the calculation
+    pub fn finish(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
+
+/// Computes the CRC-32 of a slice of bytes
+pub fn compute(data: &[u8]) -> u32 {
+    Crc32::new().add_bytes(data).finish()
+}
+
+/// Verifies the frame check sequence at the end of a frame
+pub fn verify(frame: &[u8]) -> bool {
+    frame.len() >= FCS_LEN && !compute(frame) == RESIDUE
+}
+
+/// A frame followed by a frame check sequence
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WithFcs<F> {
+    /// The frame covered by the frame check sequence
+    pub frame: F,
+    /// The frame check sequence, which is transmitted least significant byte first
+    pub fcs: u32
+}
+
+impl<F> WithFcs<F> {
+    /// Creates a new frame with a frame check sequence of zero.
+    /// Use [`write_with_fcs`](#method.write_with_fcs) to write the frame with the correct frame check sequence.
+    pub fn new(frame: F) -> Self {
+        WithFcs { frame, fcs: 0 }
+    }
+    /// Maps the frame to a new value, keeping the frame check sequence
+    pub fn map_frame<G, M: FnOnce(F) -> G>(self, f: M) -> WithFcs<G> {
+        WithFcs { frame: f(self.frame), fcs: self.fcs }
+    }
+    /// Maps the frame to a new value with a fallible function, keeping the frame check sequence
+    pub fn try_map_frame<G, E, M: FnOnce(F) -> Result<G, E>>(self, f: M) -> Result<WithFcs<G>, E> {
+        Ok(WithFcs { frame: f(self.frame)?, fcs: self.fcs })
+    }
+    /// Writes the frame to the output followed by the frame check sequence of the data written,
+    /// ignoring the current value of the [`fcs`](#structfield.fcs) field
+    pub fn write_with_fcs(&self, output: &mut [u8]) -> Result<usize, physical::Error> where F: Write {
+        let written = self.write(output)?;
+        let end = written - FCS_LEN;
+        let fcs = compute(&output[..end]);
+        output[end..written].copy_from_slice(&fcs.to_le_bytes());
+        Ok(written)
+    }
+}
+
+impl<'a> WithFcs<payload::Unknown<'a>> {
+    /// Splits a frame check sequence from the end of a slice of bytes, returning an error if the slice is shorter than the frame check sequence.
+    /// The frame is left unparsed so the frame check sequence can be verified before the frame is parsed.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, physical::Error> {
+        let bytes = payload.into().0;
+        let split = bytes.len().checked_sub(FCS_LEN).ok_or(physical::Error)?;
+        let (frame, fcs) = bytes.split_at(split);
+        Ok(WithFcs {
+            frame: payload::Unknown(frame),
+            fcs: u32::from_le_bytes(<[u8; 4]>::try_from(fcs).unwrap())
+        })
+    }
+
+    /// Computes the frame check sequence of the frame, ignoring the current value of the [`fcs`](#structfield.fcs) field
+    pub fn compute_fcs(&self) -> u32 {
+        compute(self.frame.0)
+    }
+
+    /// Returns whether the [`fcs`](#structfield.fcs) field matches the frame check sequence of the frame
+    pub fn verify_fcs(&self) -> bool {
+        self.fcs == self.compute_fcs()
+    }
+}
+
+impl<F> Sealed for WithFcs<F> { }
+impl<F: Size> Size for WithFcs<F> {
+    fn size(&self) -> usize {
+        self.frame.size() + FCS_LEN
+    }
+}
+/// Writes the frame followed by the frame check sequence field as-is
+impl<F: Write> Write for WithFcs<F> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let written = self.frame.write(output)?;
+        output.get_mut(written..written + FCS_LEN).ok_or(physical::Error)?.copy_from_slice(&self.fcs.to_le_bytes());
+        Ok(written + FCS_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{EtherType, Ethernet2, EthernetBase};
+    use crate::link::ethernet::vlan;
+    use macress::{Address, Eui48};
+
+    fn naive(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+            }
+        }
+        !crc
+    }
+
+    #[test]
+    fn check_value() {
+        assert_eq!(compute(b"123456789"), 0xCBF4_3926);
+        assert_eq!(compute(&[]), 0);
+    }
+
+    #[test]
+    fn compute_matches_naive() {
+        let data: [u8; 67] = core::array::from_fn(|i| (i as u8).wrapping_mul(151).wrapping_add(7));
+        for len in 0..data.len() {
+            assert_eq!(compute(&data[..len]), naive(&data[..len]), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn incremental() {
+        let data: [u8; 31] = core::array::from_fn(|i| (i as u8).wrapping_mul(73));
+        for split in 0..data.len() {
+            let (a, b) = data.split_at(split);
+            assert_eq!(Crc32::new().add_bytes(a).add_bytes(b).finish(), compute(&data), "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn write_and_verify() {
+        let source: Address<Eui48> = Address::new([0x02, 0, 0, 0, 0, 1]);
+        let frame = Ethernet2::new(Address::<Eui48>::BROADCAST, source, vlan::Empty, EtherType::ARP, payload::ValuePadding::zero(46));
+        let frame = WithFcs::new(frame);
+        assert_eq!(frame.size(), 64);
+
+        let mut buf = [0; 64];
+        assert_eq!(frame.write_with_fcs(&mut buf), Ok(64));
+        assert!(verify(&buf));
+        assert_eq!(&buf[60..], &compute(&buf[..60]).to_le_bytes());
+
+        let parsed = WithFcs::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_fcs());
+        let parsed = parsed.try_map_frame(EthernetBase::parse).unwrap();
+        assert_eq!(parsed.frame.source, source);
+        assert_eq!(parsed.frame.payload().len(), 46);
+
+        buf[20] ^= 1;
+        assert!(!verify(&buf));
+        assert!(!WithFcs::parse(&buf[..]).unwrap().verify_fcs());
+
+        assert_eq!(WithFcs::parse(&buf[..3]), Err(physical::Error));
+        assert_eq!(frame.write_with_fcs(&mut buf[..63]), Err(physical::Error));
+    }
+}
@@ -1,11 +1,18 @@
 //! An Ethernet frame types module
 
+pub mod dispatch;
+pub mod fcs;
 pub mod vlan;
 
+use crate::internal::Sealed;
+use crate::link::{arp, llc};
+use crate::network::{ipv4, ipv6};
 use crate::payload;
-use crate::physical;
+use crate::physical::{self, Cursor, Read, Size, Write};
 
 use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
 use macress::{Address, Eui48};
 
 type MacAddr = Address<Eui48>;
@@ -19,6 +26,54 @@ pub const MAX_JUMBO_PAYLOAD_LEN: usize = 9000;
 
 pub struct InvalidLengthError(());
 
+/// An error that occurs while classifying a frame as an Ethernet II or IEEE 802.3 frame
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClassifyError {
+    /// The VLAN extension of the frame was malformed or truncated
+    Vlan(vlan::ParseError),
+    /// The type or length field was between the largest length and the smallest ethertype
+    InvalidTypeOrLength(u16),
+    /// The length field was longer than the payload of the frame
+    LengthOutOfBounds(u16)
+}
+
+impl fmt::Display for ClassifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassifyError::Vlan(e) => e.fmt(f),
+            ClassifyError::InvalidTypeOrLength(value) => write!(f, "bad ethernet frame; type or length {:#06X} is neither a length nor an ethertype", value),
+            ClassifyError::LengthOutOfBounds(len) => write!(f, "bad ethernet frame; length {} extends past the end of the frame", len),
+        }
+    }
+}
+
+/// An error that occurs while parsing the payload of a frame as a specific protocol
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PayloadError<E> {
+    /// The ethertype of the frame wasn't an ethertype of the protocol
+    UnexpectedEtherType(EtherType),
+    /// The payload was a malformed packet of the protocol
+    Malformed(E)
+}
+
+impl<E: fmt::Display> fmt::Display for PayloadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PayloadError::UnexpectedEtherType(etype) => write!(f, "bad ethernet payload; unexpected ethertype {}", etype),
+            PayloadError::Malformed(e) => e.fmt(f),
+        }
+    }
+}
+
+/// An ethernet frame classified by its type or length field
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Frame<'a, V> {
+    /// An IEEE 802.3 frame with a payload length field, where any data after the payload is padding
+    Ethernet(Ethernet<V, payload::Padded<&'a [u8], payload::Any<'a>>>),
+    /// An Ethernet II frame with an ethertype field
+    Ethernet2(Ethernet2<V, payload::Unknown<'a>>)
+}
+
 /// A base ethernet packet that can be used to differentiate
 /// between ethernet frames with a length and frames with an ethertype
 #[derive(Clone, Debug)]
@@ -35,6 +90,14 @@ pub struct EthernetBase<V, P> {
 }
 
 impl<V, P> EthernetBase<V, P> {
+    /// Creates a new base ethernet packet with the specified type or length field and payload
+    pub fn new(destination: MacAddr, source: MacAddr, vlan: V, type_or_length: u16, payload: P) -> Self {
+        EthernetBase { destination, source, vlan, type_or_length, payload }
+    }
+    /// Gets a reference to the payload of this packet
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
     /// Returns the [`type_or_length`](#structfield.type_or_length) field as a payload length
     pub fn length(&self) -> u16 {
         self.type_or_length
@@ -122,6 +185,30 @@ impl<'a> EthernetBase<vlan::Unknown, payload::Unknown<'a>> {
         Self::consume_unknown(payload.into())
     }
 
+    /// Parses the VLAN extension of this packet, returning a packet with [`Any`](vlan/enum.Any.html) VLAN extension value.
+    /// The type or length field of the new packet is the field following the VLAN extension.
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if the VLAN extension is malformed or truncated. See [`try_parse_vlan`](#method.try_parse_vlan) for a non-panicking version.
+    pub fn parse_vlan(self) -> EthernetBase<vlan::Any<'a>, payload::Unknown<'a>> {
+        self.map_vlan(|type_or_length, _, payload| vlan::Any::parse(EtherType(type_or_length), payload))
+    }
+
+    /// Parses the VLAN extension of this packet, returning a packet with [`Any`](vlan/enum.Any.html) VLAN extension value.
+    /// The type or length field of the new packet is the field following the VLAN extension.
+    /// 
+    /// If the VLAN extension is malformed or truncated, this returns an error describing the problem.
+    pub fn try_parse_vlan(self) -> Result<EthernetBase<vlan::Any<'a>, payload::Unknown<'a>>, vlan::ParseError> {
+        self.try_map_vlan(|type_or_length, _, payload| vlan::Any::try_parse(EtherType(type_or_length), payload))
+    }
+
+    /// Parses the VLAN extension of this packet and classifies it by the type or length field following the extension.
+    /// Values of 1500 or less are lengths, values of 0x0600 or more are ethertypes, and any other value is an error.
+    pub fn classify(self) -> Result<Frame<'a, vlan::Any<'a>>, ClassifyError> {
+        self.try_parse_vlan().map_err(ClassifyError::Vlan)?.classify()
+    }
+
     unsafe fn consume_unknown(bytes: payload::Unknown<'a>) -> Self {
         let dest = MacAddr::new(<[u8; 6]>::try_from(bytes.get_unchecked(0..6)).unwrap());
         let src = MacAddr::new(<[u8; 6]>::try_from(bytes.get_unchecked(6..12)).unwrap());
@@ -137,6 +224,30 @@ impl<'a> EthernetBase<vlan::Unknown, payload::Unknown<'a>> {
     }
 }
 
+impl<V, P> Sealed for EthernetBase<V, P> { }
+impl<V: Size, P: Size> Size for EthernetBase<V, P> {
+    fn size(&self) -> usize {
+        14 + self.vlan.size() + self.payload.size()
+    }
+}
+impl<V: Write, P: Write> Write for EthernetBase<V, P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.destination)?;
+        cursor.write(&self.source)?;
+        cursor.write(&self.vlan)?;
+        cursor.write(&self.type_or_length)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+/// Reads the frame using [`parse`](#method.parse)
+impl<'a> Read<'a> for EthernetBase<vlan::Unknown, payload::Unknown<'a>> {
+    fn read(input: &'a [u8]) -> Result<Self, physical::Error> {
+        Self::parse(input)
+    }
+}
+
 impl<'a, V: vlan::Extension> EthernetBase<V, payload::Unknown<'a>> {
     /// Consumes this base packet, turning it into an Ethernet packet.
     /// This interprets the type or length field as a length, making the payload any padded payload value.
@@ -164,6 +275,20 @@ impl<'a, V: vlan::Extension> EthernetBase<V, payload::Unknown<'a>> {
         })
     }
 
+    /// Classifies this packet as an IEEE 802.3 frame or an Ethernet II frame. Type or length values of
+    /// [`MAX_LENGTH`](struct.EtherType.html#associatedconstant.MAX_LENGTH) or less are lengths and values of
+    /// [`MIN_TYPE`](struct.EtherType.html#associatedconstant.MIN_TYPE) or more are ethertypes. Any other value is an error.
+    pub fn classify(self) -> Result<Frame<'a, V>, ClassifyError> {
+        let value = EtherType(self.type_or_length);
+        if value.is_type() {
+            Ok(Frame::Ethernet2(self.unwrap_ethernet2()))
+        } else if value.is_length() {
+            self.try_unrwap_ethernet().map(Frame::Ethernet).map_err(|_| ClassifyError::LengthOutOfBounds(value.0))
+        } else {
+            Err(ClassifyError::InvalidTypeOrLength(value.0))
+        }
+    }
+
     /// Consumes this base packet, turning it into an Ethernet2 packet.
     /// This interprets the type or length field as a ethertype, leaving the payload in-place as unknown.
     pub fn unwrap_ethernet2(self) -> Ethernet2<V, payload::Unknown<'a>> {
@@ -191,6 +316,14 @@ pub struct Ethernet<V, P> {
 }
 
 impl<V, P> Ethernet<V, P> {
+    /// Creates a new ethernet frame with the specified payload
+    pub fn new(destination: MacAddr, source: MacAddr, vlan: V, payload: P) -> Self {
+        Ethernet { destination, source, vlan, payload }
+    }
+    /// Gets a reference to the payload of this frame
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
     /// Creates a VLAN header from the payload and the old header.
     pub fn map_vlan<W, Q, F: FnOnce(V, P) -> (W, Q)>(self, f: F) -> Ethernet<W, Q> {
         let Ethernet {
@@ -269,17 +402,257 @@ impl<V, P> Ethernet<V, P> {
     }
 }
 
+impl<'a, V> Ethernet<V, payload::Padded<&'a [u8], payload::Any<'a>>> {
+    /// Parses the payload of this frame as an LLC PDU, leaving the padding in place
+    pub fn try_parse_llc(self) -> Result<Ethernet<V, payload::Padded<&'a [u8], llc::Parsed<'a>>>, llc::ParseError> {
+        self.try_map_payload(|payload::Padded { payload, padding }| Ok(payload::Padded { payload: llc::Llc::parse(payload)?, padding }))
+    }
+}
+
+impl<V, P> Sealed for Ethernet<V, P> { }
+impl<V: Size, P: Size> Size for Ethernet<V, P> {
+    fn size(&self) -> usize {
+        14 + self.vlan.size() + self.payload.size()
+    }
+}
+/// Writes the frame with a length field containing the size of the payload, excluding any padding
+impl<V: Write, Q: Write, T: Write> Write for Ethernet<V, payload::Padded<Q, T>> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let length = u16::try_from(self.payload.payload.size()).map_err(|_| physical::Error)?;
+
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.destination)?;
+        cursor.write(&self.source)?;
+        cursor.write(&self.vlan)?;
+        cursor.write(&length)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
 /// A double octet EtherType value
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct EtherType(pub u16);
 
 impl EtherType {
     /// The ethertype used for IPv4 protocol payloads
-    pub const IPV4: EtherType = EtherType(0x8000);
+    pub const IPV4: EtherType = EtherType(0x0800);
+    /// The ethertype used for ARP payloads
+    pub const ARP: EtherType = EtherType(0x0806);
+    /// The ethertype used for Wake-on-LAN magic packets
+    pub const WAKE_ON_LAN: EtherType = EtherType(0x0842);
+    /// The ethertype used for IEEE 1722 audio video transport protocol payloads
+    pub const AVTP: EtherType = EtherType(0x22F0);
+    /// The ethertype used for TRILL frames
+    pub const TRILL: EtherType = EtherType(0x22F3);
+    /// The ethertype used for DECnet Phase IV payloads
+    pub const DECNET: EtherType = EtherType(0x6003);
+    /// The ethertype used for RARP payloads
+    pub const RARP: EtherType = EtherType(0x8035);
+    /// The ethertype used for AppleTalk payloads
+    pub const APPLETALK: EtherType = EtherType(0x809B);
+    /// The ethertype used for AppleTalk ARP payloads
+    pub const AARP: EtherType = EtherType(0x80F3);
     /// An ethertype used to signal that this ethernet frame is using a single VLAN extension field.
     pub const DOT1Q: EtherType = EtherType(0x8100);
+    /// The ethertype used for IPX payloads
+    pub const IPX: EtherType = EtherType(0x8137);
+    /// The ethertype used for IPv6 protocol payloads
+    pub const IPV6: EtherType = EtherType(0x86DD);
+    /// The ethertype used for ethernet flow control frames, such as pause frames
+    pub const FLOW_CONTROL: EtherType = EtherType(0x8808);
+    /// The ethertype used for IEEE 802.3 slow protocols, such as LACP
+    pub const SLOW_PROTOCOLS: EtherType = EtherType(0x8809);
+    /// The ethertype used for unicast MPLS label stacks
+    pub const MPLS: EtherType = EtherType(0x8847);
+    /// The ethertype used for multicast MPLS label stacks
+    pub const MPLS_MULTICAST: EtherType = EtherType(0x8848);
+    /// The ethertype used for PPPoE discovery stage packets
+    pub const PPPOE_DISCOVERY: EtherType = EtherType(0x8863);
+    /// The ethertype used for PPPoE session stage packets
+    pub const PPPOE_SESSION: EtherType = EtherType(0x8864);
+    /// The ethertype used for IEEE 802.1X EAP over LAN payloads
+    pub const EAPOL: EtherType = EtherType(0x888E);
+    /// The ethertype used for PROFINET payloads
+    pub const PROFINET: EtherType = EtherType(0x8892);
+    /// The ethertype used for ATA over Ethernet payloads
+    pub const ATA_OVER_ETHERNET: EtherType = EtherType(0x88A2);
+    /// The ethertype used for EtherCAT payloads
+    pub const ETHERCAT: EtherType = EtherType(0x88A4);
     /// An ethertype used to signal that this ethernet frame is using a stacked VLAN extension field.
     pub const QINQ: EtherType = EtherType(0x88a8);
+    /// The ethertype used for Ethernet Powerlink payloads
+    pub const POWERLINK: EtherType = EtherType(0x88AB);
+    /// An ethertype reserved for public use in experiments and prototypes
+    pub const LOCAL_EXPERIMENTAL_1: EtherType = EtherType(0x88B5);
+    /// An ethertype reserved for public use in experiments and prototypes
+    pub const LOCAL_EXPERIMENTAL_2: EtherType = EtherType(0x88B6);
+    /// The ethertype used for payloads identified by an organization and a protocol identifier
+    pub const OUI_EXTENDED: EtherType = EtherType(0x88B7);
+    /// The ethertype used for IEC 61850 GOOSE messages
+    pub const GOOSE: EtherType = EtherType(0x88B8);
+    /// The ethertype used for IEC 61850 sampled values
+    pub const SAMPLED_VALUES: EtherType = EtherType(0x88BA);
+    /// The ethertype used for LLDP data units
+    pub const LLDP: EtherType = EtherType(0x88CC);
+    /// The ethertype used for SERCOS III payloads
+    pub const SERCOS_III: EtherType = EtherType(0x88CD);
+    /// The ethertype used for IEC 62439-2 media redundancy protocol payloads
+    pub const MEDIA_REDUNDANCY: EtherType = EtherType(0x88E3);
+    /// The ethertype used for IEEE 802.1AE MAC security payloads
+    pub const MACSEC: EtherType = EtherType(0x88E5);
+    /// The ethertype used for IEEE 802.1ah provider backbone bridge service instance tags
+    pub const PBB: EtherType = EtherType(0x88E7);
+    /// The ethertype used for IEEE 1588 precision time protocol messages
+    pub const PTP: EtherType = EtherType(0x88F7);
+    /// The ethertype used for network controller sideband interface payloads
+    pub const NCSI: EtherType = EtherType(0x88F8);
+    /// The ethertype used for IEC 62439-3 parallel redundancy protocol supervision frames
+    pub const PRP: EtherType = EtherType(0x88FB);
+    /// The ethertype used for IEEE 802.1ag connectivity fault management messages
+    pub const CFM: EtherType = EtherType(0x8902);
+    /// The ethertype used for Fibre Channel over Ethernet payloads
+    pub const FCOE: EtherType = EtherType(0x8906);
+    /// The ethertype used for FCoE initialization protocol payloads
+    pub const FCOE_INITIALIZATION: EtherType = EtherType(0x8914);
+    /// The ethertype used for RDMA over Converged Ethernet payloads
+    pub const ROCE: EtherType = EtherType(0x8915);
+    /// The ethertype used for TTEthernet protocol control frames
+    pub const TTETHERNET: EtherType = EtherType(0x891D);
+    /// The ethertype used for IEC 62439-3 high-availability seamless redundancy payloads
+    pub const HSR: EtherType = EtherType(0x892F);
+    /// The ethertype used for IEEE 1905.1 convergent digital home network payloads
+    pub const IEEE1905: EtherType = EtherType(0x893A);
+    /// The ethertype used for ethernet configuration testing protocol loopback frames
+    pub const LOOPBACK: EtherType = EtherType(0x9000);
+
+    /// The largest value of the type or length field that's interpreted as a payload length
+    pub const MAX_LENGTH: u16 = 1500;
+    /// The smallest value of the type or length field that's interpreted as an ethertype
+    pub const MIN_TYPE: u16 = 0x0600;
+
+    /// Returns whether this value is a payload length rather than an ethertype
+    pub const fn is_length(self) -> bool {
+        self.0 <= Self::MAX_LENGTH
+    }
+
+    /// Returns whether this value is an ethertype rather than a payload length
+    pub const fn is_type(self) -> bool {
+        self.0 >= Self::MIN_TYPE
+    }
+
+    /// Gets the name of the ethertype, or `None` if it isn't a known ethertype
+    pub fn name(self) -> Option<&'static str> {
+        REGISTRY.iter().find(|(etype, _)| *etype == self).map(|&(_, name)| name)
+    }
+}
+
+/// The names of known ethertypes
+static REGISTRY: [(EtherType, &str); 45] = [
+    (EtherType::IPV4, "IPv4"),
+    (EtherType::ARP, "ARP"),
+    (EtherType::WAKE_ON_LAN, "Wake-on-LAN"),
+    (EtherType::AVTP, "AVTP"),
+    (EtherType::TRILL, "TRILL"),
+    (EtherType::DECNET, "DECnet"),
+    (EtherType::RARP, "RARP"),
+    (EtherType::APPLETALK, "AppleTalk"),
+    (EtherType::AARP, "AARP"),
+    (EtherType::DOT1Q, "802.1Q"),
+    (EtherType::IPX, "IPX"),
+    (EtherType::IPV6, "IPv6"),
+    (EtherType::FLOW_CONTROL, "Flow Control"),
+    (EtherType::SLOW_PROTOCOLS, "Slow Protocols"),
+    (EtherType::MPLS, "MPLS"),
+    (EtherType::MPLS_MULTICAST, "MPLS Multicast"),
+    (EtherType::PPPOE_DISCOVERY, "PPPoE Discovery"),
+    (EtherType::PPPOE_SESSION, "PPPoE Session"),
+    (EtherType::EAPOL, "EAPOL"),
+    (EtherType::PROFINET, "PROFINET"),
+    (EtherType::ATA_OVER_ETHERNET, "ATA over Ethernet"),
+    (EtherType::ETHERCAT, "EtherCAT"),
+    (EtherType::QINQ, "802.1ad"),
+    (EtherType::POWERLINK, "Ethernet Powerlink"),
+    (EtherType::LOCAL_EXPERIMENTAL_1, "Local Experimental 1"),
+    (EtherType::LOCAL_EXPERIMENTAL_2, "Local Experimental 2"),
+    (EtherType::OUI_EXTENDED, "OUI Extended"),
+    (EtherType::GOOSE, "GOOSE"),
+    (EtherType::SAMPLED_VALUES, "Sampled Values"),
+    (EtherType::LLDP, "LLDP"),
+    (EtherType::SERCOS_III, "SERCOS III"),
+    (EtherType::MEDIA_REDUNDANCY, "MRP"),
+    (EtherType::MACSEC, "MACsec"),
+    (EtherType::PBB, "802.1ah"),
+    (EtherType::PTP, "PTP"),
+    (EtherType::NCSI, "NC-SI"),
+    (EtherType::PRP, "PRP"),
+    (EtherType::CFM, "CFM"),
+    (EtherType::FCOE, "FCoE"),
+    (EtherType::FCOE_INITIALIZATION, "FIP"),
+    (EtherType::ROCE, "RoCE"),
+    (EtherType::TTETHERNET, "TTEthernet"),
+    (EtherType::HSR, "HSR"),
+    (EtherType::IEEE1905, "IEEE 1905.1"),
+    (EtherType::LOOPBACK, "Loopback"),
+];
+
+/// Prints the name of the ethertype followed by its value, or only the value if the ethertype isn't known
+impl fmt::Display for EtherType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{} ({:#06X})", name, self.0),
+            None => write!(f, "{:#06X}", self.0),
+        }
+    }
+}
+impl fmt::Debug for EtherType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// An error returned when parsing an ethertype from a string that isn't a known name or a hexadecimal value
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ParseEtherTypeError(());
+
+impl fmt::Display for ParseEtherTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown ethertype name")
+    }
+}
+
+/// Parses an ethertype from its name, ignoring case, or from a hexadecimal value prefixed with `0x`
+impl FromStr for EtherType {
+    type Err = ParseEtherTypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            // from_str_radix accepts a sign before the digits
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(ParseEtherTypeError(()));
+            }
+            return u16::from_str_radix(hex, 16).map(EtherType).map_err(|_| ParseEtherTypeError(()));
+        }
+        REGISTRY.iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(s))
+            .map(|&(etype, _)| etype)
+            .ok_or(ParseEtherTypeError(()))
+    }
+}
+
+impl Sealed for EtherType { }
+impl Size for EtherType {
+    fn size(&self) -> usize { 2 }
+}
+impl Write for EtherType {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+impl Read<'_> for EtherType {
+    fn read(input: &[u8]) -> Result<Self, physical::Error> {
+        u16::read(input).map(EtherType)
+    }
 }
 
 /// An ethernet frame with a payload ethertype field.
@@ -298,6 +671,14 @@ pub struct Ethernet2<V, P> {
 }
 
 impl<V, P> Ethernet2<V, P> {
+    /// Creates a new ethernet frame with the specified ethertype and payload
+    pub fn new(destination: MacAddr, source: MacAddr, vlan: V, etype: EtherType, payload: P) -> Self {
+        Ethernet2 { destination, source, vlan, etype, payload }
+    }
+    /// Gets a reference to the payload of this frame
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
     /// Creates a VLAN header from the payload and the old header.
     pub fn map_vlan<W, Q, F: FnOnce(V, P) -> (W, Q)>(self, f: F) -> Ethernet2<W, Q> {
         let Ethernet2 {
@@ -381,3 +762,250 @@ impl<V, P> Ethernet2<V, P> {
         Ok(Ethernet2 { destination, source, vlan, etype, payload })
     }
 }
+
+impl<'a, V> Ethernet2<V, payload::Unknown<'a>> {
+    /// Parses the payload of this frame as an IPv4 packet, returning an error if the ethertype isn't [`IPV4`](struct.EtherType.html#associatedconstant.IPV4)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv4(self) -> Result<Ethernet2<V, ipv4::Parsed<'a>>, PayloadError<ipv4::ParseError>> {
+        let etype = self.etype;
+        self.try_map_payload(|payload| parse_ipv4(etype, payload))
+    }
+
+    /// Parses the payload of this frame as an IPv6 packet, returning an error if the ethertype isn't [`IPV6`](struct.EtherType.html#associatedconstant.IPV6)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv6(self) -> Result<Ethernet2<V, ipv6::Parsed<'a>>, PayloadError<ipv6::ParseError>> {
+        let etype = self.etype;
+        self.try_map_payload(|payload| parse_ipv6(etype, payload))
+    }
+
+    /// Parses the payload of this frame as an ARP packet, returning an error if the ethertype isn't [`ARP`](struct.EtherType.html#associatedconstant.ARP)
+    /// or [`RARP`](struct.EtherType.html#associatedconstant.RARP) or the packet is malformed.
+    pub fn try_parse_arp(self) -> Result<Ethernet2<V, arp::Parsed<'a>>, PayloadError<arp::ParseError>> {
+        let etype = self.etype;
+        self.try_map_payload(|payload| parse_arp(etype, payload))
+    }
+
+    /// Parses the payload of this frame by its ethertype, leaving payloads with unknown ethertypes unparsed
+    pub fn parse_payload(self) -> Result<Ethernet2<V, dispatch::Payload<'a>>, dispatch::ParseError> {
+        self.parse_payload_with()
+    }
+
+    /// Parses the payload of this frame by its ethertype, using the extension to parse ethertypes that aren't handled by this crate
+    pub fn parse_payload_with<X: dispatch::Extension<'a>>(self) -> Result<Ethernet2<V, dispatch::Payload<'a, X>>, dispatch::ParseError<X::Error>> {
+        let etype = self.etype;
+        self.try_map_payload(|payload| dispatch::Payload::parse_with(etype, payload))
+    }
+}
+
+/// Parses a payload identified by an ethertype as an IPv4 packet
+pub(crate) fn parse_ipv4(etype: EtherType, payload: payload::Unknown) -> Result<ipv4::Parsed, PayloadError<ipv4::ParseError>> {
+    if etype != EtherType::IPV4 {
+        return Err(PayloadError::UnexpectedEtherType(etype));
+    }
+    ipv4::Parsed::parse(payload).map_err(PayloadError::Malformed)
+}
+
+/// Parses a payload identified by an ethertype as an IPv6 packet
+pub(crate) fn parse_ipv6(etype: EtherType, payload: payload::Unknown) -> Result<ipv6::Parsed, PayloadError<ipv6::ParseError>> {
+    if etype != EtherType::IPV6 {
+        return Err(PayloadError::UnexpectedEtherType(etype));
+    }
+    ipv6::Parsed::parse(payload).map_err(PayloadError::Malformed)
+}
+
+/// Parses a payload identified by an ethertype as an ARP or RARP packet
+pub(crate) fn parse_arp(etype: EtherType, payload: payload::Unknown) -> Result<arp::Parsed, PayloadError<arp::ParseError>> {
+    if etype != EtherType::ARP && etype != EtherType::RARP {
+        return Err(PayloadError::UnexpectedEtherType(etype));
+    }
+    arp::Arp::parse(payload).map_err(PayloadError::Malformed)
+}
+
+impl<V, P> Sealed for Ethernet2<V, P> { }
+impl<V: Size, P: Size> Size for Ethernet2<V, P> {
+    fn size(&self) -> usize {
+        14 + self.vlan.size() + self.payload.size()
+    }
+}
+impl<V: Write, P: Write> Write for Ethernet2<V, P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.destination)?;
+        cursor.write(&self.source)?;
+        cursor.write(&self.vlan)?;
+        cursor.write(&self.etype)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload;
+    use crate::physical::{Read, Size, Write};
+
+    const DEST: MacAddr = MacAddr::new([0x01, 0x23, 0x45, 0x67, 0x89, 0xAB]);
+    const SRC: MacAddr = MacAddr::new([0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67]);
+
+    #[test]
+    fn write_ethernet2() {
+        let frame = Ethernet2::new(DEST, SRC, vlan::Tag::raw(0x2064), EtherType(0x0800), payload::Any(&[1, 2, 3, 4]));
+        let mut buf = [0; 22];
+        assert_eq!(frame.size(), 22);
+        assert_eq!(frame.write(&mut buf), Ok(22));
+        assert_eq!(&buf, &[
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xAB,
+            0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67,
+            0x81, 0x00, 0x20, 0x64,
+            0x08, 0x00,
+            1, 2, 3, 4
+        ]);
+        assert_eq!(frame.write(&mut buf[..21]), Err(physical::Error));
+    }
+
+    #[test]
+    fn write_ethernet_length() {
+        let payload = payload::Padded { payload: payload::Any(&[1, 2, 3]), padding: payload::ValuePadding::zero(3) };
+        let frame = Ethernet::new(DEST, SRC, vlan::Empty, payload);
+        let mut buf = [0xFF; 20];
+        assert_eq!(frame.write(&mut buf), Ok(20));
+        assert_eq!(&buf[12..], &[0x00, 0x03, 1, 2, 3, 0, 0, 0]);
+    }
+
+    #[test]
+    fn read_write_base() {
+        let bytes = [
+            0x01, 0x23, 0x45, 0x67, 0x89, 0xAB,
+            0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67,
+            0x88, 0xB5,
+            1, 2, 3, 4
+        ];
+        let frame = EthernetBase::read(&bytes).unwrap();
+        assert_eq!(frame.destination, DEST);
+        assert_eq!(frame.source, SRC);
+        assert_eq!(frame.payload().0, &[1, 2, 3, 4]);
+        assert_eq!(frame.size(), bytes.len());
+
+        let mut buf = [0; 18];
+        assert_eq!(frame.write(&mut buf), Ok(18));
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn write_stacked() {
+        let vlan = vlan::Stacked { tag: vlan::Tag::raw(0x0064), remainder: vlan::Tag::raw(0x00C8) };
+        let mut buf = [0; 8];
+        assert_eq!(vlan.write(&mut buf), Ok(8));
+        assert_eq!(buf, [0x88, 0xA8, 0x00, 0x64, 0x81, 0x00, 0x00, 0xC8]);
+        assert_eq!(vlan::Tag::read(&buf[4..]), Ok(vlan::Tag::raw(0x00C8)));
+        assert_eq!(vlan::Tag::read(&buf), Err(physical::Error));
+    }
+
+    const TAGGED: [u8; 26] = [
+        0x01, 0x23, 0x45, 0x67, 0x89, 0xAB,
+        0xCD, 0xEF, 0x01, 0x23, 0x45, 0x67,
+        0x88, 0xA8, 0x00, 0x64,
+        0x81, 0x00, 0x00, 0xC8,
+        0x08, 0x00,
+        1, 2, 3, 4
+    ];
+
+    #[test]
+    fn parse_vlan() {
+        let frame = EthernetBase::parse(&TAGGED[..]).unwrap().try_parse_vlan().unwrap();
+        assert_eq!(frame.ethertype(), EtherType(0x0800));
+        assert_eq!(frame.payload().0, &[1, 2, 3, 4]);
+        assert_eq!(frame.size(), TAGGED.len());
+
+        let mut buf = [0; 26];
+        assert_eq!(frame.write(&mut buf), Ok(26));
+        assert_eq!(buf, TAGGED);
+
+        let stacked = frame.vlan.try_unwrap_stack().unwrap();
+        assert_eq!(stacked.tag, vlan::Tag::raw(0x0064));
+        assert_eq!(stacked.remainder.try_unwrap_tag(), Ok(vlan::Tag::raw(0x00C8)));
+        assert_eq!(frame.vlan.try_unwrap_tag(), Err(vlan::ParseError::UnexpectedHeader));
+        assert_eq!(frame.vlan.try_unwrap_empty(), Err(vlan::ParseError::UnexpectedHeader));
+
+        let frame = EthernetBase::parse(&TAGGED[..12]).unwrap_err();
+        assert_eq!(frame, physical::Error);
+        let untagged = EthernetBase::parse(&TAGGED[8..]).unwrap().try_parse_vlan().unwrap();
+        assert_eq!(untagged.vlan.try_unwrap_empty(), Ok(vlan::Empty));
+    }
+
+    #[test]
+    fn classify() {
+        let frame = EthernetBase::parse(&TAGGED[..]).unwrap().classify().unwrap();
+        match frame {
+            Frame::Ethernet2(frame) => {
+                assert_eq!(frame.etype, EtherType::IPV4);
+                assert_eq!(frame.vlan.try_unwrap_stack().map(|stack| stack.tag), Ok(vlan::Tag::raw(0x0064)));
+                assert_eq!(frame.payload().0, &[1, 2, 3, 4]);
+            },
+            other => panic!("expected an Ethernet II frame, got {:?}", other),
+        }
+
+        let mut length = TAGGED;
+        length[20..22].copy_from_slice(&[0x00, 0x03]);
+        match EthernetBase::parse(&length[..]).unwrap().classify().unwrap() {
+            Frame::Ethernet(frame) => {
+                assert_eq!(frame.vlan.try_unwrap_stack().map(|stack| stack.tag), Ok(vlan::Tag::raw(0x0064)));
+                assert_eq!(frame.payload().payload.0, &[1, 2, 3]);
+                assert_eq!(frame.payload().padding, &[4]);
+            },
+            other => panic!("expected an 802.3 frame, got {:?}", other),
+        }
+
+        let untagged = EthernetBase::parse(&length[8..]).unwrap().try_parse_vlan().unwrap();
+        assert!(matches!(untagged.classify(), Ok(Frame::Ethernet(_))));
+
+        length[21] = 5;
+        assert_eq!(EthernetBase::parse(&length[..]).unwrap().classify().unwrap_err(), ClassifyError::LengthOutOfBounds(5));
+        length[20..22].copy_from_slice(&[0x05, 0xFF]);
+        assert_eq!(EthernetBase::parse(&length[..]).unwrap().classify().unwrap_err(), ClassifyError::InvalidTypeOrLength(0x05FF));
+        assert_eq!(EthernetBase::parse(&TAGGED[..21]).unwrap().classify().unwrap_err(), ClassifyError::Vlan(vlan::ParseError::Truncated));
+    }
+
+    #[test]
+    fn ether_type_names() {
+        use alloc::format;
+
+        assert_eq!(format!("{}", EtherType::IPV4), "IPv4 (0x0800)");
+        assert_eq!(format!("{:?}", EtherType::QINQ), "802.1ad (0x88A8)");
+        assert_eq!(format!("{}", EtherType(0x1234)), "0x1234");
+        assert_eq!(EtherType::SLOW_PROTOCOLS.name(), Some("Slow Protocols"));
+
+        assert_eq!("ipv6".parse(), Ok(EtherType::IPV6));
+        assert_eq!("PPPoE Session".parse(), Ok(EtherType::PPPOE_SESSION));
+        assert_eq!("0x88e5".parse(), Ok(EtherType::MACSEC));
+        assert_eq!("IPv5".parse::<EtherType>(), Err(ParseEtherTypeError(())));
+        assert_eq!("0x10000".parse::<EtherType>(), Err(ParseEtherTypeError(())));
+        assert_eq!("0x+10".parse::<EtherType>(), Err(ParseEtherTypeError(())));
+        assert_eq!("0x-10".parse::<EtherType>(), Err(ParseEtherTypeError(())));
+
+        for &(etype, name) in REGISTRY.iter() {
+            assert!(etype.is_type(), "{}", name);
+            assert_eq!(name.parse(), Ok(etype));
+        }
+        assert!(EtherType(1500).is_length() && !EtherType(1500).is_type());
+        assert!(!EtherType(1501).is_length() && !EtherType(0x05FF).is_type());
+        assert!(EtherType(0x0600).is_type());
+    }
+
+    #[test]
+    fn parse_vlan_errors() {
+        let truncated = EthernetBase::parse(&TAGGED[..21]).unwrap().try_parse_vlan();
+        assert_eq!(truncated.unwrap_err(), vlan::ParseError::Truncated);
+
+        let mut bad = TAGGED;
+        bad[16..18].copy_from_slice(&[0x08, 0x00]);
+        let bad_qinq = EthernetBase::parse(&bad[..]).unwrap().try_parse_vlan();
+        assert_eq!(bad_qinq.unwrap_err(), vlan::ParseError::ExpectedDot1Q);
+
+        let mut bad = TAGGED;
+        bad[20..22].copy_from_slice(&[0x81, 0x00]);
+        let trailing = EthernetBase::parse(&bad[..]).unwrap().try_parse_vlan();
+        assert_eq!(trailing.unwrap_err(), vlan::ParseError::TrailingVlan);
+    }
+}
@@ -1,10 +1,12 @@
 //! Contains types for VLAN ethernet header extensions
 
+use crate::internal::Sealed;
 use crate::link::ethernet::EtherType;
 use crate::payload;
-use crate::physical::Size;
+use crate::physical::{self, Cursor, Read, Size, Write};
 
 use core::convert::TryFrom;
+use core::fmt;
 
 /// Defines a VLAN extension extension value.
 /// This may be no header, a standard vlan header, a stacked header, or any unparsed header value.
@@ -21,9 +23,20 @@ pub trait Header: Extension {
 pub struct Empty;
 
 impl Extension for Empty { }
+impl Sealed for Empty { }
 impl Size for Empty {
     fn size(&self) -> usize { 0 }
 }
+impl Write for Empty {
+    fn write(&self, _: &mut [u8]) -> Result<usize, physical::Error> {
+        Ok(0)
+    }
+}
+impl Read<'_> for Empty {
+    fn read(_: &[u8]) -> Result<Self, physical::Error> {
+        Ok(Empty)
+    }
+}
 
 /// Represents the priority level of an ethernet packet.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -38,6 +51,7 @@ pub enum PriorityLevel {
     NetworkControl = 7,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for PriorityLevel {
     fn default() -> Self {
         PriorityLevel::BestEffort
@@ -52,9 +66,28 @@ impl Extension for Tag { }
 impl Header for Tag {
     fn tag(&self) -> &Tag { self }
 }
+impl Sealed for Tag { }
 impl Size for Tag {
     fn size(&self) -> usize { 4 }
 }
+/// Writes the tag preceded by the 802.1Q tag protocol identifier
+impl Write for Tag {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&EtherType::DOT1Q)?;
+        cursor.write(&self.0)?;
+        Ok(cursor.finish())
+    }
+}
+/// Reads a tag preceded by the 802.1Q tag protocol identifier, returning an error if the identifier is missing
+impl Read<'_> for Tag {
+    fn read(input: &[u8]) -> Result<Self, physical::Error> {
+        match EtherType::read(input)? {
+            EtherType::DOT1Q => u16::read(&input[2..]).map(Tag),
+            _ => Err(physical::Error)
+        }
+    }
+}
 
 impl Tag {
     const PCP_MASK: u16 = 0xE000;
@@ -126,6 +159,10 @@ impl Identifier {
         }
     }
     /// Creates a new identifier value without checking that it's in the 12-bit value range
+    /// 
+    /// # Safety
+    /// 
+    /// The value must be in the 12-bit range (`v <= 0xFFF`). Larger values will overwrite the priority and drop eligibility bits of any tag created from this identifier.
     pub const unsafe fn new_unchecked(v: u16) -> Identifier {
         Identifier(v)
     }
@@ -156,11 +193,46 @@ impl<V: Extension> Header for Stacked<V> {
         &self.tag
     }
 }
+impl<V> Sealed for Stacked<V> { }
 impl<V: Size> Size for Stacked<V> {
     fn size(&self) -> usize {
         self.tag.size() + self.remainder.size()
     }
 }
+/// Writes the tag preceded by the QinQ tag protocol identifier, followed by the remainder
+impl<V: Write> Write for Stacked<V> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&EtherType::QINQ)?;
+        cursor.write(&self.tag.0)?;
+        cursor.write(&self.remainder)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// An error that occurs while parsing a VLAN extension
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The payload ended before the end of a VLAN tag or the ethertype or length following it
+    Truncated,
+    /// A QinQ tag was not followed by another QinQ tag or an 802.1Q tag
+    ExpectedDot1Q,
+    /// An 802.1Q tag was followed by another VLAN ethertype instead of the ethertype or length of the payload
+    TrailingVlan,
+    /// The VLAN extension was not the kind of extension requested
+    UnexpectedHeader
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ParseError::Truncated => "bad ethernet payload; expected ethertype or length after VLAN headers, but ran out of data",
+            ParseError::ExpectedDot1Q => "bad VLAN extension; expected DOT1Q after all QinQ headers",
+            ParseError::TrailingVlan => "bad VLAN extension; expected ethertype or length after VLAN DOT1Q header",
+            ParseError::UnexpectedHeader => "unexpected VLAN extension header",
+        })
+    }
+}
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct AnyHeader<'a> {
@@ -169,48 +241,25 @@ pub struct AnyHeader<'a> {
 }
 
 impl<'a> AnyHeader<'a> {
-    fn parse(ethertype: EtherType, payload: payload::Unknown<'a>) -> (u16, Self, payload::Unknown<'a>) {
-        let mut etypes_iter = 
-            payload
-                .chunks(2) // split the payload into 2 value chunks
-                .skip(1) // skip this chunk (since it's the tag of the first)
-                .step_by(2) // skip every other chunk, we're just looking at the ethertypes
-                .map(|c| <[u8; 2]>::try_from(c).map(|slice| EtherType(u16::from_be_bytes(slice)))) // turn each slice into an ethertype
-                .take_while(|result| result.map(|tp| VLAN_EXTENSIONS.contains(&tp)).unwrap_or(false)); // take ethertypes while they're VLAN extensions
-
-        let qinq_headers = // count all QinQ headers
-            etypes_iter
-                .by_ref()
-                .take_while(|result| result.map(|tp| tp == EtherType::QINQ).unwrap_or(false))
-                .count();
-
-        // make sure the next one after QinQ is the single header
-        let trailing_headers = 
-            if qinq_headers != 0 {
-                match etypes_iter.next() {
-                    Some(Ok(EtherType::DOT1Q)) => { },
-                    _ => panic!("bad VLAN extension; expected DOT1Q after all QinQ headers"),
-                }
-
-                qinq_headers + 1
-            } else {
-                0
-            };
-
-        let headers = trailing_headers + 1; // count the first one we were given
-
-        let last = 
-            match etypes_iter.next() {
-                Some(Ok(EtherType::QINQ)) | Some(Ok(EtherType::DOT1Q)) => {
-                    panic!("bad VLAN extension; expected ethertype or length after VLAN DOT1Q header")
-                },
-                Some(Ok(other)) => other,
-                _ => panic!("bad ethernet payload; expected ethertype or length after VLAN headers, but ran out of data") // we never got our final ethertype or length
-            };
-
-        let read_len = (headers * 4) - 2;
-
-        (last.0, AnyHeader { first: ethertype, data: payload::Unknown(&payload.0[..read_len]) }, payload.consume(read_len))
+    fn try_parse(ethertype: EtherType, payload: payload::Unknown<'a>) -> Result<(u16, Self, payload::Unknown<'a>), ParseError> {
+        // each header is a 2 byte tag followed by the 2 byte ethertype of the next header, or the ethertype or length of the payload
+        let mut current = ethertype;
+        let mut read_len = 0;
+        let last = loop {
+            let next = payload.get(read_len + 2..read_len + 4).ok_or(ParseError::Truncated)?;
+            let next = EtherType(u16::from_be_bytes(<[u8; 2]>::try_from(next).unwrap()));
+            read_len += 4;
+
+            match (current, next) {
+                (EtherType::QINQ, EtherType::QINQ) | (EtherType::QINQ, EtherType::DOT1Q) => current = next,
+                (EtherType::QINQ, _) => return Err(ParseError::ExpectedDot1Q),
+                (_, EtherType::QINQ) | (_, EtherType::DOT1Q) => return Err(ParseError::TrailingVlan),
+                (_, other) => break other,
+            }
+        };
+
+        let data = payload::Unknown(&payload.0[..read_len - 2]);
+        Ok((last.0, AnyHeader { first: ethertype, data }, payload.consume(read_len)))
     }
     /// Gets the EtherType of the first VLAN extension header. This is the header furthest to the left in an ethernet header
     pub fn first(&self) -> EtherType {
@@ -225,57 +274,85 @@ pub enum Any<'a> {
     None
 }
 
-const VLAN_EXTENSIONS: [EtherType; 2] = [EtherType::QINQ, EtherType::DOT1Q];
-
 impl<'a> Any<'a> {
     /// Parses any VLAN extension, returning the new ethertype or length, the value, and a new unknown payload that starts after the new type or value field.
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if the VLAN extension is malformed or truncated. See [`try_parse`](#method.try_parse) for a non-panicking version.
     pub fn parse<P: Into<payload::Unknown<'a>>>(ethertype: EtherType, payload: P) -> (u16, Self, payload::Unknown<'a>) {
+        Self::try_parse(ethertype, payload).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Parses any VLAN extension, returning the new ethertype or length, the value, and a new unknown payload that starts after the new type or value field.
+    /// 
+    /// If the VLAN extension is malformed or truncated, this returns an error describing the problem.
+    pub fn try_parse<P: Into<payload::Unknown<'a>>>(ethertype: EtherType, payload: P) -> Result<(u16, Self, payload::Unknown<'a>), ParseError> {
         let payload = payload.into();
         match ethertype {
             EtherType::DOT1Q | EtherType::QINQ => {
-                let (last, hdr, pld) = AnyHeader::parse(ethertype, payload);
-                (last, Any::Some(hdr), pld)
+                let (last, hdr, pld) = AnyHeader::try_parse(ethertype, payload)?;
+                Ok((last, Any::Some(hdr), pld))
             },
-            _ => (ethertype.0, Any::None, payload)
+            _ => Ok((ethertype.0, Any::None, payload))
         }
     }
 
     /// Consumes the value, returning a new Stacked tag where the remainder is the rest of the Any value.
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if the value isn't a stacked header. See [`try_unwrap_stack`](#method.try_unwrap_stack) for a non-panicking version.
     pub fn unwrap_stack(self) -> Stacked<Any<'a>> {
+        self.try_unwrap_stack().unwrap_or_else(|e| panic!("expected stacked VLAN header: {}", e))
+    }
+
+    /// Consumes the value, returning a new Stacked tag where the remainder is the rest of the Any value, 
+    /// or an error if the value isn't a stacked header.
+    pub fn try_unwrap_stack(self) -> Result<Stacked<Any<'a>>, ParseError> {
         match self {
             Any::Some(AnyHeader { first: EtherType::QINQ, data: payload::Unknown(data) }) => {
                 if data.len() < 4 {
-                    panic!("expected at least 4 bytes of stacked VLAN header data")
+                    return Err(ParseError::Truncated);
                 }
                 unsafe {
                     let tag = Tag::raw(u16::from_be_bytes(<[u8; 2]>::try_from(data.get_unchecked(0..2)).unwrap()));
                     let next_type = EtherType(u16::from_be_bytes(<[u8; 2]>::try_from(data.get_unchecked(2..4)).unwrap()));
                     let remainder = data.get_unchecked(4..);
 
-                    Stacked {
+                    Ok(Stacked {
                         tag,
                         remainder: Any::Some(AnyHeader {
                             first: next_type,
                             data: payload::Unknown(remainder)
                         })
-                    }
+                    })
                 }
             },
-            _ => panic!("expected stacked VLAN header")
+            _ => Err(ParseError::UnexpectedHeader)
         }
     }
 
     /// Consumes the value, returning a new single tag
+    /// 
+    /// # Panics
+    /// 
+    /// This function will panic if the value isn't a single tag. See [`try_unwrap_tag`](#method.try_unwrap_tag) for a non-panicking version.
     pub fn unwrap_tag(self) -> Tag {
+        self.try_unwrap_tag().unwrap_or_else(|e| panic!("expected VLAN tag: {}", e))
+    }
+
+    /// Consumes the value, returning a new single tag, or an error if the value isn't a single tag.
+    pub fn try_unwrap_tag(self) -> Result<Tag, ParseError> {
         match self {
             Any::Some(AnyHeader { first: EtherType::DOT1Q, data: payload::Unknown(data) }) => {
-                if data.len() != 2 {
-                    panic!("expected exactly 2 bytes of VLAN header data")
+                match <[u8; 2]>::try_from(data) {
+                    Ok(tag) => Ok(Tag::raw(u16::from_be_bytes(tag))),
+                    Err(_) if data.len() < 2 => Err(ParseError::Truncated),
+                    Err(_) => Err(ParseError::TrailingVlan),
                 }
-
-                Tag::raw(u16::from_be_bytes(<[u8; 2]>::try_from(data).unwrap()))
             },
-            _ => panic!("expected VLAN tag")
+            _ => Err(ParseError::UnexpectedHeader)
         }
     }
 
@@ -283,14 +360,20 @@ impl<'a> Any<'a> {
     /// 
     /// This will panic if a header exists. If you want to remove a VLAN header, drop it and return an Empty header.
     pub fn unwrap_empty(self) -> Empty {
+        self.try_unwrap_empty().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Consumes the value, returning an empty header value, or an error if a header exists.
+    pub fn try_unwrap_empty(self) -> Result<Empty, ParseError> {
         match self {
-            Any::Some(_) => panic!("unexpected vlan header"),
-            Any::None => Empty
+            Any::Some(_) => Err(ParseError::UnexpectedHeader),
+            Any::None => Ok(Empty)
         }
     }
 }
 
 impl Extension for Any<'_> { }
+impl Sealed for Any<'_> { }
 impl Size for Any<'_> {
     fn size(&self) -> usize {
         match self {
@@ -299,7 +382,31 @@ impl Size for Any<'_> {
         }
     }
 }
+impl Write for Any<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        match self {
+            Any::Some(AnyHeader { first, data }) => {
+                let mut cursor = Cursor::new(output);
+                cursor.write(first)?;
+                cursor.write(data)?;
+                Ok(cursor.finish())
+            },
+            Any::None => Ok(0)
+        }
+    }
+}
 
 /// A type used to indicate if it's unknown whether an ethernet frame contains a VLAN header.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct Unknown(pub(super) ());
\ No newline at end of file
+pub struct Unknown(pub(super) ());
+
+impl Sealed for Unknown { }
+/// An unknown VLAN extension is still part of the type or length field and payload, so it has no size of its own
+impl Size for Unknown {
+    fn size(&self) -> usize { 0 }
+}
+impl Write for Unknown {
+    fn write(&self, _: &mut [u8]) -> Result<usize, physical::Error> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,464 @@
+//! Types for reading and writing IEEE 802.2 LLC headers and SNAP extensions
+//!
+//! LLC headers follow the length field of IEEE 802.3 frames. Frames sent to the SNAP service access point have a SNAP
+//! extension after the header, which usually carries an ethertype that can be dispatched like the payload of an
+//! [`Ethernet2`](../ethernet/struct.Ethernet2.html) frame.
+
+use crate::internal::Sealed;
+use crate::link::ethernet::{dispatch, EtherType};
+use crate::link::stp;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::fmt;
+
+/// The length of the SNAP extension following an LLC header
+pub const SNAP_LEN: usize = 5;
+
+/// The SNAP organization used by RFC 1042 to encapsulate ethertypes
+pub const ENCAPSULATED_ETHERNET_OUI: [u8; 3] = [0x00, 0x00, 0x00];
+/// The SNAP organization used by IEEE 802.1H to encapsulate ethertypes
+pub const BRIDGE_TUNNEL_OUI: [u8; 3] = [0x00, 0x00, 0xF8];
+/// The SNAP organization of Cisco protocols such as CDP, VTP, and PVST+
+pub const CISCO_OUI: [u8; 3] = [0x00, 0x00, 0x0C];
+/// The SNAP organization of IEEE 802.1
+pub const IEEE_802_1_OUI: [u8; 3] = [0x00, 0x80, 0xC2];
+
+/// An LLC header parsed from a slice of bytes, with the rest of the data as its payload
+pub type Parsed<'a> = Llc<payload::Unknown<'a>>;
+/// A SNAP extension parsed from a slice of bytes, with the rest of the data as its payload
+pub type ParsedSnap<'a> = Snap<payload::Unknown<'a>>;
+
+/// The result of dispatching the payload of a SNAP extension by its ethertype
+type Dispatched<'a, X> = Result<Snap<dispatch::Payload<'a, X>>, dispatch::ParseError<<X as dispatch::Extension<'a>>::Error>>;
+
+/// An error that occurs while parsing an LLC header or SNAP extension
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of the header
+    Truncated,
+    /// The header wasn't an unnumbered information PDU between SNAP service access points
+    NotSnap
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad LLC header; ran out of data"),
+            ParseError::NotSnap => f.write_str("bad LLC header; expected a SNAP UI header"),
+        }
+    }
+}
+
+/// An LLC service access point address.
+/// The lowest bit is the individual/group bit of destination addresses and the command/response bit of source addresses.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Sap(pub u8);
+
+impl Sap {
+    /// The null service access point
+    pub const NULL: Sap = Sap(0x00);
+    /// The service access point of SNA path control
+    pub const SNA: Sap = Sap(0x04);
+    /// The service access point of IP
+    pub const IP: Sap = Sap(0x06);
+    /// The service access point of spanning tree BPDUs
+    pub const SPANNING_TREE: Sap = Sap(0x42);
+    /// The service access point of SNAP extensions
+    pub const SNAP: Sap = Sap(0xAA);
+    /// The service access point of Novell IPX
+    pub const IPX: Sap = Sap(0xE0);
+    /// The service access point of NetBIOS
+    pub const NETBIOS: Sap = Sap(0xF0);
+    /// The service access point of LAN management
+    pub const LAN_MANAGEMENT: Sap = Sap(0xF4);
+    /// The service access point of the ISO network layer
+    pub const ISO_NETWORK: Sap = Sap(0xFE);
+    /// The global destination service access point
+    pub const GLOBAL: Sap = Sap(0xFF);
+
+    /// Gets the address without the individual/group or command/response bit
+    pub const fn address(self) -> u8 {
+        self.0 & 0xFE
+    }
+}
+
+/// The function of a supervisory PDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Supervisory(pub u8);
+
+impl Supervisory {
+    /// Receive ready
+    pub const RR: Supervisory = Supervisory(0);
+    /// Receive not ready
+    pub const RNR: Supervisory = Supervisory(1);
+    /// Reject
+    pub const REJ: Supervisory = Supervisory(2);
+}
+
+/// The function of an unnumbered PDU, excluding the poll/final bit
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Unnumbered(pub u8);
+
+impl Unnumbered {
+    /// Unnumbered information
+    pub const UI: Unnumbered = Unnumbered(0x03);
+    /// Exchange identification
+    pub const XID: Unnumbered = Unnumbered(0xAF);
+    /// Test
+    pub const TEST: Unnumbered = Unnumbered(0xE3);
+    /// Set asynchronous balanced mode extended
+    pub const SABME: Unnumbered = Unnumbered(0x6F);
+    /// Disconnect
+    pub const DISC: Unnumbered = Unnumbered(0x43);
+    /// Unnumbered acknowledgment
+    pub const UA: Unnumbered = Unnumbered(0x63);
+    /// Disconnected mode
+    pub const DM: Unnumbered = Unnumbered(0x0F);
+    /// Frame reject
+    pub const FRMR: Unnumbered = Unnumbered(0x87);
+    /// Acknowledged connectionless information with a sequence number of 0
+    pub const AC0: Unnumbered = Unnumbered(0x67);
+    /// Acknowledged connectionless information with a sequence number of 1
+    pub const AC1: Unnumbered = Unnumbered(0xE7);
+}
+
+/// The control field of an LLC header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Control {
+    /// A sequenced information PDU used by connection-mode (type 2) LLC
+    Information {
+        send_sequence: u8,
+        receive_sequence: u8,
+        poll_final: bool
+    },
+    /// A supervisory PDU used by connection-mode (type 2) LLC
+    Supervisory {
+        function: Supervisory,
+        receive_sequence: u8,
+        poll_final: bool
+    },
+    /// An unnumbered PDU, which has a single byte control field
+    Unnumbered {
+        function: Unnumbered,
+        poll_final: bool
+    }
+}
+
+impl Control {
+    const POLL_FINAL: u8 = 0x10;
+
+    /// An unnumbered information control field, used by connectionless (type 1) LLC
+    pub const UI: Control = Control::Unnumbered { function: Unnumbered::UI, poll_final: false };
+
+    /// Gets the LLC type that uses this control field: 1 for unacknowledged connectionless,
+    /// 2 for connection-mode, and 3 for acknowledged connectionless operation
+    pub fn llc_type(&self) -> u8 {
+        match self {
+            Control::Unnumbered { function: Unnumbered::UI, .. } |
+            Control::Unnumbered { function: Unnumbered::XID, .. } |
+            Control::Unnumbered { function: Unnumbered::TEST, .. } => 1,
+            Control::Unnumbered { function: Unnumbered::AC0, .. } |
+            Control::Unnumbered { function: Unnumbered::AC1, .. } => 3,
+            _ => 2,
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let first = *bytes.first().ok_or(ParseError::Truncated)?;
+        if first & 0b11 == 0b11 {
+            return Ok(Control::Unnumbered { function: Unnumbered(first & !Self::POLL_FINAL), poll_final: first & Self::POLL_FINAL != 0 });
+        }
+
+        let second = *bytes.get(1).ok_or(ParseError::Truncated)?;
+        let (receive_sequence, poll_final) = (second >> 1, second & 1 != 0);
+        if first & 1 == 0 {
+            Ok(Control::Information { send_sequence: first >> 1, receive_sequence, poll_final })
+        } else {
+            Ok(Control::Supervisory { function: Supervisory((first >> 2) & 0b11), receive_sequence, poll_final })
+        }
+    }
+}
+
+impl Sealed for Control { }
+impl Size for Control {
+    fn size(&self) -> usize {
+        match self {
+            Control::Unnumbered { .. } => 1,
+            _ => 2,
+        }
+    }
+}
+/// Writes the control field. Sequence numbers are truncated to 7 bits.
+impl Write for Control {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        match *self {
+            Control::Information { send_sequence, receive_sequence, poll_final } =>
+                [(send_sequence << 1) & 0xFE, (receive_sequence << 1) | poll_final as u8].write(output),
+            Control::Supervisory { function, receive_sequence, poll_final } =>
+                [((function.0 & 0b11) << 2) | 0b01, (receive_sequence << 1) | poll_final as u8].write(output),
+            Control::Unnumbered { function, poll_final } => {
+                let flag = if poll_final { Self::POLL_FINAL } else { 0 };
+                (function.0 | 0b11 | flag).write(output)
+            },
+        }
+    }
+}
+
+/// An IEEE 802.2 LLC header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Llc<P> {
+    /// The destination service access point
+    pub dsap: Sap,
+    /// The source service access point
+    pub ssap: Sap,
+    /// The control field, which identifies the type of the PDU and its sequence numbers
+    pub control: Control,
+    payload: P
+}
+
+impl<P> Llc<P> {
+    /// Creates a new LLC header with the specified control field and payload
+    pub fn new(dsap: Sap, ssap: Sap, control: Control, payload: P) -> Self {
+        Llc { dsap, ssap, control, payload }
+    }
+    /// Creates a new unnumbered information PDU
+    pub fn ui(dsap: Sap, ssap: Sap, payload: P) -> Self {
+        Llc::new(dsap, ssap, Control::UI, payload)
+    }
+    /// Creates a new unnumbered information PDU between SNAP service access points containing a SNAP extension
+    pub fn snap(snap: Snap<P>) -> Llc<Snap<P>> {
+        Llc::ui(Sap::SNAP, Sap::SNAP, snap)
+    }
+    /// Gets a reference to the payload of the PDU
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    /// Returns whether the destination is a group of service access points
+    pub fn is_group(&self) -> bool {
+        self.dsap.0 & 1 != 0
+    }
+    /// Returns whether the PDU is a response rather than a command
+    pub fn is_response(&self) -> bool {
+        self.ssap.0 & 1 != 0
+    }
+    /// Returns whether the PDU is an unnumbered information PDU between SNAP service access points
+    pub fn is_snap(&self) -> bool {
+        self.dsap == Sap::SNAP && self.ssap == Sap::SNAP && self.control == Control::UI
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Llc<Q> {
+        let Llc { dsap, ssap, control, payload } = self;
+        Llc { dsap, ssap, control, payload: f(payload) }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Llc<Q>, E> {
+        let Llc { dsap, ssap, control, payload } = self;
+        Ok(Llc { dsap, ssap, control, payload: f(payload)? })
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses an LLC header from a slice of bytes
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < 3 {
+            return Err(ParseError::Truncated);
+        }
+        let control = Control::parse(&bytes[2..])?;
+        Ok(Llc {
+            dsap: Sap(bytes[0]),
+            ssap: Sap(bytes[1]),
+            control,
+            payload: bytes.consume(2 + control.size())
+        })
+    }
+
+    /// Parses the SNAP extension following the header, returning an error if the PDU isn't an
+    /// unnumbered information PDU between SNAP service access points
+    pub fn try_parse_snap(self) -> Result<Llc<ParsedSnap<'a>>, ParseError> {
+        if !self.is_snap() {
+            return Err(ParseError::NotSnap);
+        }
+        self.try_map_payload(Snap::parse)
+    }
+
+    /// Parses the payload as a spanning tree BPDU, returning an error if the PDU isn't an
+    /// unnumbered information PDU between spanning tree service access points
+    pub fn try_parse_bpdu(self) -> Result<Llc<stp::Bpdu<'a>>, stp::ParseError> {
+        if self.dsap.address() != Sap::SPANNING_TREE.0 || self.ssap.address() != Sap::SPANNING_TREE.0 || self.control != Control::UI {
+            return Err(stp::ParseError::UnexpectedSap);
+        }
+        self.try_map_payload(stp::Bpdu::parse)
+    }
+}
+
+impl<P> Sealed for Llc<P> { }
+impl<P: Size> Size for Llc<P> {
+    fn size(&self) -> usize {
+        2 + self.control.size() + self.payload.size()
+    }
+}
+impl<P: Write> Write for Llc<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.dsap.0)?;
+        cursor.write(&self.ssap.0)?;
+        cursor.write(&self.control)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// A SNAP extension identifying the protocol of the payload by an organization and a protocol ID
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Snap<P> {
+    /// The organizationally unique identifier of the organization that defined the protocol ID
+    pub oui: [u8; 3],
+    /// The protocol ID, which is an ethertype for the [`ENCAPSULATED_ETHERNET_OUI`](constant.ENCAPSULATED_ETHERNET_OUI.html)
+    /// and [`BRIDGE_TUNNEL_OUI`](constant.BRIDGE_TUNNEL_OUI.html) organizations
+    pub protocol_id: u16,
+    payload: P
+}
+
+impl<P> Snap<P> {
+    /// Creates a new SNAP extension with the specified organization and protocol ID
+    pub fn new(oui: [u8; 3], protocol_id: u16, payload: P) -> Self {
+        Snap { oui, protocol_id, payload }
+    }
+    /// Creates a new SNAP extension encapsulating a payload with the specified ethertype as described by RFC 1042
+    pub fn ethernet(etype: EtherType, payload: P) -> Self {
+        Snap::new(ENCAPSULATED_ETHERNET_OUI, etype.0, payload)
+    }
+    /// Gets a reference to the payload of the extension
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    /// Gets the protocol ID as an ethertype, or `None` if the organization doesn't use ethertypes as protocol IDs
+    pub fn ether_type(&self) -> Option<EtherType> {
+        if self.oui == ENCAPSULATED_ETHERNET_OUI || self.oui == BRIDGE_TUNNEL_OUI {
+            Some(EtherType(self.protocol_id))
+        } else {
+            None
+        }
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Snap<Q> {
+        let Snap { oui, protocol_id, payload } = self;
+        Snap { oui, protocol_id, payload: f(payload) }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Snap<Q>, E> {
+        let Snap { oui, protocol_id, payload } = self;
+        Ok(Snap { oui, protocol_id, payload: f(payload)? })
+    }
+}
+
+impl<'a> ParsedSnap<'a> {
+    /// Parses a SNAP extension from a slice of bytes
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < SNAP_LEN {
+            return Err(ParseError::Truncated);
+        }
+        Ok(Snap {
+            oui: [bytes[0], bytes[1], bytes[2]],
+            protocol_id: u16::from_be_bytes([bytes[3], bytes[4]]),
+            payload: bytes.consume(SNAP_LEN)
+        })
+    }
+
+    /// Parses the payload by its ethertype, returning `None` if the organization doesn't use ethertypes as protocol IDs
+    pub fn parse_payload(self) -> Option<Dispatched<'a, core::convert::Infallible>> {
+        self.parse_payload_with()
+    }
+
+    /// Parses the payload by its ethertype, using the extension to parse ethertypes that aren't handled by this crate.
+    /// This returns `None` if the organization doesn't use ethertypes as protocol IDs.
+    pub fn parse_payload_with<X: dispatch::Extension<'a>>(self) -> Option<Dispatched<'a, X>> {
+        let etype = self.ether_type()?;
+        Some(self.try_map_payload(|payload| dispatch::Payload::parse_with(etype, payload)))
+    }
+}
+
+impl<P> Sealed for Snap<P> { }
+impl<P: Size> Size for Snap<P> {
+    fn size(&self) -> usize {
+        SNAP_LEN + self.payload.size()
+    }
+}
+impl<P: Write> Write for Snap<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.oui[..])?;
+        cursor.write(&self.protocol_id)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{vlan, Ethernet, EthernetBase, Frame};
+    use crate::link::arp;
+    use macress::{Address, Eui48};
+
+    #[test]
+    fn control_formats() {
+        let cases: [(&[u8], Control, u8); 5] = [
+            (&[0x03], Control::UI, 1),
+            (&[0xBF], Control::Unnumbered { function: Unnumbered::XID, poll_final: true }, 1),
+            (&[0xE7], Control::Unnumbered { function: Unnumbered::AC1, poll_final: false }, 3),
+            (&[0x0A, 0x0B], Control::Information { send_sequence: 5, receive_sequence: 5, poll_final: true }, 2),
+            (&[0x05, 0x10], Control::Supervisory { function: Supervisory::RNR, receive_sequence: 8, poll_final: false }, 2),
+        ];
+        for &(bytes, control, llc_type) in &cases {
+            let mut data = [0x42, 0x43, 0, 0, 0xFF];
+            data[2..2 + bytes.len()].copy_from_slice(bytes);
+            let llc = Parsed::parse(&data[..]).unwrap();
+            assert_eq!(llc.control, control);
+            assert_eq!(llc.control.llc_type(), llc_type);
+            assert!(!llc.is_group() && llc.is_response());
+            assert_eq!(llc.payload().len(), 3 - bytes.len());
+
+            let mut buf = [0; 5];
+            assert_eq!(llc.write(&mut buf), Ok(5));
+            assert_eq!(buf, data);
+        }
+        assert_eq!(Parsed::parse(&[0x42, 0x42, 0x00][..]).unwrap_err(), ParseError::Truncated);
+        assert_eq!(Parsed::parse(&[0x42, 0x42][..]).unwrap_err(), ParseError::Truncated);
+    }
+
+    #[test]
+    fn snap_dispatch() {
+        let request = arp::Arp::request(Address::new([0x02, 0, 0, 0, 0, 1]), [192, 0, 2, 1].into(), [192, 0, 2, 2].into());
+        let pdu = Llc::snap(Snap::ethernet(EtherType::ARP, request));
+        let frame = Ethernet::new(Address::<Eui48>::BROADCAST, Address::new([0x02, 0, 0, 0, 0, 1]), vlan::Empty,
+            payload::Padded { payload: pdu, padding: payload::ValuePadding::zero(10) });
+        assert_eq!(frame.size(), 14 + 3 + SNAP_LEN + arp::PACKET_LEN + 10);
+
+        let mut buf = [0; 60];
+        assert_eq!(frame.write(&mut buf), Ok(60));
+        assert_eq!(&buf[12..22], &[0x00, 36, 0xAA, 0xAA, 0x03, 0, 0, 0, 0x08, 0x06]);
+
+        let frame = match EthernetBase::parse(&buf[..]).unwrap().classify().unwrap() {
+            Frame::Ethernet(frame) => frame.try_parse_llc().unwrap(),
+            other => panic!("expected an 802.3 frame, got {:?}", other),
+        };
+        assert_eq!(frame.payload().padding.len(), 10);
+        let llc = frame.payload().payload;
+        assert!(llc.is_snap());
+
+        let snap = llc.try_parse_snap().unwrap();
+        assert_eq!(snap.payload().ether_type(), Some(EtherType::ARP));
+        match snap.payload().parse_payload() {
+            Some(Ok(parsed)) => assert_eq!(parsed.payload(), &dispatch::Payload::Arp(payload::Padded { payload: request, padding: &[] })),
+            other => panic!("expected an ARP payload, got {:?}", other),
+        }
+
+        let cdp = Snap::new(CISCO_OUI, 0x2000, payload::Unknown(&[2, 180]));
+        assert_eq!(cdp.ether_type(), None);
+        assert!(cdp.parse_payload().is_none());
+
+        let stp = Parsed::parse(&[0x42, 0x42, 0x03, 0, 0][..]).unwrap();
+        assert_eq!(stp.try_parse_snap().unwrap_err(), ParseError::NotSnap);
+        assert_eq!(ParsedSnap::parse(&[0, 0, 0, 8][..]).unwrap_err(), ParseError::Truncated);
+    }
+}
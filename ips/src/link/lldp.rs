@@ -0,0 +1,1440 @@
+//! Types for reading and writing LLDP data units
+//!
+//! An LLDPDU is a list of type-length-value structures (TLVs) sent with the [`LLDP`](../ethernet/struct.EtherType.html#associatedconstant.LLDP)
+//! ethertype. The chassis ID, port ID and time to live TLVs are always first and are stored in the fields of [`Lldpdu`](struct.Lldpdu.html),
+//! with the optional TLVs following them. Parsed LLDPDUs keep their optional TLVs unparsed in [`Tlvs`](struct.Tlvs.html),
+//! while LLDPDUs to write can use a slice of [`Tlv`](enum.Tlv.html) values.
+
+use crate::internal::Sealed;
+use crate::link::llc;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::{Ipv4Addr, Ipv6Addr};
+use core::ops::BitOr;
+use macress::{Address, Eui48};
+
+type MacAddr = Address<Eui48>;
+
+/// The destination address of LLDPDUs that are only received by the nearest bridge
+pub const NEAREST_BRIDGE_ADDRESS: MacAddr = MacAddr::new([0x01, 0x80, 0xC2, 0x00, 0x00, 0x0E]);
+/// The destination address of LLDPDUs that pass through two-port MAC relays
+pub const NEAREST_NON_TPMR_BRIDGE_ADDRESS: MacAddr = MacAddr::new([0x01, 0x80, 0xC2, 0x00, 0x00, 0x03]);
+/// The destination address of LLDPDUs that pass through two-port MAC relays and S-VLAN components
+pub const NEAREST_CUSTOMER_BRIDGE_ADDRESS: MacAddr = MacAddr::new([0x01, 0x80, 0xC2, 0x00, 0x00, 0x00]);
+
+/// The maximum length of the information string of a TLV
+pub const MAX_TLV_LEN: usize = 511;
+/// The maximum length of a chassis or port identifier, excluding its subtype
+pub const MAX_ID_LEN: usize = 255;
+
+/// An LLDPDU parsed from a slice of bytes
+pub type Parsed<'a> = Lldpdu<'a, Tlvs<'a>>;
+
+/// An error that occurs while parsing an LLDPDU or its TLVs
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of a TLV
+    Truncated,
+    /// A mandatory TLV was missing or out of order
+    MissingTlv(u8),
+    /// A TLV had an information string that isn't valid for its type
+    InvalidTlv {
+        /// The type of the TLV
+        kind: u8,
+        /// The length of the information string of the TLV
+        length: u16
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad LLDPDU; ran out of data"),
+            ParseError::MissingTlv(kind) => write!(f, "bad LLDPDU; missing mandatory TLV of type {}", kind),
+            ParseError::InvalidTlv { kind, length } => write!(f, "bad LLDP TLV; invalid information string of length {} for type {}", length, kind),
+        }
+    }
+}
+
+/// Splits the first TLV from the data, returning its type, its information string, and the data after it
+fn split_tlv(bytes: &[u8]) -> Result<(u8, &[u8], &[u8]), ParseError> {
+    let header = bytes.get(..2).ok_or(ParseError::Truncated)?;
+    let header = u16::from_be_bytes([header[0], header[1]]);
+    let (kind, length) = ((header >> 9) as u8, (header & 0x1FF) as usize);
+    let info = bytes.get(2..2 + length).ok_or(ParseError::Truncated)?;
+    Ok((kind, info, &bytes[2 + length..]))
+}
+
+fn write_header(cursor: &mut Cursor, kind: u8, length: usize) -> Result<(), physical::Error> {
+    if kind > Tlv::ORGANIZATIONALLY_SPECIFIC || length > MAX_TLV_LEN {
+        return Err(physical::Error);
+    }
+    cursor.write(&(((kind as u16) << 9) | length as u16))
+}
+
+/// Writes the header and subtype of a chassis or port ID TLV, checking the length of the identifier
+fn write_id_header(cursor: &mut Cursor, kind: u8, subtype: u8, id_len: usize) -> Result<(), physical::Error> {
+    if id_len == 0 || id_len > MAX_ID_LEN {
+        return Err(physical::Error);
+    }
+    write_header(cursor, kind, 1 + id_len)?;
+    cursor.write(&subtype)
+}
+
+fn u16_at(bytes: &[u8], start: usize) -> u16 {
+    u16::from_be_bytes([bytes[start], bytes[start + 1]])
+}
+
+fn u32_at(bytes: &[u8], start: usize) -> u32 {
+    u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[start..start + 4]).unwrap())
+}
+
+/// A network address prefixed by its IANA address family number
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NetworkAddress<'a> {
+    /// An IPv4 address
+    Ipv4(Ipv4Addr),
+    /// An IPv6 address
+    Ipv6(Ipv6Addr),
+    /// An IEEE 802 MAC address
+    Mac(MacAddr),
+    /// An address of another family
+    Other {
+        /// The IANA address family number
+        family: u8,
+        /// The address in the format of its family
+        address: &'a [u8]
+    }
+}
+
+impl<'a> NetworkAddress<'a> {
+    /// The IPv4 address family number
+    pub const IPV4: u8 = 1;
+    /// The IPv6 address family number
+    pub const IPV6: u8 = 2;
+    /// The IEEE 802 MAC address family number
+    pub const MAC: u8 = 6;
+
+    /// Gets the address family number of the address
+    pub fn family(&self) -> u8 {
+        match self {
+            NetworkAddress::Ipv4(_) => Self::IPV4,
+            NetworkAddress::Ipv6(_) => Self::IPV6,
+            NetworkAddress::Mac(_) => Self::MAC,
+            NetworkAddress::Other { family, .. } => *family,
+        }
+    }
+
+    fn parse(bytes: &'a [u8]) -> Option<Self> {
+        let (&family, address) = bytes.split_first()?;
+        match family {
+            Self::IPV4 => <[u8; 4]>::try_from(address).ok().map(|a| NetworkAddress::Ipv4(a.into())),
+            Self::IPV6 => <[u8; 16]>::try_from(address).ok().map(|a| NetworkAddress::Ipv6(a.into())),
+            Self::MAC => <[u8; 6]>::try_from(address).ok().map(|a| NetworkAddress::Mac(MacAddr::new(a))),
+            family => Some(NetworkAddress::Other { family, address }),
+        }
+    }
+}
+
+impl From<Ipv4Addr> for NetworkAddress<'_> {
+    fn from(address: Ipv4Addr) -> Self {
+        NetworkAddress::Ipv4(address)
+    }
+}
+
+impl From<Ipv6Addr> for NetworkAddress<'_> {
+    fn from(address: Ipv6Addr) -> Self {
+        NetworkAddress::Ipv6(address)
+    }
+}
+
+impl From<MacAddr> for NetworkAddress<'_> {
+    fn from(address: MacAddr) -> Self {
+        NetworkAddress::Mac(address)
+    }
+}
+
+impl Sealed for NetworkAddress<'_> { }
+impl Size for NetworkAddress<'_> {
+    fn size(&self) -> usize {
+        1 + match self {
+            NetworkAddress::Ipv4(_) => 4,
+            NetworkAddress::Ipv6(_) => 16,
+            NetworkAddress::Mac(_) => 6,
+            NetworkAddress::Other { address, .. } => address.len(),
+        }
+    }
+}
+impl Write for NetworkAddress<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.family())?;
+        match self {
+            NetworkAddress::Ipv4(address) => cursor.write(address)?,
+            NetworkAddress::Ipv6(address) => cursor.write(address)?,
+            NetworkAddress::Mac(address) => cursor.write(address)?,
+            NetworkAddress::Other { address, .. } => cursor.write(*address)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// The identifier of the chassis that sent an LLDPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ChassisId<'a> {
+    /// The `entPhysicalAlias` of a chassis component
+    ChassisComponent(&'a [u8]),
+    /// The `ifAlias` of an interface
+    InterfaceAlias(&'a [u8]),
+    /// The `entPhysicalAlias` of a port or backplane component
+    PortComponent(&'a [u8]),
+    /// The MAC address of the chassis
+    MacAddress(MacAddr),
+    /// A network address of the chassis
+    NetworkAddress(NetworkAddress<'a>),
+    /// The `ifName` of an interface
+    InterfaceName(&'a [u8]),
+    /// A locally assigned identifier
+    Local(&'a [u8]),
+    /// An identifier with a reserved subtype
+    Unknown {
+        /// The subtype of the identifier
+        subtype: u8,
+        /// The identifier
+        id: &'a [u8]
+    }
+}
+
+impl<'a> ChassisId<'a> {
+    /// The chassis component subtype
+    pub const CHASSIS_COMPONENT: u8 = 1;
+    /// The interface alias subtype
+    pub const INTERFACE_ALIAS: u8 = 2;
+    /// The port component subtype
+    pub const PORT_COMPONENT: u8 = 3;
+    /// The MAC address subtype
+    pub const MAC_ADDRESS: u8 = 4;
+    /// The network address subtype
+    pub const NETWORK_ADDRESS: u8 = 5;
+    /// The interface name subtype
+    pub const INTERFACE_NAME: u8 = 6;
+    /// The locally assigned subtype
+    pub const LOCAL: u8 = 7;
+
+    /// Gets the subtype of the identifier
+    pub fn subtype(&self) -> u8 {
+        match self {
+            ChassisId::ChassisComponent(_) => Self::CHASSIS_COMPONENT,
+            ChassisId::InterfaceAlias(_) => Self::INTERFACE_ALIAS,
+            ChassisId::PortComponent(_) => Self::PORT_COMPONENT,
+            ChassisId::MacAddress(_) => Self::MAC_ADDRESS,
+            ChassisId::NetworkAddress(_) => Self::NETWORK_ADDRESS,
+            ChassisId::InterfaceName(_) => Self::INTERFACE_NAME,
+            ChassisId::Local(_) => Self::LOCAL,
+            ChassisId::Unknown { subtype, .. } => *subtype,
+        }
+    }
+
+    fn parse(info: &'a [u8]) -> Result<Self, ParseError> {
+        let invalid = ParseError::InvalidTlv { kind: Tlv::CHASSIS_ID, length: info.len() as u16 };
+        let (&subtype, id) = info.split_first().filter(|(_, id)| !id.is_empty() && id.len() <= MAX_ID_LEN).ok_or(invalid)?;
+        match subtype {
+            Self::CHASSIS_COMPONENT => Ok(ChassisId::ChassisComponent(id)),
+            Self::INTERFACE_ALIAS => Ok(ChassisId::InterfaceAlias(id)),
+            Self::PORT_COMPONENT => Ok(ChassisId::PortComponent(id)),
+            Self::MAC_ADDRESS => <[u8; 6]>::try_from(id).map(|a| ChassisId::MacAddress(MacAddr::new(a))).map_err(|_| invalid),
+            Self::NETWORK_ADDRESS => NetworkAddress::parse(id).map(ChassisId::NetworkAddress).ok_or(invalid),
+            Self::INTERFACE_NAME => Ok(ChassisId::InterfaceName(id)),
+            Self::LOCAL => Ok(ChassisId::Local(id)),
+            subtype => Ok(ChassisId::Unknown { subtype, id }),
+        }
+    }
+}
+
+impl Sealed for ChassisId<'_> { }
+/// The size of the chassis ID TLV
+impl Size for ChassisId<'_> {
+    fn size(&self) -> usize {
+        3 + match self {
+            ChassisId::MacAddress(_) => 6,
+            ChassisId::NetworkAddress(address) => address.size(),
+            ChassisId::ChassisComponent(id) | ChassisId::InterfaceAlias(id) | ChassisId::PortComponent(id) |
+            ChassisId::InterfaceName(id) | ChassisId::Local(id) | ChassisId::Unknown { id, .. } => id.len(),
+        }
+    }
+}
+/// Writes the chassis ID TLV
+impl Write for ChassisId<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        write_id_header(&mut cursor, Tlv::CHASSIS_ID, self.subtype(), self.size() - 3)?;
+        match self {
+            ChassisId::MacAddress(address) => cursor.write(address)?,
+            ChassisId::NetworkAddress(address) => cursor.write(address)?,
+            ChassisId::ChassisComponent(id) | ChassisId::InterfaceAlias(id) | ChassisId::PortComponent(id) |
+            ChassisId::InterfaceName(id) | ChassisId::Local(id) | ChassisId::Unknown { id, .. } => cursor.write(*id)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// The identifier of the port an LLDPDU was sent from
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PortId<'a> {
+    /// The `ifAlias` of the interface
+    InterfaceAlias(&'a [u8]),
+    /// The `entPhysicalAlias` of the port component
+    PortComponent(&'a [u8]),
+    /// The MAC address of the port
+    MacAddress(MacAddr),
+    /// A network address of the port
+    NetworkAddress(NetworkAddress<'a>),
+    /// The `ifName` of the interface
+    InterfaceName(&'a [u8]),
+    /// The agent circuit ID of a DHCP relay agent
+    AgentCircuitId(&'a [u8]),
+    /// A locally assigned identifier
+    Local(&'a [u8]),
+    /// An identifier with a reserved subtype
+    Unknown {
+        /// The subtype of the identifier
+        subtype: u8,
+        /// The identifier
+        id: &'a [u8]
+    }
+}
+
+impl<'a> PortId<'a> {
+    /// The interface alias subtype
+    pub const INTERFACE_ALIAS: u8 = 1;
+    /// The port component subtype
+    pub const PORT_COMPONENT: u8 = 2;
+    /// The MAC address subtype
+    pub const MAC_ADDRESS: u8 = 3;
+    /// The network address subtype
+    pub const NETWORK_ADDRESS: u8 = 4;
+    /// The interface name subtype
+    pub const INTERFACE_NAME: u8 = 5;
+    /// The agent circuit ID subtype
+    pub const AGENT_CIRCUIT_ID: u8 = 6;
+    /// The locally assigned subtype
+    pub const LOCAL: u8 = 7;
+
+    /// Gets the subtype of the identifier
+    pub fn subtype(&self) -> u8 {
+        match self {
+            PortId::InterfaceAlias(_) => Self::INTERFACE_ALIAS,
+            PortId::PortComponent(_) => Self::PORT_COMPONENT,
+            PortId::MacAddress(_) => Self::MAC_ADDRESS,
+            PortId::NetworkAddress(_) => Self::NETWORK_ADDRESS,
+            PortId::InterfaceName(_) => Self::INTERFACE_NAME,
+            PortId::AgentCircuitId(_) => Self::AGENT_CIRCUIT_ID,
+            PortId::Local(_) => Self::LOCAL,
+            PortId::Unknown { subtype, .. } => *subtype,
+        }
+    }
+
+    fn parse(info: &'a [u8]) -> Result<Self, ParseError> {
+        let invalid = ParseError::InvalidTlv { kind: Tlv::PORT_ID, length: info.len() as u16 };
+        let (&subtype, id) = info.split_first().filter(|(_, id)| !id.is_empty() && id.len() <= MAX_ID_LEN).ok_or(invalid)?;
+        match subtype {
+            Self::INTERFACE_ALIAS => Ok(PortId::InterfaceAlias(id)),
+            Self::PORT_COMPONENT => Ok(PortId::PortComponent(id)),
+            Self::MAC_ADDRESS => <[u8; 6]>::try_from(id).map(|a| PortId::MacAddress(MacAddr::new(a))).map_err(|_| invalid),
+            Self::NETWORK_ADDRESS => NetworkAddress::parse(id).map(PortId::NetworkAddress).ok_or(invalid),
+            Self::INTERFACE_NAME => Ok(PortId::InterfaceName(id)),
+            Self::AGENT_CIRCUIT_ID => Ok(PortId::AgentCircuitId(id)),
+            Self::LOCAL => Ok(PortId::Local(id)),
+            subtype => Ok(PortId::Unknown { subtype, id }),
+        }
+    }
+}
+
+impl Sealed for PortId<'_> { }
+/// The size of the port ID TLV
+impl Size for PortId<'_> {
+    fn size(&self) -> usize {
+        3 + match self {
+            PortId::MacAddress(_) => 6,
+            PortId::NetworkAddress(address) => address.size(),
+            PortId::InterfaceAlias(id) | PortId::PortComponent(id) | PortId::InterfaceName(id) |
+            PortId::AgentCircuitId(id) | PortId::Local(id) | PortId::Unknown { id, .. } => id.len(),
+        }
+    }
+}
+/// Writes the port ID TLV
+impl Write for PortId<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        write_id_header(&mut cursor, Tlv::PORT_ID, self.subtype(), self.size() - 3)?;
+        match self {
+            PortId::MacAddress(address) => cursor.write(address)?,
+            PortId::NetworkAddress(address) => cursor.write(address)?,
+            PortId::InterfaceAlias(id) | PortId::PortComponent(id) | PortId::InterfaceName(id) |
+            PortId::AgentCircuitId(id) | PortId::Local(id) | PortId::Unknown { id, .. } => cursor.write(*id)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// The capabilities of a system
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Capabilities(u16);
+
+impl Capabilities {
+    /// No capabilities
+    pub const NONE: Capabilities = Capabilities(0);
+    /// A capability not covered by the other values
+    pub const OTHER: Capabilities = Capabilities(0x0001);
+    /// A repeater
+    pub const REPEATER: Capabilities = Capabilities(0x0002);
+    /// A MAC bridge
+    pub const BRIDGE: Capabilities = Capabilities(0x0004);
+    /// A WLAN access point
+    pub const WLAN_ACCESS_POINT: Capabilities = Capabilities(0x0008);
+    /// A router
+    pub const ROUTER: Capabilities = Capabilities(0x0010);
+    /// A telephone
+    pub const TELEPHONE: Capabilities = Capabilities(0x0020);
+    /// A DOCSIS cable device
+    pub const DOCSIS: Capabilities = Capabilities(0x0040);
+    /// An end station
+    pub const STATION: Capabilities = Capabilities(0x0080);
+    /// A C-VLAN component of a VLAN bridge
+    pub const CUSTOMER_BRIDGE: Capabilities = Capabilities(0x0100);
+    /// An S-VLAN component of a provider bridge
+    pub const SERVICE_BRIDGE: Capabilities = Capabilities(0x0200);
+    /// A two-port MAC relay
+    pub const TWO_PORT_MAC_RELAY: Capabilities = Capabilities(0x0400);
+
+    /// Returns a new capabilities value from the specified value
+    pub const fn raw(value: u16) -> Capabilities {
+        Capabilities(value)
+    }
+
+    /// Gets whether all of the specified capabilities are set
+    pub const fn contains(self, other: Capabilities) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Gets whether the bridge capability is set
+    pub const fn bridge(self) -> bool { self.contains(Self::BRIDGE) }
+    /// Gets whether the WLAN access point capability is set
+    pub const fn wlan_access_point(self) -> bool { self.contains(Self::WLAN_ACCESS_POINT) }
+    /// Gets whether the router capability is set
+    pub const fn router(self) -> bool { self.contains(Self::ROUTER) }
+    /// Gets whether the telephone capability is set
+    pub const fn telephone(self) -> bool { self.contains(Self::TELEPHONE) }
+    /// Gets whether the station capability is set
+    pub const fn station(self) -> bool { self.contains(Self::STATION) }
+
+    /// Gets the raw underlying value of these capabilities
+    pub const fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Capabilities;
+
+    fn bitor(self, rhs: Capabilities) -> Capabilities {
+        Capabilities(self.0 | rhs.0)
+    }
+}
+
+/// An address used to reach the management entity of a system
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ManagementAddress<'a> {
+    /// The address used to reach the management entity of the system
+    pub address: NetworkAddress<'a>,
+    /// How the interface number is assigned
+    pub interface_subtype: u8,
+    /// The number of the interface the address belongs to, or zero if it isn't known
+    pub interface_number: u32,
+    /// The ASN.1 BER encoded object identifier of the hardware component or protocol entity of the address
+    pub object_id: &'a [u8]
+}
+
+impl<'a> ManagementAddress<'a> {
+    /// The interface numbering subtype for unknown interfaces
+    pub const UNKNOWN: u8 = 1;
+    /// The interface numbering subtype for interfaces identified by `ifIndex`
+    pub const IF_INDEX: u8 = 2;
+    /// The interface numbering subtype for interfaces identified by their system port number
+    pub const SYSTEM_PORT_NUMBER: u8 = 3;
+
+    /// Creates a new management address for the interface with the specified `ifIndex`
+    pub fn new<A: Into<NetworkAddress<'a>>>(address: A, if_index: u32) -> Self {
+        ManagementAddress { address: address.into(), interface_subtype: Self::IF_INDEX, interface_number: if_index, object_id: &[] }
+    }
+
+    fn parse(info: &'a [u8]) -> Option<Self> {
+        let address_len = *info.first()? as usize;
+        if !(2..=32).contains(&address_len) {
+            return None;
+        }
+        let address = NetworkAddress::parse(info.get(1..1 + address_len)?)?;
+        let rest = &info[1 + address_len..];
+        let (interface, rest) = (rest.get(..5)?, &rest[5..]);
+        let (&object_id_len, object_id) = rest.split_first()?;
+        if object_id.len() != object_id_len as usize || object_id.len() > 128 {
+            return None;
+        }
+        Some(ManagementAddress { address, interface_subtype: interface[0], interface_number: u32_at(interface, 1), object_id })
+    }
+}
+
+impl Sealed for ManagementAddress<'_> { }
+/// The size of the information string of the management address
+impl Size for ManagementAddress<'_> {
+    fn size(&self) -> usize {
+        1 + self.address.size() + 5 + 1 + self.object_id.len()
+    }
+}
+/// Writes the information string of the management address
+impl Write for ManagementAddress<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let address_len = u8::try_from(self.address.size()).map_err(|_| physical::Error)?;
+        let object_id_len = u8::try_from(self.object_id.len()).map_err(|_| physical::Error)?;
+        let mut cursor = Cursor::new(output);
+        cursor.write(&address_len)?;
+        cursor.write(&self.address)?;
+        cursor.write(&self.interface_subtype)?;
+        cursor.write(&self.interface_number)?;
+        cursor.write(&object_id_len)?;
+        cursor.write(self.object_id)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// A TLV defined by IEEE 802.1
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Ieee8021<'a> {
+    /// The VLAN ID of untagged and priority tagged frames received by the port
+    PortVlanId(u16),
+    /// A protocol VLAN ID supported by the port
+    PortAndProtocolVlanId {
+        /// Whether protocol VLANs are supported (0x02) and enabled (0x04)
+        flags: u8,
+        /// The protocol VLAN ID
+        vlan: u16
+    },
+    /// The name of a VLAN the port is a member of
+    VlanName {
+        /// The VLAN ID
+        vlan: u16,
+        /// The name of the VLAN
+        name: &'a [u8]
+    },
+    /// The first octets of a protocol frame the port can process, starting with the ethertype or LLC header
+    ProtocolIdentity(&'a [u8]),
+    /// A TLV with a subtype that isn't handled by this crate
+    Unknown {
+        /// The organizationally defined subtype
+        subtype: u8,
+        /// The information string after the subtype
+        info: &'a [u8]
+    }
+}
+
+impl<'a> Ieee8021<'a> {
+    /// The port VLAN ID subtype
+    pub const PORT_VLAN_ID: u8 = 1;
+    /// The port and protocol VLAN ID subtype
+    pub const PORT_AND_PROTOCOL_VLAN_ID: u8 = 2;
+    /// The VLAN name subtype
+    pub const VLAN_NAME: u8 = 3;
+    /// The protocol identity subtype
+    pub const PROTOCOL_IDENTITY: u8 = 4;
+
+    /// Gets the subtype of the TLV
+    pub fn subtype(&self) -> u8 {
+        match self {
+            Ieee8021::PortVlanId(_) => Self::PORT_VLAN_ID,
+            Ieee8021::PortAndProtocolVlanId { .. } => Self::PORT_AND_PROTOCOL_VLAN_ID,
+            Ieee8021::VlanName { .. } => Self::VLAN_NAME,
+            Ieee8021::ProtocolIdentity(_) => Self::PROTOCOL_IDENTITY,
+            Ieee8021::Unknown { subtype, .. } => *subtype,
+        }
+    }
+
+    fn parse(subtype: u8, info: &'a [u8]) -> Option<Self> {
+        match (subtype, info.len()) {
+            (Self::PORT_VLAN_ID, 2) => Some(Ieee8021::PortVlanId(u16_at(info, 0))),
+            (Self::PORT_AND_PROTOCOL_VLAN_ID, 3) => Some(Ieee8021::PortAndProtocolVlanId { flags: info[0], vlan: u16_at(info, 1) }),
+            (Self::VLAN_NAME, len) if len >= 3 && len == 3 + info[2] as usize => Some(Ieee8021::VlanName { vlan: u16_at(info, 0), name: &info[3..] }),
+            (Self::PROTOCOL_IDENTITY, len) if len >= 1 && len == 1 + info[0] as usize => Some(Ieee8021::ProtocolIdentity(&info[1..])),
+            (Self::PORT_VLAN_ID, _) | (Self::PORT_AND_PROTOCOL_VLAN_ID, _) | (Self::VLAN_NAME, _) | (Self::PROTOCOL_IDENTITY, _) => None,
+            (subtype, _) => Some(Ieee8021::Unknown { subtype, info }),
+        }
+    }
+}
+
+impl Sealed for Ieee8021<'_> { }
+/// The size of the information string after the subtype
+impl Size for Ieee8021<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Ieee8021::PortVlanId(_) => 2,
+            Ieee8021::PortAndProtocolVlanId { .. } => 3,
+            Ieee8021::VlanName { name, .. } => 3 + name.len(),
+            Ieee8021::ProtocolIdentity(identity) => 1 + identity.len(),
+            Ieee8021::Unknown { info, .. } => info.len(),
+        }
+    }
+}
+/// Writes the information string after the subtype
+impl Write for Ieee8021<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        match self {
+            Ieee8021::PortVlanId(vlan) => cursor.write(vlan)?,
+            Ieee8021::PortAndProtocolVlanId { flags, vlan } => {
+                cursor.write(flags)?;
+                cursor.write(vlan)?;
+            },
+            Ieee8021::VlanName { vlan, name } => {
+                cursor.write(vlan)?;
+                cursor.write(&u8::try_from(name.len()).map_err(|_| physical::Error)?)?;
+                cursor.write(*name)?;
+            },
+            Ieee8021::ProtocolIdentity(identity) => {
+                cursor.write(&u8::try_from(identity.len()).map_err(|_| physical::Error)?)?;
+                cursor.write(*identity)?;
+            },
+            Ieee8021::Unknown { info, .. } => cursor.write(*info)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// A TLV defined by IEEE 802.3
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Ieee8023<'a> {
+    /// The duplex and speed configuration of the port
+    MacPhyConfigurationStatus {
+        /// Whether auto-negotiation is supported (0x01) and enabled (0x02)
+        autonegotiation: u8,
+        /// The `ifMauAutoNegCapAdvertisedBits` of the port
+        advertised_capability: u16,
+        /// The `ifMauType` of the port
+        operational_mau_type: u16
+    },
+    /// The power via MDI capabilities of the port
+    PowerViaMdi {
+        /// The MDI power support bits
+        support: u8,
+        /// The `pethPsePortPowerPairs` of the port
+        power_pair: u8,
+        /// The `pethPsePortPowerClassifications` of the port
+        power_class: u8,
+        /// The DTE power fields added by IEEE 802.3at and later, if any
+        extension: &'a [u8]
+    },
+    /// The link aggregation status of the port, superseded by the IEEE 802.1 link aggregation TLV
+    LinkAggregation {
+        /// Whether aggregation is supported (0x01) and enabled (0x02)
+        status: u8,
+        /// The `ifIndex` of the aggregated port, or zero if the port isn't aggregated
+        port_id: u32
+    },
+    /// The maximum frame size of the MAC and PHY of the port
+    MaximumFrameSize(u16),
+    /// A TLV with a subtype that isn't handled by this crate
+    Unknown {
+        /// The organizationally defined subtype
+        subtype: u8,
+        /// The information string after the subtype
+        info: &'a [u8]
+    }
+}
+
+impl<'a> Ieee8023<'a> {
+    /// The MAC/PHY configuration/status subtype
+    pub const MAC_PHY_CONFIGURATION_STATUS: u8 = 1;
+    /// The power via MDI subtype
+    pub const POWER_VIA_MDI: u8 = 2;
+    /// The link aggregation subtype
+    pub const LINK_AGGREGATION: u8 = 3;
+    /// The maximum frame size subtype
+    pub const MAXIMUM_FRAME_SIZE: u8 = 4;
+
+    /// Gets the subtype of the TLV
+    pub fn subtype(&self) -> u8 {
+        match self {
+            Ieee8023::MacPhyConfigurationStatus { .. } => Self::MAC_PHY_CONFIGURATION_STATUS,
+            Ieee8023::PowerViaMdi { .. } => Self::POWER_VIA_MDI,
+            Ieee8023::LinkAggregation { .. } => Self::LINK_AGGREGATION,
+            Ieee8023::MaximumFrameSize(_) => Self::MAXIMUM_FRAME_SIZE,
+            Ieee8023::Unknown { subtype, .. } => *subtype,
+        }
+    }
+
+    fn parse(subtype: u8, info: &'a [u8]) -> Option<Self> {
+        match (subtype, info.len()) {
+            (Self::MAC_PHY_CONFIGURATION_STATUS, 5) => Some(Ieee8023::MacPhyConfigurationStatus {
+                autonegotiation: info[0],
+                advertised_capability: u16_at(info, 1),
+                operational_mau_type: u16_at(info, 3)
+            }),
+            (Self::POWER_VIA_MDI, len) if len >= 3 => Some(Ieee8023::PowerViaMdi {
+                support: info[0],
+                power_pair: info[1],
+                power_class: info[2],
+                extension: &info[3..]
+            }),
+            (Self::LINK_AGGREGATION, 5) => Some(Ieee8023::LinkAggregation { status: info[0], port_id: u32_at(info, 1) }),
+            (Self::MAXIMUM_FRAME_SIZE, 2) => Some(Ieee8023::MaximumFrameSize(u16_at(info, 0))),
+            (Self::MAC_PHY_CONFIGURATION_STATUS, _) | (Self::POWER_VIA_MDI, _) | (Self::LINK_AGGREGATION, _) | (Self::MAXIMUM_FRAME_SIZE, _) => None,
+            (subtype, _) => Some(Ieee8023::Unknown { subtype, info }),
+        }
+    }
+}
+
+impl Sealed for Ieee8023<'_> { }
+/// The size of the information string after the subtype
+impl Size for Ieee8023<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Ieee8023::MacPhyConfigurationStatus { .. } | Ieee8023::LinkAggregation { .. } => 5,
+            Ieee8023::PowerViaMdi { extension, .. } => 3 + extension.len(),
+            Ieee8023::MaximumFrameSize(_) => 2,
+            Ieee8023::Unknown { info, .. } => info.len(),
+        }
+    }
+}
+/// Writes the information string after the subtype
+impl Write for Ieee8023<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        match self {
+            Ieee8023::MacPhyConfigurationStatus { autonegotiation, advertised_capability, operational_mau_type } => {
+                cursor.write(autonegotiation)?;
+                cursor.write(advertised_capability)?;
+                cursor.write(operational_mau_type)?;
+            },
+            Ieee8023::PowerViaMdi { support, power_pair, power_class, extension } => {
+                cursor.write(support)?;
+                cursor.write(power_pair)?;
+                cursor.write(power_class)?;
+                cursor.write(*extension)?;
+            },
+            Ieee8023::LinkAggregation { status, port_id } => {
+                cursor.write(status)?;
+                cursor.write(port_id)?;
+            },
+            Ieee8023::MaximumFrameSize(size) => cursor.write(size)?,
+            Ieee8023::Unknown { info, .. } => cursor.write(*info)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// An LLDP-MED network policy, which advertises the VLAN and priorities to use for an application
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NetworkPolicy {
+    /// The application the policy is for, such as 1 for voice
+    pub application_type: u8,
+    /// Whether the network policy for the application is unknown
+    pub unknown: bool,
+    /// Whether the application uses tagged frames
+    pub tagged: bool,
+    /// The VLAN ID the application uses, or zero for priority tagged frames
+    pub vlan: u16,
+    /// The 802.1p priority the application uses
+    pub priority: u8,
+    /// The DSCP value the application uses
+    pub dscp: u8
+}
+
+impl NetworkPolicy {
+    fn parse(info: &[u8]) -> Self {
+        let policy = u32_at(info, 0);
+        NetworkPolicy {
+            application_type: info[0],
+            unknown: policy & 0x0080_0000 != 0,
+            tagged: policy & 0x0040_0000 != 0,
+            vlan: ((policy >> 9) & 0x0FFF) as u16,
+            priority: ((policy >> 6) & 0x07) as u8,
+            dscp: (policy & 0x3F) as u8
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        ((self.application_type as u32) << 24) |
+            ((self.unknown as u32) << 23) |
+            ((self.tagged as u32) << 22) |
+            (((self.vlan & 0x0FFF) as u32) << 9) |
+            (((self.priority & 0x07) as u32) << 6) |
+            (self.dscp & 0x3F) as u32
+    }
+}
+
+/// A TLV defined by ANSI/TIA-1057 for media endpoint devices
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Med<'a> {
+    /// The LLDP-MED TLVs supported by the system and its device type
+    Capabilities {
+        /// The supported LLDP-MED TLVs, with bit 0x01 for this TLV and bit 0x02 for network policies
+        capabilities: u16,
+        /// The device type, with 1 to 3 for endpoint classes and 4 for network connectivity devices
+        device_type: u8
+    },
+    /// The VLAN, priority and DSCP the system uses for an application type
+    NetworkPolicy(NetworkPolicy),
+    /// The location of the system
+    LocationIdentification {
+        /// The format of the location, with 1 for coordinates, 2 for a civic address and 3 for an ECS ELIN
+        format: u8,
+        /// The location data in the specified format
+        location: &'a [u8]
+    },
+    /// The power requirements or capabilities of the system
+    ExtendedPowerViaMdi {
+        /// The power type, source and priority bits
+        power: u8,
+        /// The power value in units of 0.1 W
+        value: u16
+    },
+    /// The hardware revision of the system
+    HardwareRevision(&'a [u8]),
+    /// The firmware revision of the system
+    FirmwareRevision(&'a [u8]),
+    /// The software revision of the system
+    SoftwareRevision(&'a [u8]),
+    /// The serial number of the system
+    SerialNumber(&'a [u8]),
+    /// The name of the manufacturer of the system
+    ManufacturerName(&'a [u8]),
+    /// The model name of the system
+    ModelName(&'a [u8]),
+    /// The asset identifier of the system
+    AssetId(&'a [u8]),
+    /// A TLV with a subtype that isn't handled by this crate
+    Unknown {
+        /// The organizationally defined subtype
+        subtype: u8,
+        /// The information string after the subtype
+        info: &'a [u8]
+    }
+}
+
+impl<'a> Med<'a> {
+    /// The LLDP-MED capabilities subtype
+    pub const CAPABILITIES: u8 = 1;
+    /// The network policy subtype
+    pub const NETWORK_POLICY: u8 = 2;
+    /// The location identification subtype
+    pub const LOCATION_IDENTIFICATION: u8 = 3;
+    /// The extended power via MDI subtype
+    pub const EXTENDED_POWER_VIA_MDI: u8 = 4;
+    /// The hardware revision inventory subtype
+    pub const HARDWARE_REVISION: u8 = 5;
+    /// The firmware revision inventory subtype
+    pub const FIRMWARE_REVISION: u8 = 6;
+    /// The software revision inventory subtype
+    pub const SOFTWARE_REVISION: u8 = 7;
+    /// The serial number inventory subtype
+    pub const SERIAL_NUMBER: u8 = 8;
+    /// The manufacturer name inventory subtype
+    pub const MANUFACTURER_NAME: u8 = 9;
+    /// The model name inventory subtype
+    pub const MODEL_NAME: u8 = 10;
+    /// The asset ID inventory subtype
+    pub const ASSET_ID: u8 = 11;
+
+    /// Gets the subtype of the TLV
+    pub fn subtype(&self) -> u8 {
+        match self {
+            Med::Capabilities { .. } => Self::CAPABILITIES,
+            Med::NetworkPolicy(_) => Self::NETWORK_POLICY,
+            Med::LocationIdentification { .. } => Self::LOCATION_IDENTIFICATION,
+            Med::ExtendedPowerViaMdi { .. } => Self::EXTENDED_POWER_VIA_MDI,
+            Med::HardwareRevision(_) => Self::HARDWARE_REVISION,
+            Med::FirmwareRevision(_) => Self::FIRMWARE_REVISION,
+            Med::SoftwareRevision(_) => Self::SOFTWARE_REVISION,
+            Med::SerialNumber(_) => Self::SERIAL_NUMBER,
+            Med::ManufacturerName(_) => Self::MANUFACTURER_NAME,
+            Med::ModelName(_) => Self::MODEL_NAME,
+            Med::AssetId(_) => Self::ASSET_ID,
+            Med::Unknown { subtype, .. } => *subtype,
+        }
+    }
+
+    fn parse(subtype: u8, info: &'a [u8]) -> Option<Self> {
+        match (subtype, info.len()) {
+            (Self::CAPABILITIES, 3) => Some(Med::Capabilities { capabilities: u16_at(info, 0), device_type: info[2] }),
+            (Self::NETWORK_POLICY, 4) => Some(Med::NetworkPolicy(NetworkPolicy::parse(info))),
+            (Self::LOCATION_IDENTIFICATION, len) if len >= 1 => Some(Med::LocationIdentification { format: info[0], location: &info[1..] }),
+            (Self::EXTENDED_POWER_VIA_MDI, 3) => Some(Med::ExtendedPowerViaMdi { power: info[0], value: u16_at(info, 1) }),
+            (Self::HARDWARE_REVISION, len) if len <= 32 => Some(Med::HardwareRevision(info)),
+            (Self::FIRMWARE_REVISION, len) if len <= 32 => Some(Med::FirmwareRevision(info)),
+            (Self::SOFTWARE_REVISION, len) if len <= 32 => Some(Med::SoftwareRevision(info)),
+            (Self::SERIAL_NUMBER, len) if len <= 32 => Some(Med::SerialNumber(info)),
+            (Self::MANUFACTURER_NAME, len) if len <= 32 => Some(Med::ManufacturerName(info)),
+            (Self::MODEL_NAME, len) if len <= 32 => Some(Med::ModelName(info)),
+            (Self::ASSET_ID, len) if len <= 32 => Some(Med::AssetId(info)),
+            (subtype, _) if subtype > Self::ASSET_ID => Some(Med::Unknown { subtype, info }),
+            _ => None,
+        }
+    }
+}
+
+impl Sealed for Med<'_> { }
+/// The size of the information string after the subtype
+impl Size for Med<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Med::Capabilities { .. } | Med::ExtendedPowerViaMdi { .. } => 3,
+            Med::NetworkPolicy(_) => 4,
+            Med::LocationIdentification { location, .. } => 1 + location.len(),
+            Med::HardwareRevision(info) | Med::FirmwareRevision(info) | Med::SoftwareRevision(info) | Med::SerialNumber(info) |
+            Med::ManufacturerName(info) | Med::ModelName(info) | Med::AssetId(info) | Med::Unknown { info, .. } => info.len(),
+        }
+    }
+}
+/// Writes the information string after the subtype
+impl Write for Med<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        match self {
+            Med::Capabilities { capabilities, device_type } => {
+                cursor.write(capabilities)?;
+                cursor.write(device_type)?;
+            },
+            Med::NetworkPolicy(policy) => cursor.write(&policy.to_u32())?,
+            Med::LocationIdentification { format, location } => {
+                cursor.write(format)?;
+                cursor.write(*location)?;
+            },
+            Med::ExtendedPowerViaMdi { power, value } => {
+                cursor.write(power)?;
+                cursor.write(value)?;
+            },
+            Med::HardwareRevision(info) | Med::FirmwareRevision(info) | Med::SoftwareRevision(info) | Med::SerialNumber(info) |
+            Med::ManufacturerName(info) | Med::ModelName(info) | Med::AssetId(info) | Med::Unknown { info, .. } => cursor.write(*info)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// An organizationally specific TLV
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OrganizationallySpecific<'a> {
+    /// A TLV defined by IEEE 802.1
+    Ieee8021(Ieee8021<'a>),
+    /// A TLV defined by IEEE 802.3
+    Ieee8023(Ieee8023<'a>),
+    /// A TLV defined by LLDP-MED
+    Med(Med<'a>),
+    /// A TLV of an organization that isn't handled by this crate
+    Other {
+        /// The OUI of the organization
+        oui: [u8; 3],
+        /// The organizationally defined subtype
+        subtype: u8,
+        /// The information string after the subtype
+        info: &'a [u8]
+    }
+}
+
+impl<'a> OrganizationallySpecific<'a> {
+    /// The OUI of IEEE 802.1
+    pub const IEEE_802_1: [u8; 3] = llc::IEEE_802_1_OUI;
+    /// The OUI of IEEE 802.3
+    pub const IEEE_802_3: [u8; 3] = [0x00, 0x12, 0x0F];
+    /// The OUI of TIA TR-41 used by LLDP-MED
+    pub const MED: [u8; 3] = [0x00, 0x12, 0xBB];
+
+    /// Gets the OUI of the organization that defined the TLV
+    pub fn oui(&self) -> [u8; 3] {
+        match self {
+            OrganizationallySpecific::Ieee8021(_) => Self::IEEE_802_1,
+            OrganizationallySpecific::Ieee8023(_) => Self::IEEE_802_3,
+            OrganizationallySpecific::Med(_) => Self::MED,
+            OrganizationallySpecific::Other { oui, .. } => *oui,
+        }
+    }
+
+    /// Gets the organizationally defined subtype of the TLV
+    pub fn subtype(&self) -> u8 {
+        match self {
+            OrganizationallySpecific::Ieee8021(tlv) => tlv.subtype(),
+            OrganizationallySpecific::Ieee8023(tlv) => tlv.subtype(),
+            OrganizationallySpecific::Med(tlv) => tlv.subtype(),
+            OrganizationallySpecific::Other { subtype, .. } => *subtype,
+        }
+    }
+
+    fn parse(info: &'a [u8]) -> Option<Self> {
+        let header = info.get(..4)?;
+        let (oui, subtype, info) = ([header[0], header[1], header[2]], header[3], &info[4..]);
+        match oui {
+            Self::IEEE_802_1 => Ieee8021::parse(subtype, info).map(OrganizationallySpecific::Ieee8021),
+            Self::IEEE_802_3 => Ieee8023::parse(subtype, info).map(OrganizationallySpecific::Ieee8023),
+            Self::MED => Med::parse(subtype, info).map(OrganizationallySpecific::Med),
+            oui => Some(OrganizationallySpecific::Other { oui, subtype, info }),
+        }
+    }
+}
+
+impl Sealed for OrganizationallySpecific<'_> { }
+/// The size of the information string, including the OUI and subtype
+impl Size for OrganizationallySpecific<'_> {
+    fn size(&self) -> usize {
+        4 + match self {
+            OrganizationallySpecific::Ieee8021(tlv) => tlv.size(),
+            OrganizationallySpecific::Ieee8023(tlv) => tlv.size(),
+            OrganizationallySpecific::Med(tlv) => tlv.size(),
+            OrganizationallySpecific::Other { info, .. } => info.len(),
+        }
+    }
+}
+/// Writes the information string, including the OUI and subtype
+impl Write for OrganizationallySpecific<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.oui()[..])?;
+        cursor.write(&self.subtype())?;
+        match self {
+            OrganizationallySpecific::Ieee8021(tlv) => cursor.write(tlv)?,
+            OrganizationallySpecific::Ieee8023(tlv) => cursor.write(tlv)?,
+            OrganizationallySpecific::Med(tlv) => cursor.write(tlv)?,
+            OrganizationallySpecific::Other { info, .. } => cursor.write(*info)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// An optional TLV of an LLDPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Tlv<'a> {
+    /// A description of the port, such as its `ifDescr`
+    PortDescription(&'a [u8]),
+    /// The administratively assigned name of the system, such as its fully qualified domain name
+    SystemName(&'a [u8]),
+    /// A description of the system, including its hardware and software versions
+    SystemDescription(&'a [u8]),
+    /// The capabilities of the system, and which of them are enabled
+    SystemCapabilities {
+        /// The capabilities of the system
+        system: Capabilities,
+        /// The capabilities that are enabled
+        enabled: Capabilities
+    },
+    /// An address used to reach the management entity of the system
+    ManagementAddress(ManagementAddress<'a>),
+    /// A TLV defined by an organization, identified by its OUI
+    OrganizationallySpecific(OrganizationallySpecific<'a>),
+    /// A TLV with a reserved type. Writing fails if the type is one of the mandatory TLV types or doesn't fit in 7 bits.
+    Unknown {
+        /// The type of the TLV
+        kind: u8,
+        /// The information string of the TLV
+        info: &'a [u8]
+    }
+}
+
+impl<'a> Tlv<'a> {
+    /// The end of LLDPDU TLV type
+    pub const END: u8 = 0;
+    /// The chassis ID TLV type
+    pub const CHASSIS_ID: u8 = 1;
+    /// The port ID TLV type
+    pub const PORT_ID: u8 = 2;
+    /// The time to live TLV type
+    pub const TIME_TO_LIVE: u8 = 3;
+    /// The port description TLV type
+    pub const PORT_DESCRIPTION: u8 = 4;
+    /// The system name TLV type
+    pub const SYSTEM_NAME: u8 = 5;
+    /// The system description TLV type
+    pub const SYSTEM_DESCRIPTION: u8 = 6;
+    /// The system capabilities TLV type
+    pub const SYSTEM_CAPABILITIES: u8 = 7;
+    /// The management address TLV type
+    pub const MANAGEMENT_ADDRESS: u8 = 8;
+    /// The organizationally specific TLV type
+    pub const ORGANIZATIONALLY_SPECIFIC: u8 = 127;
+
+    /// Gets the type of the TLV
+    pub fn kind(&self) -> u8 {
+        match self {
+            Tlv::PortDescription(_) => Self::PORT_DESCRIPTION,
+            Tlv::SystemName(_) => Self::SYSTEM_NAME,
+            Tlv::SystemDescription(_) => Self::SYSTEM_DESCRIPTION,
+            Tlv::SystemCapabilities { .. } => Self::SYSTEM_CAPABILITIES,
+            Tlv::ManagementAddress(_) => Self::MANAGEMENT_ADDRESS,
+            Tlv::OrganizationallySpecific(_) => Self::ORGANIZATIONALLY_SPECIFIC,
+            Tlv::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    fn parse(kind: u8, info: &'a [u8]) -> Result<Self, ParseError> {
+        let invalid = ParseError::InvalidTlv { kind, length: info.len() as u16 };
+        match (kind, info.len()) {
+            (Self::PORT_DESCRIPTION, len) if len <= 255 => Ok(Tlv::PortDescription(info)),
+            (Self::SYSTEM_NAME, len) if len <= 255 => Ok(Tlv::SystemName(info)),
+            (Self::SYSTEM_DESCRIPTION, len) if len <= 255 => Ok(Tlv::SystemDescription(info)),
+            (Self::SYSTEM_CAPABILITIES, 4) => Ok(Tlv::SystemCapabilities {
+                system: Capabilities(u16_at(info, 0)),
+                enabled: Capabilities(u16_at(info, 2))
+            }),
+            (Self::MANAGEMENT_ADDRESS, _) => ManagementAddress::parse(info).map(Tlv::ManagementAddress).ok_or(invalid),
+            (Self::ORGANIZATIONALLY_SPECIFIC, _) => OrganizationallySpecific::parse(info).map(Tlv::OrganizationallySpecific).ok_or(invalid),
+            (Self::CHASSIS_ID, _) | (Self::PORT_ID, _) | (Self::TIME_TO_LIVE, _) | (Self::PORT_DESCRIPTION, _) |
+            (Self::SYSTEM_NAME, _) | (Self::SYSTEM_DESCRIPTION, _) | (Self::SYSTEM_CAPABILITIES, _) => Err(invalid),
+            (kind, _) => Ok(Tlv::Unknown { kind, info }),
+        }
+    }
+
+    fn info_size(&self) -> usize {
+        match self {
+            Tlv::PortDescription(info) | Tlv::SystemName(info) | Tlv::SystemDescription(info) | Tlv::Unknown { info, .. } => info.len(),
+            Tlv::SystemCapabilities { .. } => 4,
+            Tlv::ManagementAddress(address) => address.size(),
+            Tlv::OrganizationallySpecific(tlv) => tlv.size(),
+        }
+    }
+}
+
+impl Sealed for Tlv<'_> { }
+impl Size for Tlv<'_> {
+    fn size(&self) -> usize {
+        2 + self.info_size()
+    }
+}
+impl Write for Tlv<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        if let Tlv::Unknown { kind: Tlv::END..=Tlv::TIME_TO_LIVE, .. } = self {
+            return Err(physical::Error);
+        }
+        let mut cursor = Cursor::new(output);
+        write_header(&mut cursor, self.kind(), self.info_size())?;
+        match self {
+            Tlv::PortDescription(info) | Tlv::SystemName(info) | Tlv::SystemDescription(info) | Tlv::Unknown { info, .. } => cursor.write(*info)?,
+            Tlv::SystemCapabilities { system, enabled } => {
+                cursor.write(&system.0)?;
+                cursor.write(&enabled.0)?;
+            },
+            Tlv::ManagementAddress(address) => cursor.write(address)?,
+            Tlv::OrganizationallySpecific(tlv) => cursor.write(tlv)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+impl Sealed for [Tlv<'_>] { }
+impl Size for [Tlv<'_>] {
+    fn size(&self) -> usize {
+        self.iter().map(Size::size).sum()
+    }
+}
+impl Write for [Tlv<'_>] {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        for tlv in self {
+            cursor.write(tlv)?;
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// The unparsed optional TLVs of an LLDPDU, excluding the end of LLDPDU TLV
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Tlvs<'a>(pub &'a [u8]);
+
+impl<'a> Tlvs<'a> {
+    /// An empty TLV list
+    pub const EMPTY: Tlvs<'static> = Tlvs(&[]);
+
+    /// Returns an iterator over the TLVs in the list.
+    /// The iterator ends after an end of LLDPDU TLV, or returns an error if a TLV is malformed.
+    pub fn iter(&self) -> TlvsIter<'a> {
+        TlvsIter(self.0)
+    }
+
+    /// Gets the first system name in the list, ignoring any malformed TLVs
+    pub fn system_name(&self) -> Option<&'a [u8]> {
+        self.iter().find_map(|tlv| match tlv {
+            Ok(Tlv::SystemName(name)) => Some(name),
+            _ => None,
+        })
+    }
+}
+
+impl Sealed for Tlvs<'_> { }
+impl Size for Tlvs<'_> {
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+impl Write for Tlvs<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+
+/// An iterator over the optional TLVs of an LLDPDU
+#[derive(Clone, Debug)]
+pub struct TlvsIter<'a>(&'a [u8]);
+
+impl<'a> Iterator for TlvsIter<'a> {
+    type Item = Result<Tlv<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let tlv = split_tlv(self.0).and_then(|(kind, info, rest)| {
+            self.0 = rest;
+            match kind {
+                Tlv::END => Ok(None),
+                kind => Tlv::parse(kind, info).map(Some),
+            }
+        });
+        match tlv {
+            Ok(Some(tlv)) => Some(Ok(tlv)),
+            Ok(None) => {
+                self.0 = &[];
+                None
+            },
+            Err(e) => {
+                // the rest of the TLVs can't be found without a valid length
+                self.0 = &[];
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// An LLDP data unit
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Lldpdu<'a, T> {
+    /// The identifier of the chassis that sent the LLDPDU
+    pub chassis_id: ChassisId<'a>,
+    /// The identifier of the port the LLDPDU was sent from
+    pub port_id: PortId<'a>,
+    /// The number of seconds the information in the LLDPDU is valid for, or zero if it should be removed
+    pub time_to_live: u16,
+    /// The optional TLVs of the LLDPDU
+    pub tlvs: T
+}
+
+impl<'a, T> Lldpdu<'a, T> {
+    /// Creates a new LLDPDU. The optional TLVs can be a slice of [`Tlv`](enum.Tlv.html) values or an unparsed [`Tlvs`](struct.Tlvs.html) list.
+    pub fn new(chassis_id: ChassisId<'a>, port_id: PortId<'a>, time_to_live: u16, tlvs: T) -> Self {
+        Lldpdu { chassis_id, port_id, time_to_live, tlvs }
+    }
+
+    /// Gets whether the LLDPDU is a shutdown LLDPDU, telling the receiver to remove the information of the sender
+    pub fn is_shutdown(&self) -> bool {
+        self.time_to_live == 0
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses an LLDPDU. Data after the end of LLDPDU TLV is ignored.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into().0;
+
+        let (kind, info, bytes) = split_tlv(bytes)?;
+        if kind != Tlv::CHASSIS_ID {
+            return Err(ParseError::MissingTlv(Tlv::CHASSIS_ID));
+        }
+        let chassis_id = ChassisId::parse(info)?;
+
+        let (kind, info, bytes) = split_tlv(bytes)?;
+        if kind != Tlv::PORT_ID {
+            return Err(ParseError::MissingTlv(Tlv::PORT_ID));
+        }
+        let port_id = PortId::parse(info)?;
+
+        let (kind, info, bytes) = split_tlv(bytes)?;
+        if kind != Tlv::TIME_TO_LIVE {
+            return Err(ParseError::MissingTlv(Tlv::TIME_TO_LIVE));
+        }
+        if info.len() != 2 {
+            return Err(ParseError::InvalidTlv { kind, length: info.len() as u16 });
+        }
+        let time_to_live = u16_at(info, 0);
+
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (kind, _, next) = split_tlv(rest)?;
+            if kind == Tlv::END {
+                break;
+            }
+            rest = next;
+        }
+        let tlvs = Tlvs(&bytes[..bytes.len() - rest.len()]);
+
+        Ok(Lldpdu { chassis_id, port_id, time_to_live, tlvs })
+    }
+}
+
+impl<T> Sealed for Lldpdu<'_, T> { }
+/// The size of the LLDPDU, including the end of LLDPDU TLV
+impl<T: Size> Size for Lldpdu<'_, T> {
+    fn size(&self) -> usize {
+        self.chassis_id.size() + self.port_id.size() + 4 + self.tlvs.size() + 2
+    }
+}
+/// Writes the LLDPDU followed by the end of LLDPDU TLV
+impl<T: Write> Write for Lldpdu<'_, T> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.chassis_id)?;
+        cursor.write(&self.port_id)?;
+        write_header(&mut cursor, Tlv::TIME_TO_LIVE, 2)?;
+        cursor.write(&self.time_to_live)?;
+        cursor.write(&self.tlvs)?;
+        write_header(&mut cursor, Tlv::END, 0)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{dispatch, vlan, EtherType, Ethernet2, EthernetBase};
+
+    const SWITCH: MacAddr = MacAddr::new([0x00, 0x04, 0x96, 0x1F, 0xA7, 0x26]);
+
+    // an LLDPDU from a switch, with 802.1, 802.3 and LLDP-MED TLVs
+    const LLDPDU: [u8; 106] = [
+        0x02, 0x07, 0x04, 0x00, 0x04, 0x96, 0x1F, 0xA7, 0x26,
+        0x04, 0x05, 0x05, b'1', b'/', b'1', b'5',
+        0x06, 0x02, 0x00, 0x78,
+        0x0A, 0x04, b's', b'w', b'-', b'1',
+        0x0E, 0x04, 0x00, 0x14, 0x00, 0x04,
+        0x10, 0x0C, 0x05, 0x01, 0xC0, 0x00, 0x02, 0x01, 0x02, 0x00, 0x00, 0x00, 0x0F, 0x00,
+        0xFE, 0x06, 0x00, 0x80, 0xC2, 0x01, 0x00, 0x01,
+        0xFE, 0x0A, 0x00, 0x80, 0xC2, 0x03, 0x00, 0x0A, 0x03, b'l', b'a', b'b',
+        0xFE, 0x09, 0x00, 0x12, 0x0F, 0x01, 0x03, 0x6C, 0x00, 0x00, 0x1E,
+        0xFE, 0x06, 0x00, 0x12, 0x0F, 0x04, 0x05, 0xEE,
+        0xFE, 0x08, 0x00, 0x12, 0xBB, 0x02, 0x01, 0x40, 0x15, 0x6E,
+        0xFE, 0x07, 0x00, 0x12, 0xBB, 0x01, 0x00, 0x33, 0x04,
+        0x00, 0x00
+    ];
+
+    #[test]
+    fn parse_lldpdu() {
+        let lldpdu = Parsed::parse(&LLDPDU[..]).unwrap();
+        assert_eq!(lldpdu.chassis_id, ChassisId::MacAddress(SWITCH));
+        assert_eq!(lldpdu.port_id, PortId::InterfaceName(b"1/15"));
+        assert_eq!(lldpdu.time_to_live, 120);
+        assert!(!lldpdu.is_shutdown());
+        assert_eq!(lldpdu.tlvs.system_name(), Some(&b"sw-1"[..]));
+
+        let mut tlvs = lldpdu.tlvs.iter();
+        assert_eq!(tlvs.next(), Some(Ok(Tlv::SystemName(b"sw-1"))));
+        match tlvs.next() {
+            Some(Ok(Tlv::SystemCapabilities { system, enabled })) => {
+                assert!(system.bridge() && system.router());
+                assert!(enabled.bridge() && !enabled.router());
+            },
+            other => panic!("expected system capabilities, got {:?}", other),
+        }
+        assert_eq!(tlvs.next(), Some(Ok(Tlv::ManagementAddress(ManagementAddress::new(Ipv4Addr::new(192, 0, 2, 1), 15)))));
+        let org = |tlv| Some(Ok(Tlv::OrganizationallySpecific(tlv)));
+        assert_eq!(tlvs.next(), org(OrganizationallySpecific::Ieee8021(Ieee8021::PortVlanId(1))));
+        assert_eq!(tlvs.next(), org(OrganizationallySpecific::Ieee8021(Ieee8021::VlanName { vlan: 10, name: b"lab" })));
+        assert_eq!(tlvs.next(), org(OrganizationallySpecific::Ieee8023(Ieee8023::MacPhyConfigurationStatus {
+            autonegotiation: 3,
+            advertised_capability: 0x6C00,
+            operational_mau_type: 30
+        })));
+        assert_eq!(tlvs.next(), org(OrganizationallySpecific::Ieee8023(Ieee8023::MaximumFrameSize(1518))));
+        assert_eq!(tlvs.next(), org(OrganizationallySpecific::Med(Med::NetworkPolicy(NetworkPolicy {
+            application_type: 1,
+            unknown: false,
+            tagged: true,
+            vlan: 10,
+            priority: 5,
+            dscp: 46
+        }))));
+        assert_eq!(tlvs.next(), org(OrganizationallySpecific::Med(Med::Capabilities { capabilities: 0x0033, device_type: 4 })));
+        assert_eq!(tlvs.next(), None);
+
+        let mut buf = [0; 106];
+        assert_eq!(lldpdu.size(), LLDPDU.len());
+        assert_eq!(lldpdu.write(&mut buf), Ok(LLDPDU.len()));
+        assert_eq!(buf, LLDPDU);
+    }
+
+    #[test]
+    fn build_lldpdu() {
+        let tlvs = [
+            Tlv::PortDescription(b"uplink"),
+            Tlv::SystemName(b"sim-7"),
+            Tlv::SystemCapabilities { system: Capabilities::ROUTER | Capabilities::BRIDGE, enabled: Capabilities::ROUTER },
+            Tlv::ManagementAddress(ManagementAddress::new(NetworkAddress::Mac(SWITCH), 3)),
+            Tlv::OrganizationallySpecific(OrganizationallySpecific::Ieee8021(Ieee8021::ProtocolIdentity(&[0x88, 0xCC]))),
+            Tlv::OrganizationallySpecific(OrganizationallySpecific::Ieee8023(Ieee8023::PowerViaMdi { support: 0x0F, power_pair: 1, power_class: 4, extension: &[] })),
+            Tlv::OrganizationallySpecific(OrganizationallySpecific::Med(Med::SoftwareRevision(b"1.2.3"))),
+            Tlv::OrganizationallySpecific(OrganizationallySpecific::Other { oui: [0x00, 0x00, 0x0C], subtype: 1, info: &[1, 2] }),
+            Tlv::Unknown { kind: 9, info: &[0xFF] },
+        ];
+        let lldpdu = Lldpdu::new(
+            ChassisId::NetworkAddress(NetworkAddress::Ipv6(Ipv6Addr::LOCALHOST)),
+            PortId::Local(b"port 7"),
+            0,
+            &tlvs[..]
+        );
+        let size = lldpdu.size();
+        let frame = Ethernet2::new(NEAREST_BRIDGE_ADDRESS, SWITCH, vlan::Empty, EtherType::LLDP, lldpdu.clone());
+        let mut buf = [0; 256];
+        let len = frame.write(&mut buf).unwrap();
+        assert_eq!(len, 14 + size);
+
+        let parsed = EthernetBase::parse(&buf[..len]).unwrap().parse_vlan().unwrap_ethernet2().parse_payload().unwrap();
+        let parsed = match parsed.payload() {
+            dispatch::Payload::Lldp(parsed) => parsed.clone(),
+            other => panic!("expected an LLDPDU, got {:?}", other),
+        };
+        assert!(parsed.is_shutdown());
+        assert_eq!(parsed.chassis_id, lldpdu.chassis_id);
+        assert_eq!(parsed.port_id, lldpdu.port_id);
+        assert!(parsed.tlvs.iter().eq(tlvs.iter().map(|&tlv| Ok(tlv))));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Parsed::parse(&LLDPDU[..1]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&LLDPDU[..8]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&LLDPDU[9..]), Err(ParseError::MissingTlv(Tlv::CHASSIS_ID)));
+        assert_eq!(Parsed::parse(&[0x02, 0x02, 0x04, 0x00][..]), Err(ParseError::InvalidTlv { kind: Tlv::CHASSIS_ID, length: 2 }));
+
+        // the TLV list is truncated in the middle of the port VLAN ID TLV
+        assert_eq!(Parsed::parse(&LLDPDU[..50]), Err(ParseError::Truncated));
+
+        let mut tlvs = Tlvs(&[0x0E, 0x02, 0x00, 0x14, 0x0A, 0x01, b'x']).iter();
+        assert_eq!(tlvs.next(), Some(Err(ParseError::InvalidTlv { kind: Tlv::SYSTEM_CAPABILITIES, length: 2 })));
+        assert_eq!(tlvs.next(), None);
+
+        let tlvs = Tlvs(&[0xFE, 0x05, 0x00, 0x80, 0xC2, 0x01, 0x00]);
+        assert_eq!(tlvs.iter().next(), Some(Err(ParseError::InvalidTlv { kind: Tlv::ORGANIZATIONALLY_SPECIFIC, length: 5 })));
+
+        let long = [0; MAX_TLV_LEN + 1];
+        let tlvs = [Tlv::Unknown { kind: 9, info: &long }];
+        let lldpdu = Lldpdu::new(ChassisId::Local(b"c"), PortId::Local(b"p"), 120, &tlvs[..]);
+        assert_eq!(lldpdu.write(&mut [0; 1024]), Err(physical::Error));
+
+        let long = [b'x'; MAX_ID_LEN + 1];
+        assert_eq!(ChassisId::Local(&long).write(&mut [0; 1024]), Err(physical::Error));
+        assert_eq!(PortId::InterfaceName(&long).write(&mut [0; 1024]), Err(physical::Error));
+        assert_eq!(PortId::Local(&[]).write(&mut [0; 1024]), Err(physical::Error));
+
+        let mut buf = [0; 4];
+        assert_eq!(Tlv::Unknown { kind: 9, info: &[1, 2] }.write(&mut buf), Ok(4));
+        assert_eq!(Tlvs(&buf).iter().next(), Some(Ok(Tlv::Unknown { kind: 9, info: &[1, 2] })));
+        for &kind in &[Tlv::END, Tlv::CHASSIS_ID, Tlv::TIME_TO_LIVE, 128, 255] {
+            assert_eq!(Tlv::Unknown { kind, info: &[1, 2] }.write(&mut buf), Err(physical::Error));
+        }
+        assert_eq!(ChassisId::Local(&long[..MAX_ID_LEN]).write(&mut [0; 1024]), Ok(2 + 1 + MAX_ID_LEN));
+    }
+}
@@ -1,3 +1,11 @@
 //! A module containing link layer types for the internet protocol suite
 
-pub mod ethernet;
\ No newline at end of file
+pub mod arp;
+pub mod ethernet;
+pub mod llc;
+pub mod lldp;
+pub mod mpls;
+pub mod pppoe;
+pub mod sll;
+pub mod slow;
+pub mod stp;
\ No newline at end of file
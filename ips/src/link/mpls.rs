@@ -0,0 +1,570 @@
+//! Types for reading and writing MPLS label stacks
+//!
+//! MPLS packets are sent with the [`MPLS`](../ethernet/struct.EtherType.html#associatedconstant.MPLS) or
+//! [`MPLS_MULTICAST`](../ethernet/struct.EtherType.html#associatedconstant.MPLS_MULTICAST) ethertypes and start with a stack of
+//! label entries. The last entry has its bottom of stack bit set, and the payload after it isn't identified by the stack.
+//!
+//! Like VLAN extensions, label stacks can be kept unparsed in an [`Any`](struct.Any.html) value,
+//! or unwrapped into [`Stacked`](struct.Stacked.html) and [`Entry`](struct.Entry.html) values.
+
+use crate::internal::Sealed;
+use crate::network::{ipv4, ipv6};
+use crate::payload;
+use crate::physical::{self, Cursor, Read, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// An MPLS packet parsed from a slice of bytes
+pub type Parsed<'a> = Mpls<Any<'a>, payload::Unknown<'a>>;
+
+/// An error that occurs while parsing an MPLS label stack or its payload
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the entry with the bottom of stack bit set
+    Truncated,
+    /// The label stack didn't have the number of entries requested
+    UnexpectedDepth,
+    /// The payload looked like an IPv4 packet, but wasn't valid
+    Ipv4(ipv4::ParseError),
+    /// The payload looked like an IPv6 packet, but wasn't valid
+    Ipv6(ipv6::ParseError)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad MPLS label stack; expected bottom of stack, but ran out of data"),
+            ParseError::UnexpectedDepth => f.write_str("unexpected MPLS label stack depth"),
+            ParseError::Ipv4(e) => e.fmt(f),
+            ParseError::Ipv6(e) => e.fmt(f),
+        }
+    }
+}
+
+/// A 20-bit MPLS label
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Label(u32);
+
+impl Label {
+    /// The label of IPv4 packets that should be forwarded by their IPv4 header, only valid at the bottom of the stack
+    pub const IPV4_EXPLICIT_NULL: Label = Label(0);
+    /// The label of packets that should be delivered to the local software for processing
+    pub const ROUTER_ALERT: Label = Label(1);
+    /// The label of IPv6 packets that should be forwarded by their IPv6 header, only valid at the bottom of the stack
+    pub const IPV6_EXPLICIT_NULL: Label = Label(2);
+    /// The label used in signaling to request the label be popped, which never appears in packets
+    pub const IMPLICIT_NULL: Label = Label(3);
+    /// The label indicating the next entry is an entropy label
+    pub const ENTROPY_LABEL_INDICATOR: Label = Label(7);
+    /// The generic associated channel label, identifying packets with an associated channel header
+    pub const GAL: Label = Label(13);
+    /// The label of OAM packets
+    pub const OAM_ALERT: Label = Label(14);
+    /// The label indicating the next entry contains an extended special purpose label
+    pub const EXTENSION: Label = Label(15);
+    /// The largest label
+    pub const MAX: Label = Label(0xFFFFF);
+
+    /// Creates a new label, or returns `None` if the value is larger than 20 bits
+    pub fn new(v: u32) -> Option<Label> {
+        if v <= Self::MAX.0 {
+            Some(Label(v))
+        } else {
+            None
+        }
+    }
+
+    /// Gets whether the label is one of the special purpose labels from 0 to 15
+    pub const fn is_reserved(self) -> bool {
+        self.0 < 16
+    }
+
+    /// Gets the value of the label
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// A label stack entry
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entry(u32);
+
+impl Entry {
+    const LABEL_SHIFT: u32 = 12;
+
+    const TC_MASK: u32 = 0x0E00;
+    const TC_SHIFT: u32 = 9;
+
+    const BOS_MASK: u32 = 0x0100;
+
+    const TTL_MASK: u32 = 0x00FF;
+
+    /// Creates a new entry using the specified label, the low 3 bits of the traffic class, bottom of stack bit, and TTL
+    pub const fn new(label: Label, traffic_class: u8, bottom_of_stack: bool, ttl: u8) -> Entry {
+        Entry(
+            (label.0 << Self::LABEL_SHIFT) |
+            (((traffic_class & 0x7) as u32) << Self::TC_SHIFT) |
+            ((bottom_of_stack as u32) << 8) |
+            ttl as u32
+        )
+    }
+
+    /// Returns a new entry from the specified raw value
+    pub const fn raw(value: u32) -> Entry {
+        Entry(value)
+    }
+
+    /// Gets the label of the entry
+    pub const fn label(self) -> Label {
+        Label(self.0 >> Self::LABEL_SHIFT)
+    }
+
+    /// Gets the 3-bit traffic class of the entry
+    pub const fn traffic_class(self) -> u8 {
+        ((self.0 & Self::TC_MASK) >> Self::TC_SHIFT) as u8
+    }
+
+    /// Gets whether this entry is the last entry in the stack
+    pub const fn bottom_of_stack(self) -> bool {
+        (self.0 & Self::BOS_MASK) != 0
+    }
+
+    /// Gets the time to live of the entry
+    pub const fn ttl(self) -> u8 {
+        (self.0 & Self::TTL_MASK) as u8
+    }
+
+    /// Returns a copy of the entry with the bottom of stack bit set to the specified value
+    pub const fn with_bottom_of_stack(self, bottom_of_stack: bool) -> Entry {
+        Entry((self.0 & !Self::BOS_MASK) | ((bottom_of_stack as u32) << 8))
+    }
+
+    /// Gets the raw underlying value of this entry
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl Sealed for Entry { }
+impl Size for Entry {
+    fn size(&self) -> usize { 4 }
+}
+/// Writes the entry as is, which should have the bottom of stack bit set if it's the last entry in the stack
+impl Write for Entry {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+impl Read<'_> for Entry {
+    fn read(input: &[u8]) -> Result<Self, physical::Error> {
+        u32::read(input).map(Entry)
+    }
+}
+
+/// A label stack entry followed by the rest of the stack
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Stacked<S> {
+    /// The label stack entry
+    pub entry: Entry,
+    /// The entries after this one
+    pub remainder: S
+}
+
+impl<S> Stacked<S> {
+    pub fn map_remainder<T, F: FnOnce(S) -> T>(self, f: F) -> Stacked<T> {
+        let Stacked { entry, remainder } = self;
+        let remainder = f(remainder);
+        Stacked { entry, remainder }
+    }
+}
+
+impl<S> Sealed for Stacked<S> { }
+impl<S: Size> Size for Stacked<S> {
+    fn size(&self) -> usize {
+        self.entry.size() + self.remainder.size()
+    }
+}
+/// Writes the entry with the bottom of stack bit cleared, followed by the remainder
+impl<S: Write> Write for Stacked<S> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.entry.with_bottom_of_stack(false))?;
+        cursor.write(&self.remainder)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// Any unparsed label stack
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Any<'a> {
+    data: &'a [u8]
+}
+
+impl<'a> Any<'a> {
+    /// Parses a label stack, returning the stack and a new unknown payload that starts after the bottom of the stack.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the stack is truncated. See [`try_parse`](#method.try_parse) for a non-panicking version.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> (Self, payload::Unknown<'a>) {
+        Self::try_parse(payload).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Parses a label stack, returning the stack and a new unknown payload that starts after the bottom of the stack.
+    ///
+    /// If the payload ends before an entry with the bottom of stack bit set, this returns an error.
+    pub fn try_parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<(Self, payload::Unknown<'a>), ParseError> {
+        let payload = payload.into();
+        let mut read_len = 0;
+        loop {
+            let entry = Entry::read(payload.get(read_len..).unwrap_or(&[])).map_err(|_| ParseError::Truncated)?;
+            read_len += 4;
+            if entry.bottom_of_stack() {
+                break;
+            }
+        }
+        Ok((Any { data: &payload.0[..read_len] }, payload.consume(read_len)))
+    }
+
+    /// Returns an iterator over the entries of the stack, from the top of the stack to the bottom
+    pub fn iter(&self) -> Entries<'a> {
+        Entries(self.data)
+    }
+
+    /// Gets the number of entries in the stack
+    pub fn depth(&self) -> usize {
+        self.data.len() / 4
+    }
+
+    /// Gets the entry at the top of the stack, which is the first entry after the ethernet header
+    pub fn top(&self) -> Entry {
+        Entry::read(self.data).unwrap()
+    }
+
+    /// Gets the entry at the bottom of the stack, which is the last entry before the payload
+    pub fn bottom(&self) -> Entry {
+        Entry::read(&self.data[self.data.len() - 4..]).unwrap()
+    }
+
+    /// Gets the raw bytes of the stack
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Consumes the value, returning a new Stacked entry where the remainder is the rest of the stack.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the stack only has one entry. See [`try_unwrap_stack`](#method.try_unwrap_stack) for a non-panicking version.
+    pub fn unwrap_stack(self) -> Stacked<Any<'a>> {
+        self.try_unwrap_stack().unwrap_or_else(|e| panic!("expected stacked MPLS entries: {}", e))
+    }
+
+    /// Consumes the value, returning a new Stacked entry where the remainder is the rest of the stack,
+    /// or an error if the stack only has one entry.
+    pub fn try_unwrap_stack(self) -> Result<Stacked<Any<'a>>, ParseError> {
+        if self.depth() < 2 {
+            return Err(ParseError::UnexpectedDepth);
+        }
+        Ok(Stacked { entry: self.top(), remainder: Any { data: &self.data[4..] } })
+    }
+
+    /// Consumes the value, returning the single entry of the stack
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the stack has more than one entry. See [`try_unwrap_entry`](#method.try_unwrap_entry) for a non-panicking version.
+    pub fn unwrap_entry(self) -> Entry {
+        self.try_unwrap_entry().unwrap_or_else(|e| panic!("expected single MPLS entry: {}", e))
+    }
+
+    /// Consumes the value, returning the single entry of the stack, or an error if the stack has more than one entry.
+    pub fn try_unwrap_entry(self) -> Result<Entry, ParseError> {
+        if self.depth() != 1 {
+            return Err(ParseError::UnexpectedDepth);
+        }
+        Ok(self.top())
+    }
+}
+
+impl Sealed for Any<'_> { }
+impl Size for Any<'_> {
+    fn size(&self) -> usize {
+        self.data.len()
+    }
+}
+impl Write for Any<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.data.write(output)
+    }
+}
+
+/// An iterator over the entries of a label stack
+#[derive(Clone, Debug)]
+pub struct Entries<'a>(&'a [u8]);
+
+impl Iterator for Entries<'_> {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = Entry::read(self.0).ok()?;
+        self.0 = &self.0[4..];
+        Some(entry)
+    }
+}
+
+/// A pseudowire control word, which starts with a zero nibble
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ControlWord(pub u32);
+
+impl ControlWord {
+    /// Gets the 4 bits of flags specific to the pseudowire type
+    pub const fn flags(self) -> u8 {
+        ((self.0 >> 24) & 0xF) as u8
+    }
+
+    /// Gets the 2-bit fragmentation field
+    pub const fn fragment(self) -> u8 {
+        ((self.0 >> 22) & 0x3) as u8
+    }
+
+    /// Gets the length of the payload if it was padded to the minimum ethernet frame size, or zero if it wasn't
+    pub const fn length(self) -> u8 {
+        ((self.0 >> 16) & 0x3F) as u8
+    }
+
+    /// Gets the sequence number, or zero if sequencing isn't used
+    pub const fn sequence(self) -> u16 {
+        self.0 as u16
+    }
+}
+
+/// The payload after a label stack, identified by its first nibble
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Payload<'a> {
+    /// A payload starting with the nibble 4
+    Ipv4(ipv4::Parsed<'a>),
+    /// A payload starting with the nibble 6
+    Ipv6(ipv6::Parsed<'a>),
+    /// A payload starting with the nibble 0, containing a pseudowire control word followed by the emulated service payload
+    Pseudowire(ControlWord, payload::Unknown<'a>),
+    /// A payload starting with the nibble 1, containing an associated channel header followed by the channel message
+    AssociatedChannel {
+        /// The version of the associated channel header, which is zero
+        version: u8,
+        /// The type of the channel message, such as BFD or an OAM protocol
+        channel_type: u16,
+        /// The channel message after the header
+        payload: payload::Unknown<'a>
+    },
+    /// A payload starting with any other nibble, or too short to contain a control word or channel header
+    Unknown(payload::Unknown<'a>)
+}
+
+impl<'a> Payload<'a> {
+    /// Guesses the type of the payload after a label stack by its first nibble.
+    /// Pseudowires without a control word, such as Ethernet pseudowires, may be mistaken for IP packets.
+    pub fn guess<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let payload = payload.into();
+        let first = match payload.first() {
+            Some(first) => *first,
+            None => return Ok(Payload::Unknown(payload)),
+        };
+        match (first >> 4, payload.get(..4)) {
+            (4, _) => ipv4::Parsed::parse(payload).map(Payload::Ipv4).map_err(ParseError::Ipv4),
+            (6, _) => ipv6::Parsed::parse(payload).map(Payload::Ipv6).map_err(ParseError::Ipv6),
+            (0, Some(word)) => {
+                let word = u32::from_be_bytes(<[u8; 4]>::try_from(word).unwrap());
+                Ok(Payload::Pseudowire(ControlWord(word), payload.consume(4)))
+            },
+            (1, Some(header)) => Ok(Payload::AssociatedChannel {
+                version: header[0] & 0xF,
+                channel_type: u16::from_be_bytes([header[2], header[3]]),
+                payload: payload.consume(4)
+            }),
+            _ => Ok(Payload::Unknown(payload)),
+        }
+    }
+}
+
+impl Sealed for Payload<'_> { }
+impl Size for Payload<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Payload::Ipv4(packet) => packet.size(),
+            Payload::Ipv6(packet) => packet.size(),
+            Payload::Pseudowire(_, payload) | Payload::AssociatedChannel { payload, .. } => 4 + payload.len(),
+            Payload::Unknown(payload) => payload.len(),
+        }
+    }
+}
+impl Write for Payload<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        match self {
+            Payload::Ipv4(packet) => cursor.write(packet)?,
+            Payload::Ipv6(packet) => cursor.write(packet)?,
+            Payload::Pseudowire(word, payload) => {
+                cursor.write(&word.0)?;
+                cursor.write(payload)?;
+            },
+            Payload::AssociatedChannel { version, channel_type, payload } => {
+                cursor.write(&(0x10 | (version & 0xF)))?;
+                cursor.write(&0u8)?;
+                cursor.write(channel_type)?;
+                cursor.write(payload)?;
+            },
+            Payload::Unknown(payload) => cursor.write(payload)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// An MPLS packet, made of a label stack and the payload after it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mpls<S, P> {
+    /// The label stack of the packet
+    pub stack: S,
+    payload: P
+}
+
+impl<S, P> Mpls<S, P> {
+    /// Creates a new MPLS packet. The last entry of the stack should have its bottom of stack bit set.
+    pub fn new(stack: S, payload: P) -> Self {
+        Mpls { stack, payload }
+    }
+
+    /// Gets the payload after the label stack
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    pub fn map_payload<U, F: FnOnce(P) -> U>(self, f: F) -> Mpls<S, U> {
+        let Mpls { stack, payload } = self;
+        let payload = f(payload);
+        Mpls { stack, payload }
+    }
+
+    pub fn try_map_payload<U, E, F: FnOnce(P) -> Result<U, E>>(self, f: F) -> Result<Mpls<S, U>, E> {
+        let Mpls { stack, payload } = self;
+        let payload = f(payload)?;
+        Ok(Mpls { stack, payload })
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses an MPLS packet, leaving the label stack and payload unparsed
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let (stack, payload) = Any::try_parse(payload)?;
+        Ok(Mpls { stack, payload })
+    }
+}
+
+impl<'a, S> Mpls<S, payload::Unknown<'a>> {
+    /// Guesses the type of the payload by its first nibble. See [`Payload::guess`](enum.Payload.html#method.guess).
+    pub fn guess_payload(self) -> Result<Mpls<S, Payload<'a>>, ParseError> {
+        self.try_map_payload(Payload::guess)
+    }
+}
+
+impl<S, P> Sealed for Mpls<S, P> { }
+impl<S: Size, P: Size> Size for Mpls<S, P> {
+    fn size(&self) -> usize {
+        self.stack.size() + self.payload.size()
+    }
+}
+impl<S: Write, P: Write> Write for Mpls<S, P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.stack)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{dispatch, vlan, EtherType, Ethernet2, EthernetBase};
+    use crate::network::Protocol;
+    use core::net::Ipv4Addr;
+    use macress::{Address, Eui48};
+
+    #[test]
+    fn entries() {
+        let entry = Entry::new(Label::new(16004).unwrap(), 5, true, 64);
+        assert_eq!(entry.get(), 0x03E8_4B40);
+        assert_eq!(entry.label(), Label::new(16004).unwrap());
+        assert_eq!(entry.traffic_class(), 5);
+        assert!(entry.bottom_of_stack());
+        assert_eq!(entry.ttl(), 64);
+        assert!(!entry.with_bottom_of_stack(false).bottom_of_stack());
+        assert_eq!(Label::new(0x10_0000), None);
+        assert!(Label::GAL.is_reserved() && !entry.label().is_reserved());
+    }
+
+    #[test]
+    fn stack_and_guess() {
+        let ip = ipv4::Ipv4::new(Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(198, 51, 100, 1), Protocol::UDP, payload::Any(&[1, 2, 3, 4][..])).unwrap();
+        let stack = Stacked {
+            entry: Entry::new(Label::new(24001).unwrap(), 0, true, 255),
+            remainder: Stacked {
+                entry: Entry::new(Label::ENTROPY_LABEL_INDICATOR, 0, false, 0),
+                remainder: Entry::new(Label::new(0xABCDE).unwrap(), 0, true, 0)
+            }
+        };
+        let source: Address<Eui48> = Address::new([0x02, 0, 0, 0, 0, 1]);
+        let frame = Ethernet2::new(Address::<Eui48>::BROADCAST, source, vlan::Empty, EtherType::MPLS, Mpls::new(stack, ip));
+        let mut buf = [0; 64];
+        let len = frame.write(&mut buf).unwrap();
+        assert_eq!(len, 14 + 12 + 24);
+        assert_eq!(&buf[14..18], &[0x05, 0xDC, 0x10, 0xFF]);
+
+        let parsed = EthernetBase::parse(&buf[..len]).unwrap().parse_vlan().unwrap_ethernet2().parse_payload().unwrap();
+        let mpls = match parsed.payload() {
+            dispatch::Payload::Mpls(EtherType::MPLS, mpls) => mpls.clone(),
+            other => panic!("expected an MPLS packet, got {:?}", other),
+        };
+        assert_eq!(mpls.stack.depth(), 3);
+        assert_eq!(mpls.stack.iter().map(Entry::label).collect::<alloc::vec::Vec<_>>(),
+            [Label::new(24001).unwrap(), Label::ENTROPY_LABEL_INDICATOR, Label::new(0xABCDE).unwrap()]);
+        assert_eq!(mpls.stack.bottom().label(), Label::new(0xABCDE).unwrap());
+        assert!(mpls.stack.iter().take(2).all(|entry| !entry.bottom_of_stack()));
+
+        let unwrapped = mpls.stack.unwrap_stack().map_remainder(|s| s.unwrap_stack().map_remainder(Any::unwrap_entry));
+        assert_eq!(unwrapped.entry.label(), stack.entry.label());
+        assert_eq!(unwrapped.remainder.remainder, stack.remainder.remainder);
+
+        let guessed = mpls.guess_payload().unwrap();
+        match guessed.payload() {
+            Payload::Ipv4(packet) => assert_eq!(packet.source, Ipv4Addr::new(192, 0, 2, 1)),
+            other => panic!("expected an IPv4 packet, got {:?}", other),
+        }
+        let mut out = [0; 64];
+        assert_eq!(guessed.write(&mut out), Ok(36));
+        assert_eq!(&out[..36], &buf[14..50]);
+    }
+
+    #[test]
+    fn pseudowire_and_errors() {
+        let bytes = [0x00, 0x3E, 0x81, 0x40, 0x00, 0x00, 0x00, 0x07, 0x02, 0x00];
+        let (stack, payload) = Any::parse(&bytes[..]);
+        assert_eq!(stack.unwrap_entry().label(), Label::new(1000).unwrap());
+        match Payload::guess(payload).unwrap() {
+            Payload::Pseudowire(word, payload) => {
+                assert_eq!(word.sequence(), 7);
+                assert_eq!(payload.0, &[0x02, 0x00]);
+            },
+            other => panic!("expected a pseudowire, got {:?}", other),
+        }
+        let ach = Payload::guess(&[0x10, 0x00, 0x00, 0x07, 0xAA][..]).unwrap();
+        assert_eq!(ach, Payload::AssociatedChannel { version: 0, channel_type: 7, payload: payload::Unknown(&[0xAA]) });
+        assert_eq!(Payload::guess(&[0x02, 0x00][..]), Ok(Payload::Unknown(payload::Unknown(&[0x02, 0x00]))));
+        assert!(matches!(Payload::guess(&[0x45, 0x00][..]), Err(ParseError::Ipv4(_))));
+
+        assert_eq!(Any::try_parse(&bytes[..3]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&[0x00, 0x3E, 0x80, 0x40, 0x00][..]), Err(ParseError::Truncated));
+        assert_eq!(stack.try_unwrap_stack(), Err(ParseError::UnexpectedDepth));
+    }
+}
@@ -0,0 +1,661 @@
+//! Types for reading and writing PPPoE frames
+//!
+//! PPPoE discovery frames are sent with the [`PPPOE_DISCOVERY`](../ethernet/struct.EtherType.html#associatedconstant.PPPOE_DISCOVERY)
+//! ethertype and contain a list of tags, while session frames are sent with the
+//! [`PPPOE_SESSION`](../ethernet/struct.EtherType.html#associatedconstant.PPPOE_SESSION) ethertype and contain a PPP frame.
+
+use crate::internal::Sealed;
+use crate::network::{ipv4, ipv6};
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The length of a PPPoE header
+pub const HEADER_LEN: usize = 6;
+
+/// A PPPoE frame parsed from a slice of bytes
+pub type Parsed<'a> = Pppoe<payload::Padded<&'a [u8], payload::Any<'a>>>;
+/// A PPPoE discovery frame with unparsed tags
+pub type Discovery<'a> = Pppoe<payload::Padded<&'a [u8], Tags<'a>>>;
+/// A PPPoE session frame with a PPP payload decoded by its protocol
+pub type Session<'a> = Pppoe<payload::Padded<&'a [u8], Ppp<Payload<'a>>>>;
+
+/// An error that occurs while parsing a PPPoE frame, its tags, or its PPP payload
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the PPPoE header, the length of the payload, or a tag
+    Truncated,
+    /// The version and type field wasn't 0x11
+    UnsupportedVersion(u8),
+    /// The code was a discovery code in a session frame, or the session code in a discovery frame
+    UnexpectedCode(Code),
+    /// The PPP protocol wasn't the protocol requested
+    UnexpectedProtocol(Protocol),
+    /// A tag had a length that isn't valid for its type
+    InvalidTagLength {
+        /// The type of the tag
+        kind: u16,
+        /// The length of the tag value
+        length: u16
+    },
+    /// The PPP payload was a malformed IPv4 packet
+    Ipv4(ipv4::ParseError),
+    /// The PPP payload was a malformed IPv6 packet
+    Ipv6(ipv6::ParseError)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad PPPoE frame; ran out of data"),
+            ParseError::UnsupportedVersion(version) => write!(f, "bad PPPoE frame; unsupported version and type {:#04X}", version),
+            ParseError::UnexpectedCode(code) => write!(f, "unexpected PPPoE code {:#04X}", code.0),
+            ParseError::UnexpectedProtocol(protocol) => write!(f, "unexpected PPP protocol {:#06X}", protocol.0),
+            ParseError::InvalidTagLength { kind, length } => write!(f, "bad PPPoE tag; invalid length {} for tag type {:#06X}", length, kind),
+            ParseError::Ipv4(e) => e.fmt(f),
+            ParseError::Ipv6(e) => e.fmt(f),
+        }
+    }
+}
+
+/// The code of a PPPoE frame, identifying the discovery packet or the session stage
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Code(pub u8);
+
+impl Code {
+    /// A session frame
+    pub const SESSION: Code = Code(0x00);
+    /// PPPoE active discovery offer, sent by an access concentrator in response to a PADI
+    pub const PADO: Code = Code(0x07);
+    /// PPPoE active discovery initiation, broadcast by a host looking for access concentrators
+    pub const PADI: Code = Code(0x09);
+    /// PPPoE active discovery request, sent by a host to the access concentrator it chose
+    pub const PADR: Code = Code(0x19);
+    /// PPPoE active discovery session-confirmation, sent by an access concentrator with the new session ID
+    pub const PADS: Code = Code(0x65);
+    /// PPPoE active discovery terminate, sent by either side to end a session
+    pub const PADT: Code = Code(0xA7);
+
+    /// Gets whether the code is a discovery code
+    pub const fn is_discovery(self) -> bool {
+        self.0 != Self::SESSION.0
+    }
+}
+
+/// A PPPoE tag
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Tag<'a> {
+    /// The name of a service, or any service if the name is empty
+    ServiceName(&'a [u8]),
+    /// The name of the access concentrator
+    AcName(&'a [u8]),
+    /// A value used by the host to match responses to its requests
+    HostUniq(&'a [u8]),
+    /// A value used by the access concentrator to protect against denial of service attacks
+    AcCookie(&'a [u8]),
+    /// A vendor specific value, identified by the IANA enterprise number of the vendor
+    VendorSpecific {
+        /// The IANA enterprise number of the vendor
+        vendor_id: u32,
+        /// The value defined by the vendor
+        data: &'a [u8]
+    },
+    /// A value added by a relay agent, which must be echoed in responses
+    RelaySessionId(&'a [u8]),
+    /// The largest PPP payload the host can send and receive, from RFC 4638
+    PppMaxPayload(u16),
+    /// The requested service name wasn't accepted, with an optional UTF-8 explanation
+    ServiceNameError(&'a [u8]),
+    /// The access concentrator had an error, with an optional UTF-8 explanation
+    AcSystemError(&'a [u8]),
+    /// An unrecoverable error occurred, with an optional UTF-8 explanation
+    GenericError(&'a [u8]),
+    /// A tag of an unknown type
+    Unknown {
+        /// The type of the tag
+        kind: u16,
+        /// The value of the tag
+        data: &'a [u8]
+    }
+}
+
+impl Tag<'_> {
+    /// The end of list tag type
+    pub const END_OF_LIST: u16 = 0x0000;
+    /// The service name tag type
+    pub const SERVICE_NAME: u16 = 0x0101;
+    /// The access concentrator name tag type
+    pub const AC_NAME: u16 = 0x0102;
+    /// The host unique tag type
+    pub const HOST_UNIQ: u16 = 0x0103;
+    /// The access concentrator cookie tag type
+    pub const AC_COOKIE: u16 = 0x0104;
+    /// The vendor specific tag type
+    pub const VENDOR_SPECIFIC: u16 = 0x0105;
+    /// The relay session ID tag type
+    pub const RELAY_SESSION_ID: u16 = 0x0110;
+    /// The PPP max payload tag type
+    pub const PPP_MAX_PAYLOAD: u16 = 0x0120;
+    /// The service name error tag type
+    pub const SERVICE_NAME_ERROR: u16 = 0x0201;
+    /// The access concentrator system error tag type
+    pub const AC_SYSTEM_ERROR: u16 = 0x0202;
+    /// The generic error tag type
+    pub const GENERIC_ERROR: u16 = 0x0203;
+
+    /// Gets the type of the tag
+    pub fn kind(&self) -> u16 {
+        match self {
+            Tag::ServiceName(_) => Self::SERVICE_NAME,
+            Tag::AcName(_) => Self::AC_NAME,
+            Tag::HostUniq(_) => Self::HOST_UNIQ,
+            Tag::AcCookie(_) => Self::AC_COOKIE,
+            Tag::VendorSpecific { .. } => Self::VENDOR_SPECIFIC,
+            Tag::RelaySessionId(_) => Self::RELAY_SESSION_ID,
+            Tag::PppMaxPayload(_) => Self::PPP_MAX_PAYLOAD,
+            Tag::ServiceNameError(_) => Self::SERVICE_NAME_ERROR,
+            Tag::AcSystemError(_) => Self::AC_SYSTEM_ERROR,
+            Tag::GenericError(_) => Self::GENERIC_ERROR,
+            Tag::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    fn value_size(&self) -> usize {
+        match self {
+            Tag::VendorSpecific { data, .. } => 4 + data.len(),
+            Tag::PppMaxPayload(_) => 2,
+            Tag::ServiceName(data) | Tag::AcName(data) | Tag::HostUniq(data) | Tag::AcCookie(data) | Tag::RelaySessionId(data) |
+            Tag::ServiceNameError(data) | Tag::AcSystemError(data) | Tag::GenericError(data) | Tag::Unknown { data, .. } => data.len(),
+        }
+    }
+}
+
+impl Sealed for Tag<'_> { }
+impl Size for Tag<'_> {
+    fn size(&self) -> usize {
+        4 + self.value_size()
+    }
+}
+impl Write for Tag<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let length = u16::try_from(self.value_size()).map_err(|_| physical::Error)?;
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.kind())?;
+        cursor.write(&length)?;
+        match self {
+            Tag::VendorSpecific { vendor_id, data } => {
+                cursor.write(vendor_id)?;
+                cursor.write(*data)?;
+            },
+            Tag::PppMaxPayload(max) => cursor.write(max)?,
+            Tag::ServiceName(data) | Tag::AcName(data) | Tag::HostUniq(data) | Tag::AcCookie(data) | Tag::RelaySessionId(data) |
+            Tag::ServiceNameError(data) | Tag::AcSystemError(data) | Tag::GenericError(data) | Tag::Unknown { data, .. } => cursor.write(*data)?,
+        }
+        Ok(cursor.finish())
+    }
+}
+
+impl Sealed for [Tag<'_>] { }
+impl Size for [Tag<'_>] {
+    fn size(&self) -> usize {
+        self.iter().map(Size::size).sum()
+    }
+}
+impl Write for [Tag<'_>] {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        for tag in self {
+            cursor.write(tag)?;
+        }
+        Ok(cursor.finish())
+    }
+}
+
+/// The unparsed tags of a PPPoE discovery frame
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Tags<'a>(pub &'a [u8]);
+
+impl<'a> Tags<'a> {
+    /// An empty tag list
+    pub const EMPTY: Tags<'static> = Tags(&[]);
+
+    /// Returns an iterator over the tags in the list.
+    /// The iterator ends after an end of list tag, or returns an error if a tag is malformed.
+    pub fn iter(&self) -> TagsIter<'a> {
+        TagsIter(self.0)
+    }
+}
+
+impl Sealed for Tags<'_> { }
+impl Size for Tags<'_> {
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+impl Write for Tags<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+
+/// An iterator over the tags of a PPPoE discovery frame
+#[derive(Clone, Debug)]
+pub struct TagsIter<'a>(&'a [u8]);
+
+impl<'a> TagsIter<'a> {
+    fn parse_tag(&mut self) -> Result<Option<Tag<'a>>, ParseError> {
+        let header = self.0.get(..4).ok_or(ParseError::Truncated)?;
+        let kind = u16::from_be_bytes([header[0], header[1]]);
+        let length = u16::from_be_bytes([header[2], header[3]]);
+        let data = self.0.get(4..4 + length as usize).ok_or(ParseError::Truncated)?;
+        self.0 = &self.0[4 + data.len()..];
+
+        match (kind, data.len()) {
+            (Tag::END_OF_LIST, _) => Ok(None),
+            (Tag::SERVICE_NAME, _) => Ok(Some(Tag::ServiceName(data))),
+            (Tag::AC_NAME, _) => Ok(Some(Tag::AcName(data))),
+            (Tag::HOST_UNIQ, _) => Ok(Some(Tag::HostUniq(data))),
+            (Tag::AC_COOKIE, _) => Ok(Some(Tag::AcCookie(data))),
+            (Tag::VENDOR_SPECIFIC, len) if len >= 4 => Ok(Some(Tag::VendorSpecific {
+                vendor_id: u32::from_be_bytes(<[u8; 4]>::try_from(&data[..4]).unwrap()),
+                data: &data[4..]
+            })),
+            (Tag::RELAY_SESSION_ID, _) => Ok(Some(Tag::RelaySessionId(data))),
+            (Tag::PPP_MAX_PAYLOAD, 2) => Ok(Some(Tag::PppMaxPayload(u16::from_be_bytes([data[0], data[1]])))),
+            (Tag::SERVICE_NAME_ERROR, _) => Ok(Some(Tag::ServiceNameError(data))),
+            (Tag::AC_SYSTEM_ERROR, _) => Ok(Some(Tag::AcSystemError(data))),
+            (Tag::GENERIC_ERROR, _) => Ok(Some(Tag::GenericError(data))),
+            (Tag::VENDOR_SPECIFIC, _) | (Tag::PPP_MAX_PAYLOAD, _) => Err(ParseError::InvalidTagLength { kind, length }),
+            (kind, _) => Ok(Some(Tag::Unknown { kind, data })),
+        }
+    }
+}
+
+impl<'a> Iterator for TagsIter<'a> {
+    type Item = Result<Tag<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        match self.parse_tag() {
+            Ok(Some(tag)) => Some(Ok(tag)),
+            Ok(None) => {
+                self.0 = &[];
+                None
+            },
+            Err(e) => {
+                // the rest of the tags can't be found without a valid length
+                self.0 = &[];
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// A PPP protocol number
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Protocol(pub u16);
+
+impl Protocol {
+    /// Internet Protocol version 4
+    pub const IPV4: Protocol = Protocol(0x0021);
+    /// Internet Protocol version 6
+    pub const IPV6: Protocol = Protocol(0x0057);
+    /// MPLS unicast
+    pub const MPLS: Protocol = Protocol(0x0281);
+    /// The IP control protocol
+    pub const IPCP: Protocol = Protocol(0x8021);
+    /// The IPv6 control protocol
+    pub const IPV6CP: Protocol = Protocol(0x8057);
+    /// The link control protocol
+    pub const LCP: Protocol = Protocol(0xC021);
+    /// The password authentication protocol
+    pub const PAP: Protocol = Protocol(0xC023);
+    /// The link quality report protocol
+    pub const LQR: Protocol = Protocol(0xC025);
+    /// The challenge handshake authentication protocol
+    pub const CHAP: Protocol = Protocol(0xC223);
+    /// The extensible authentication protocol
+    pub const EAP: Protocol = Protocol(0xC227);
+}
+
+impl Sealed for Protocol { }
+impl Size for Protocol {
+    fn size(&self) -> usize { 2 }
+}
+impl Write for Protocol {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+
+/// The payload of a PPP frame decoded by its protocol
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Payload<'a> {
+    /// An IPv4 packet
+    Ipv4(ipv4::Parsed<'a>),
+    /// An IPv6 packet
+    Ipv6(ipv6::Parsed<'a>),
+    /// A payload with a protocol that isn't handled by this crate, such as a control protocol packet
+    Unknown(payload::Unknown<'a>)
+}
+
+impl Sealed for Payload<'_> { }
+impl Size for Payload<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Payload::Ipv4(packet) => packet.size(),
+            Payload::Ipv6(packet) => packet.size(),
+            Payload::Unknown(payload) => payload.len(),
+        }
+    }
+}
+impl Write for Payload<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        match self {
+            Payload::Ipv4(packet) => packet.write(output),
+            Payload::Ipv6(packet) => packet.write(output),
+            Payload::Unknown(payload) => payload.write(output),
+        }
+    }
+}
+
+/// A PPP frame in a PPPoE session, made of the protocol field and the payload.
+/// PPPoE sessions don't use the address, control and frame check sequence fields of PPP, and protocol field compression isn't supported.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ppp<P> {
+    /// The protocol of the payload
+    pub protocol: Protocol,
+    payload: P
+}
+
+impl<P> Ppp<P> {
+    /// Creates a new PPP frame
+    pub fn new(protocol: Protocol, payload: P) -> Self {
+        Ppp { protocol, payload }
+    }
+
+    /// Gets the payload of the frame
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    pub fn map_payload<U, F: FnOnce(P) -> U>(self, f: F) -> Ppp<U> {
+        let Ppp { protocol, payload } = self;
+        let payload = f(payload);
+        Ppp { protocol, payload }
+    }
+
+    pub fn try_map_payload<U, E, F: FnOnce(P) -> Result<U, E>>(self, f: F) -> Result<Ppp<U>, E> {
+        let Ppp { protocol, payload } = self;
+        let payload = f(payload)?;
+        Ok(Ppp { protocol, payload })
+    }
+}
+
+impl<'a> Ppp<payload::Unknown<'a>> {
+    /// Parses a PPP frame, leaving the payload unparsed
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        let protocol = bytes.get(..2).ok_or(ParseError::Truncated)?;
+        let protocol = Protocol(u16::from_be_bytes([protocol[0], protocol[1]]));
+        Ok(Ppp { protocol, payload: bytes.consume(2) })
+    }
+
+    /// Parses the payload as an IPv4 packet, returning an error if the protocol isn't [`IPV4`](struct.Protocol.html#associatedconstant.IPV4)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv4(self) -> Result<Ppp<ipv4::Parsed<'a>>, ParseError> {
+        if self.protocol != Protocol::IPV4 {
+            return Err(ParseError::UnexpectedProtocol(self.protocol));
+        }
+        self.try_map_payload(|p| ipv4::Parsed::parse(p).map_err(ParseError::Ipv4))
+    }
+
+    /// Parses the payload as an IPv6 packet, returning an error if the protocol isn't [`IPV6`](struct.Protocol.html#associatedconstant.IPV6)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv6(self) -> Result<Ppp<ipv6::Parsed<'a>>, ParseError> {
+        if self.protocol != Protocol::IPV6 {
+            return Err(ParseError::UnexpectedProtocol(self.protocol));
+        }
+        self.try_map_payload(|p| ipv6::Parsed::parse(p).map_err(ParseError::Ipv6))
+    }
+
+    /// Parses the payload by the protocol of the frame, leaving payloads of unknown protocols unparsed
+    pub fn parse_payload(self) -> Result<Ppp<Payload<'a>>, ParseError> {
+        let protocol = self.protocol;
+        self.try_map_payload(|p| match protocol {
+            Protocol::IPV4 => ipv4::Parsed::parse(p).map(Payload::Ipv4).map_err(ParseError::Ipv4),
+            Protocol::IPV6 => ipv6::Parsed::parse(p).map(Payload::Ipv6).map_err(ParseError::Ipv6),
+            _ => Ok(Payload::Unknown(p)),
+        })
+    }
+}
+
+impl<P> Sealed for Ppp<P> { }
+impl<P: Size> Size for Ppp<P> {
+    fn size(&self) -> usize {
+        2 + self.payload.size()
+    }
+}
+impl<P: Write> Write for Ppp<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.protocol)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// A PPPoE frame
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pppoe<P> {
+    /// The discovery packet code, or the session code
+    pub code: Code,
+    /// The ID of the session, or zero before the PADS assigns one
+    pub session_id: u16,
+    /// The length of the payload, excluding any ethernet padding
+    pub length: u16,
+    payload: P
+}
+
+impl<P> Pppoe<P> {
+    /// The version and type of PPPoE frames
+    pub const VERSION_TYPE: u8 = 0x11;
+
+    /// Creates a new PPPoE frame, using the size of the payload without any padding as its length.
+    ///
+    /// Returns an error if the size of the payload is too large for the length field.
+    pub fn new(code: Code, session_id: u16, payload: P) -> Result<Self, physical::Error> where P: Size {
+        let length = u16::try_from(payload.unpadded_size()).map_err(|_| physical::Error)?;
+        Ok(Pppoe { code, session_id, length, payload })
+    }
+
+    /// Gets the payload of the frame
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    pub fn map_payload<U, F: FnOnce(P) -> U>(self, f: F) -> Pppoe<U> {
+        let Pppoe { code, session_id, length, payload } = self;
+        let payload = f(payload);
+        Pppoe { code, session_id, length, payload }
+    }
+
+    pub fn try_map_payload<U, E, F: FnOnce(P) -> Result<U, E>>(self, f: F) -> Result<Pppoe<U>, E> {
+        let Pppoe { code, session_id, length, payload } = self;
+        let payload = f(payload)?;
+        Ok(Pppoe { code, session_id, length, payload })
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses a PPPoE frame. The payload of the frame is the data following the header up to the length of the frame,
+    /// and any data after the length is padding.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+        if bytes[0] != Self::VERSION_TYPE {
+            return Err(ParseError::UnsupportedVersion(bytes[0]));
+        }
+        let length = u16::from_be_bytes([bytes[4], bytes[5]]);
+        let payload = bytes.consume(HEADER_LEN).try_as_padded_any(length as usize).ok_or(ParseError::Truncated)?;
+        Ok(Pppoe {
+            code: Code(bytes[1]),
+            session_id: u16::from_be_bytes([bytes[2], bytes[3]]),
+            length,
+            payload
+        })
+    }
+
+    /// Parses the payload as the tags of a discovery frame, returning an error if the frame is a session frame
+    pub fn try_parse_discovery(self) -> Result<Discovery<'a>, ParseError> {
+        if !self.code.is_discovery() {
+            return Err(ParseError::UnexpectedCode(self.code));
+        }
+        Ok(self.map_payload(|p| payload::Padded { payload: Tags(p.payload.0), padding: p.padding }))
+    }
+
+    /// Parses the payload as a PPP frame and decodes it by its protocol, returning an error if the frame is a discovery frame
+    pub fn try_parse_session(self) -> Result<Session<'a>, ParseError> {
+        if self.code.is_discovery() {
+            return Err(ParseError::UnexpectedCode(self.code));
+        }
+        self.try_map_payload(|p| {
+            let ppp = Ppp::parse(p.payload)?.parse_payload()?;
+            Ok(payload::Padded { payload: ppp, padding: p.padding })
+        })
+    }
+}
+
+impl<P> Sealed for Pppoe<P> { }
+impl<P: Size> Size for Pppoe<P> {
+    fn size(&self) -> usize {
+        HEADER_LEN + self.payload.size()
+    }
+}
+/// Writes the frame using the length field as-is
+impl<P: Write> Write for Pppoe<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&Self::VERSION_TYPE)?;
+        cursor.write(&self.code.0)?;
+        cursor.write(&self.session_id)?;
+        cursor.write(&self.length)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{dispatch, vlan, EtherType, Ethernet2, EthernetBase};
+    use crate::network::Protocol as IpProtocol;
+    use core::net::Ipv4Addr;
+    use macress::{Address, Eui48};
+
+    const HOST: Address<Eui48> = Address::new([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    const AC: Address<Eui48> = Address::new([0x02, 0x00, 0x00, 0x00, 0x00, 0xAC]);
+
+    fn parse(buf: &[u8]) -> dispatch::Payload<'_> {
+        EthernetBase::parse(buf).unwrap().parse_vlan().unwrap_ethernet2().parse_payload().unwrap().payload().clone()
+    }
+
+    #[test]
+    fn discovery() {
+        let tags = [
+            Tag::ServiceName(b""),
+            Tag::HostUniq(&[0xDE, 0xAD, 0xBE, 0xEF]),
+            Tag::PppMaxPayload(1500),
+            Tag::VendorSpecific { vendor_id: 3561, data: &[0x01, 0x02] },
+        ];
+        let padi = Pppoe::new(Code::PADI, 0, &tags[..]).unwrap();
+        assert_eq!(padi.length, 28);
+        let frame = Ethernet2::new(Address::<Eui48>::BROADCAST, HOST, vlan::Empty, EtherType::PPPOE_DISCOVERY, padi);
+        let mut buf = [0; 60];
+        assert_eq!(frame.write(&mut buf), Ok(48));
+
+        // the frame is padded to the minimum ethernet frame size
+        let discovery = match parse(&buf) {
+            dispatch::Payload::PppoeDiscovery(discovery) => discovery,
+            other => panic!("expected a PPPoE discovery frame, got {:?}", other),
+        };
+        assert_eq!((discovery.code, discovery.session_id), (Code::PADI, 0));
+        assert_eq!(discovery.payload().padding.len(), 12);
+        let parsed = discovery.payload().payload.iter().collect::<Result<alloc::vec::Vec<_>, _>>().unwrap();
+        assert_eq!(parsed, tags);
+
+        // a PADS with an end of list tag, followed by data that isn't read
+        let pads = [
+            0x11, 0x65, 0x12, 0x34, 0x00, 0x0C,
+            0x01, 0x02, 0x00, 0x02, b'a', b'c',
+            0x00, 0x00, 0x00, 0x00,
+            0xFF, 0xFF
+        ];
+        let pads = Parsed::parse(&pads[..]).unwrap().try_parse_discovery().unwrap();
+        assert_eq!((pads.code, pads.session_id), (Code::PADS, 0x1234));
+        let mut tags = pads.payload().payload.iter();
+        assert_eq!(tags.next(), Some(Ok(Tag::AcName(b"ac"))));
+        assert_eq!(tags.next(), None);
+    }
+
+    #[test]
+    fn session() {
+        let ip = ipv4::Ipv4::new(Ipv4Addr::new(100, 64, 0, 10), Ipv4Addr::new(192, 0, 2, 1), IpProtocol::UDP, payload::Any(&[0; 8][..])).unwrap();
+        let session = Pppoe::new(Code::SESSION, 0x1234, Ppp::new(Protocol::IPV4, ip)).unwrap();
+        assert_eq!(session.length, 30);
+        let frame = Ethernet2::new(AC, HOST, vlan::Empty, EtherType::PPPOE_SESSION, session);
+        let mut buf = [0; 64];
+        let len = frame.write(&mut buf).unwrap();
+        assert_eq!(&buf[14..22], &[0x11, 0x00, 0x12, 0x34, 0x00, 0x1E, 0x00, 0x21]);
+
+        let session = match parse(&buf[..len]) {
+            dispatch::Payload::PppoeSession(session) => session,
+            other => panic!("expected a PPPoE session frame, got {:?}", other),
+        };
+        assert_eq!(session.session_id, 0x1234);
+        let ppp = &session.payload().payload;
+        assert_eq!(ppp.protocol, Protocol::IPV4);
+        match ppp.payload() {
+            Payload::Ipv4(packet) => assert_eq!(packet.source, Ipv4Addr::new(100, 64, 0, 10)),
+            other => panic!("expected an IPv4 packet, got {:?}", other),
+        }
+
+        let mut out = [0; 64];
+        assert_eq!(session.write(&mut out), Ok(len - 14));
+        assert_eq!(&out[..len - 14], &buf[14..len]);
+
+        let lcp = [0x11, 0x00, 0x00, 0x01, 0x00, 0x06, 0xC0, 0x21, 0x09, 0x01, 0x00, 0x04];
+        let lcp = Parsed::parse(&lcp[..]).unwrap();
+        let ppp = Ppp::parse(lcp.payload().payload.0).unwrap();
+        assert_eq!(ppp.clone().try_parse_ipv4(), Err(ParseError::UnexpectedProtocol(Protocol::LCP)));
+        assert_eq!(ppp.parse_payload().unwrap().payload(), &Payload::Unknown(payload::Unknown(&[0x09, 0x01, 0x00, 0x04])));
+        assert_eq!(lcp.try_parse_discovery(), Err(ParseError::UnexpectedCode(Code::SESSION)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Parsed::parse(&[0x11, 0x09, 0x00, 0x00, 0x00][..]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&[0x12, 0x09, 0x00, 0x00, 0x00, 0x00][..]), Err(ParseError::UnsupportedVersion(0x12)));
+        assert_eq!(Parsed::parse(&[0x11, 0x09, 0x00, 0x00, 0x00, 0x04, 0x01, 0x01][..]), Err(ParseError::Truncated));
+
+        let mut tags = Tags(&[0x01, 0x20, 0x00, 0x01, 0x05, 0x01, 0x01, 0x00, 0x00]).iter();
+        assert_eq!(tags.next(), Some(Err(ParseError::InvalidTagLength { kind: Tag::PPP_MAX_PAYLOAD, length: 1 })));
+        assert_eq!(tags.next(), None);
+        assert_eq!(Tags(&[0x01, 0x01, 0x00, 0x04, 0x00]).iter().next(), Some(Err(ParseError::Truncated)));
+
+        let data = [0; 65536];
+        assert_eq!(Pppoe::new(Code::SESSION, 1, payload::Any(&data[..])), Err(physical::Error));
+        assert_eq!(Pppoe::new(Code::SESSION, 1, payload::Any(&data[1..])).map(|pppoe| pppoe.length), Ok(0xFFFF));
+
+        let padr = Parsed::parse(&[0x11, 0x19, 0x00, 0x00, 0x00, 0x00][..]).unwrap();
+        assert_eq!(padr.try_parse_session(), Err(ParseError::UnexpectedCode(Code::PADR)));
+        let bad_ip = Parsed::parse(&[0x11, 0x00, 0x00, 0x01, 0x00, 0x03, 0x00, 0x21, 0x45][..]).unwrap();
+        assert!(matches!(bad_ip.try_parse_session(), Err(ParseError::Ipv4(_))));
+    }
+}
@@ -0,0 +1,402 @@
+//! Types for reading and writing Linux cooked capture headers
+//!
+//! Captures made on the Linux "any" device use these headers in place of link-layer headers, with link types
+//! [`LINUX_SLL`](../../pcap/struct.LinkType.html#associatedconstant.LINUX_SLL) and [`LINUX_SLL2`](../../pcap/struct.LinkType.html#associatedconstant.LINUX_SLL2).
+//! The protocol field of both headers is an ethertype, so their payloads are parsed the same way as the payloads of [`Ethernet2`](../ethernet/struct.Ethernet2.html) frames.
+
+use crate::internal::Sealed;
+use crate::link::arp;
+use crate::link::ethernet::{self, dispatch, EtherType};
+use crate::network::{ipv4, ipv6};
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use macress::{Address, Eui48};
+
+type MacAddr = Address<Eui48>;
+
+/// The length of a version 1 cooked capture header
+pub const HEADER_LEN: usize = 16;
+/// The length of a version 2 cooked capture header
+pub const SLL2_HEADER_LEN: usize = 20;
+
+/// A version 1 cooked capture header parsed from a slice of bytes, with the rest of the data as its payload
+pub type Parsed<'a> = Sll<payload::Unknown<'a>>;
+/// A version 2 cooked capture header parsed from a slice of bytes, with the rest of the data as its payload
+pub type Parsed2<'a> = Sll2<payload::Unknown<'a>>;
+
+/// An error that occurs while parsing a cooked capture header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of the header
+    Truncated
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad cooked capture header; ran out of data"),
+        }
+    }
+}
+
+/// The type of a packet relative to the host it was captured on
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PacketType(pub u16);
+
+impl PacketType {
+    /// A packet sent to the host
+    pub const HOST: PacketType = PacketType(0);
+    /// A packet broadcast by another host
+    pub const BROADCAST: PacketType = PacketType(1);
+    /// A packet multicast by another host
+    pub const MULTICAST: PacketType = PacketType(2);
+    /// A packet sent by another host to a third host
+    pub const OTHER_HOST: PacketType = PacketType(3);
+    /// A packet sent by the host
+    pub const OUTGOING: PacketType = PacketType(4);
+}
+
+/// A Linux `ARPHRD_` device type, identifying the type of the link-layer address
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct HardwareType(pub u16);
+
+impl HardwareType {
+    /// An ethernet device
+    pub const ETHERNET: HardwareType = HardwareType(1);
+    /// An IEEE 802.2 device
+    pub const IEEE802: HardwareType = HardwareType(6);
+    /// A PPP device
+    pub const PPP: HardwareType = HardwareType(512);
+    /// An IP over IP tunnel
+    pub const TUNNEL: HardwareType = HardwareType(768);
+    /// An IPv6 over IP tunnel
+    pub const TUNNEL6: HardwareType = HardwareType(769);
+    /// The loopback device
+    pub const LOOPBACK: HardwareType = HardwareType(772);
+    /// A GRE over IP tunnel
+    pub const IPGRE: HardwareType = HardwareType(778);
+    /// An IEEE 802.11 device
+    pub const IEEE80211: HardwareType = HardwareType(801);
+    /// A netlink socket, where the protocol field is the netlink family rather than an ethertype
+    pub const NETLINK: HardwareType = HardwareType(824);
+    /// A device with no link-layer header
+    pub const NONE: HardwareType = HardwareType(0xFFFE);
+}
+
+/// The link-layer address of the sender of a packet, which is truncated to 8 bytes
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct LinkAddress {
+    /// The length of the address, which may be longer than the bytes stored
+    pub length: u16,
+    /// The first 8 bytes of the address, followed by zeroes if the address is shorter
+    pub bytes: [u8; 8]
+}
+
+impl LinkAddress {
+    /// An empty link-layer address
+    pub const EMPTY: LinkAddress = LinkAddress { length: 0, bytes: [0; 8] };
+
+    /// Creates a new link-layer address, returning `None` if the address is longer than 8 bytes
+    pub fn new(address: &[u8]) -> Option<Self> {
+        let mut bytes = [0; 8];
+        bytes.get_mut(..address.len())?.copy_from_slice(address);
+        Some(LinkAddress { length: address.len() as u16, bytes })
+    }
+
+    /// Gets the bytes of the address stored in the header
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..(self.length as usize).min(8)]
+    }
+
+    /// Gets the address as an ethernet MAC address, or `None` if the address isn't 6 bytes long
+    pub fn to_mac(&self) -> Option<MacAddr> {
+        <[u8; 6]>::try_from(self.as_bytes()).ok().map(MacAddr::new)
+    }
+}
+
+impl From<MacAddr> for LinkAddress {
+    fn from(address: MacAddr) -> Self {
+        LinkAddress::new(&address.get()).unwrap()
+    }
+}
+
+/// A version 1 cooked capture header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Sll<P> {
+    /// The type of the packet relative to the capturing host
+    pub packet_type: PacketType,
+    /// The device type of the interface the packet was captured on
+    pub hardware_type: HardwareType,
+    /// The link-layer address of the sender
+    pub address: LinkAddress,
+    /// The protocol of the payload, which is an ethertype for most device types
+    pub protocol: EtherType,
+    payload: P
+}
+
+impl<P> Sll<P> {
+    /// Creates a new header for a packet with the specified protocol and payload
+    pub fn new(packet_type: PacketType, hardware_type: HardwareType, address: LinkAddress, protocol: EtherType, payload: P) -> Self {
+        Sll { packet_type, hardware_type, address, protocol, payload }
+    }
+    /// Gets a reference to the payload of the packet
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Sll<Q> {
+        let Sll { packet_type, hardware_type, address, protocol, payload } = self;
+        Sll { packet_type, hardware_type, address, protocol, payload: f(payload) }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Sll<Q>, E> {
+        let Sll { packet_type, hardware_type, address, protocol, payload } = self;
+        Ok(Sll { packet_type, hardware_type, address, protocol, payload: f(payload)? })
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses a version 1 cooked capture header from a slice of bytes
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+        let u16_at = |start: usize| u16::from_be_bytes([bytes[start], bytes[start + 1]]);
+
+        Ok(Sll {
+            packet_type: PacketType(u16_at(0)),
+            hardware_type: HardwareType(u16_at(2)),
+            address: LinkAddress { length: u16_at(4), bytes: <[u8; 8]>::try_from(&bytes[6..14]).unwrap() },
+            protocol: EtherType(u16_at(14)),
+            payload: bytes.consume(HEADER_LEN)
+        })
+    }
+
+    /// Parses the payload as an IPv4 packet, returning an error if the protocol isn't [`IPV4`](../ethernet/struct.EtherType.html#associatedconstant.IPV4)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv4(self) -> Result<Sll<ipv4::Parsed<'a>>, ethernet::PayloadError<ipv4::ParseError>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| ethernet::parse_ipv4(protocol, payload))
+    }
+
+    /// Parses the payload as an IPv6 packet, returning an error if the protocol isn't [`IPV6`](../ethernet/struct.EtherType.html#associatedconstant.IPV6)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv6(self) -> Result<Sll<ipv6::Parsed<'a>>, ethernet::PayloadError<ipv6::ParseError>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| ethernet::parse_ipv6(protocol, payload))
+    }
+
+    /// Parses the payload as an ARP packet, returning an error if the protocol isn't [`ARP`](../ethernet/struct.EtherType.html#associatedconstant.ARP)
+    /// or [`RARP`](../ethernet/struct.EtherType.html#associatedconstant.RARP) or the packet is malformed.
+    pub fn try_parse_arp(self) -> Result<Sll<arp::Parsed<'a>>, ethernet::PayloadError<arp::ParseError>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| ethernet::parse_arp(protocol, payload))
+    }
+
+    /// Parses the payload by its protocol, leaving payloads with unknown protocols unparsed
+    pub fn parse_payload(self) -> Result<Sll<dispatch::Payload<'a>>, dispatch::ParseError> {
+        self.parse_payload_with()
+    }
+
+    /// Parses the payload by its protocol, using the extension to parse ethertypes that aren't handled by this crate
+    pub fn parse_payload_with<X: dispatch::Extension<'a>>(self) -> Result<Sll<dispatch::Payload<'a, X>>, dispatch::ParseError<X::Error>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| dispatch::Payload::parse_with(protocol, payload))
+    }
+}
+
+impl<P> Sealed for Sll<P> { }
+impl<P: Size> Size for Sll<P> {
+    fn size(&self) -> usize {
+        HEADER_LEN + self.payload.size()
+    }
+}
+impl<P: Write> Write for Sll<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.packet_type.0)?;
+        cursor.write(&self.hardware_type.0)?;
+        cursor.write(&self.address.length)?;
+        cursor.write(&self.address.bytes[..])?;
+        cursor.write(&self.protocol)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// A version 2 cooked capture header, which adds the index of the interface the packet was captured on
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Sll2<P> {
+    /// The protocol of the payload, which is an ethertype for most device types
+    pub protocol: EtherType,
+    /// The index of the interface the packet was captured on
+    pub interface_index: u32,
+    /// The device type of the interface the packet was captured on
+    pub hardware_type: HardwareType,
+    /// The type of the packet relative to the capturing host, which is written as a single byte
+    pub packet_type: PacketType,
+    /// The link-layer address of the sender, whose length is written as a single byte
+    pub address: LinkAddress,
+    payload: P
+}
+
+impl<P> Sll2<P> {
+    /// Creates a new header for a packet with the specified protocol and payload
+    pub fn new(protocol: EtherType, interface_index: u32, hardware_type: HardwareType, packet_type: PacketType, address: LinkAddress, payload: P) -> Self {
+        Sll2 { protocol, interface_index, hardware_type, packet_type, address, payload }
+    }
+    /// Gets a reference to the payload of the packet
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Sll2<Q> {
+        let Sll2 { protocol, interface_index, hardware_type, packet_type, address, payload } = self;
+        Sll2 { protocol, interface_index, hardware_type, packet_type, address, payload: f(payload) }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Sll2<Q>, E> {
+        let Sll2 { protocol, interface_index, hardware_type, packet_type, address, payload } = self;
+        Ok(Sll2 { protocol, interface_index, hardware_type, packet_type, address, payload: f(payload)? })
+    }
+}
+
+impl<'a> Parsed2<'a> {
+    /// Parses a version 2 cooked capture header from a slice of bytes
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < SLL2_HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        Ok(Sll2 {
+            protocol: EtherType(u16::from_be_bytes([bytes[0], bytes[1]])),
+            interface_index: u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[4..8]).unwrap()),
+            hardware_type: HardwareType(u16::from_be_bytes([bytes[8], bytes[9]])),
+            packet_type: PacketType(bytes[10] as u16),
+            address: LinkAddress { length: bytes[11] as u16, bytes: <[u8; 8]>::try_from(&bytes[12..20]).unwrap() },
+            payload: bytes.consume(SLL2_HEADER_LEN)
+        })
+    }
+
+    /// Parses the payload as an IPv4 packet, returning an error if the protocol isn't [`IPV4`](../ethernet/struct.EtherType.html#associatedconstant.IPV4)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv4(self) -> Result<Sll2<ipv4::Parsed<'a>>, ethernet::PayloadError<ipv4::ParseError>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| ethernet::parse_ipv4(protocol, payload))
+    }
+
+    /// Parses the payload as an IPv6 packet, returning an error if the protocol isn't [`IPV6`](../ethernet/struct.EtherType.html#associatedconstant.IPV6)
+    /// or the packet is malformed.
+    pub fn try_parse_ipv6(self) -> Result<Sll2<ipv6::Parsed<'a>>, ethernet::PayloadError<ipv6::ParseError>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| ethernet::parse_ipv6(protocol, payload))
+    }
+
+    /// Parses the payload as an ARP packet, returning an error if the protocol isn't [`ARP`](../ethernet/struct.EtherType.html#associatedconstant.ARP)
+    /// or [`RARP`](../ethernet/struct.EtherType.html#associatedconstant.RARP) or the packet is malformed.
+    pub fn try_parse_arp(self) -> Result<Sll2<arp::Parsed<'a>>, ethernet::PayloadError<arp::ParseError>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| ethernet::parse_arp(protocol, payload))
+    }
+
+    /// Parses the payload by its protocol, leaving payloads with unknown protocols unparsed
+    pub fn parse_payload(self) -> Result<Sll2<dispatch::Payload<'a>>, dispatch::ParseError> {
+        self.parse_payload_with()
+    }
+
+    /// Parses the payload by its protocol, using the extension to parse ethertypes that aren't handled by this crate
+    pub fn parse_payload_with<X: dispatch::Extension<'a>>(self) -> Result<Sll2<dispatch::Payload<'a, X>>, dispatch::ParseError<X::Error>> {
+        let protocol = self.protocol;
+        self.try_map_payload(|payload| dispatch::Payload::parse_with(protocol, payload))
+    }
+}
+
+impl<P> Sealed for Sll2<P> { }
+impl<P: Size> Size for Sll2<P> {
+    fn size(&self) -> usize {
+        SLL2_HEADER_LEN + self.payload.size()
+    }
+}
+/// Writes the header, returning an error if the packet type or address length don't fit in a single byte
+impl<P: Write> Write for Sll2<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let packet_type = u8::try_from(self.packet_type.0).map_err(|_| physical::Error)?;
+        let address_length = u8::try_from(self.address.length).map_err(|_| physical::Error)?;
+
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.protocol)?;
+        cursor.write(&0u16)?;
+        cursor.write(&self.interface_index)?;
+        cursor.write(&self.hardware_type.0)?;
+        cursor.write(&packet_type)?;
+        cursor.write(&address_length)?;
+        cursor.write(&self.address.bytes[..])?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Protocol;
+    use crate::pcap::{self, LinkType, Record, Timestamp};
+    use core::net::Ipv6Addr;
+
+    const SOURCE: MacAddr = MacAddr::new([0x02, 0, 0, 0, 0, 1]);
+
+    // an SLL header for an outgoing ARP request from an ethernet device
+    const ARP_REQUEST: [u8; 44] = [
+        0x00, 0x04, 0x00, 0x01, 0x00, 0x06, 0x02, 0, 0, 0, 0, 1, 0, 0, 0x08, 0x06,
+        0x00, 0x01, 0x08, 0x00, 6, 4, 0x00, 0x01, 0x02, 0, 0, 0, 0, 1, 192, 0, 2, 1, 0, 0, 0, 0, 0, 0, 192, 0, 2, 2
+    ];
+
+    #[test]
+    fn parse_sll() {
+        let packet = Parsed::parse(&ARP_REQUEST[..]).unwrap();
+        assert_eq!(packet.packet_type, PacketType::OUTGOING);
+        assert_eq!(packet.hardware_type, HardwareType::ETHERNET);
+        assert_eq!(packet.address.to_mac(), Some(SOURCE));
+        assert_eq!(packet.protocol, EtherType::ARP);
+        assert_eq!(packet.size(), ARP_REQUEST.len());
+
+        let mut buf = [0; 44];
+        assert_eq!(packet.write(&mut buf), Ok(44));
+        assert_eq!(buf, ARP_REQUEST);
+
+        assert_eq!(packet.try_parse_ipv4().unwrap_err(), ethernet::PayloadError::UnexpectedEtherType(EtherType::ARP));
+        let arp = packet.try_parse_arp().unwrap();
+        assert_eq!(arp.payload().payload.sender_hardware, SOURCE);
+        assert_eq!(Parsed::parse(&ARP_REQUEST[..15]).unwrap_err(), ParseError::Truncated);
+    }
+
+    #[test]
+    fn sll2_from_pcap() {
+        let ipv6 = ipv6::Ipv6::new(Ipv6Addr::LOCALHOST, Ipv6Addr::LOCALHOST, Protocol::UDP, payload::Any(&[1, 2, 3, 4])).unwrap();
+        let packet = Sll2::new(EtherType::IPV6, 1, HardwareType::LOOPBACK, PacketType::HOST, LinkAddress::EMPTY, ipv6);
+        assert_eq!(packet.size(), SLL2_HEADER_LEN + 44);
+
+        let mut frame = [0; 64];
+        assert_eq!(packet.write(&mut frame), Ok(64));
+        assert_eq!(&frame[..12], &[0x86, 0xDD, 0, 0, 0, 0, 0, 1, 0x03, 0x04, 0, 0]);
+
+        let header = pcap::Header::new(LinkType::LINUX_SLL2, 0);
+        let mut file = [0; 128];
+        let mut written = header.write(&mut file).unwrap();
+        written += header.write_record(&Record::new(Timestamp::default(), &frame), &mut file[written..]).unwrap();
+
+        let mut reader = pcap::Reader::new(&file[..written]).unwrap().expect_link_type(LinkType::LINUX_SLL2).unwrap();
+        let record = reader.next().unwrap().unwrap();
+        let parsed = Parsed2::parse(record.data).unwrap();
+        assert_eq!(parsed.interface_index, 1);
+        assert_eq!(parsed.address.as_bytes(), &[]);
+        assert_eq!(parsed.address.to_mac(), None);
+        let parsed = parsed.try_parse_ipv6().unwrap();
+        assert_eq!(parsed.payload().payload().payload.0, &[1, 2, 3, 4]);
+
+        let mut too_long = packet;
+        too_long.packet_type = PacketType(0x100);
+        assert_eq!(too_long.write(&mut frame), Err(physical::Error));
+    }
+}
@@ -0,0 +1,435 @@
+//! Types for reading and writing Slow Protocols PDUs
+//!
+//! Slow Protocols PDUs are sent with the [`SLOW_PROTOCOLS`](../ethernet/struct.EtherType.html#associatedconstant.SLOW_PROTOCOLS)
+//! ethertype to the [`SLOW_PROTOCOLS_ADDRESS`](constant.SLOW_PROTOCOLS_ADDRESS.html), and start with a subtype identifying the protocol.
+//! This module decodes the LACPDUs and Marker PDUs of the link aggregation control protocol.
+
+use crate::internal::Sealed;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::BitOr;
+use macress::{Address, Eui48};
+
+type MacAddr = Address<Eui48>;
+
+/// The destination address of Slow Protocols PDUs
+pub const SLOW_PROTOCOLS_ADDRESS: MacAddr = MacAddr::new([0x01, 0x80, 0xC2, 0x00, 0x00, 0x02]);
+
+/// The length of an LACPDU or Marker PDU, including the subtype and the reserved octets at the end
+pub const PDU_LEN: usize = 110;
+
+const INFO_LEN: u8 = 20;
+const COLLECTOR_LEN: u8 = 16;
+const MARKER_LEN: u8 = 16;
+const ACTOR_TYPE: u8 = 0x01;
+const PARTNER_TYPE: u8 = 0x02;
+const COLLECTOR_TYPE: u8 = 0x03;
+
+/// A Slow Protocols PDU parsed from a slice of bytes
+pub type Parsed<'a> = Pdu<'a>;
+
+/// An error that occurs while parsing a Slow Protocols PDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of the PDU
+    Truncated,
+    /// A TLV had an unexpected type or length
+    InvalidTlv {
+        /// The type of the TLV
+        kind: u8,
+        /// The length of the TLV, including the type and length octets
+        length: u8
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad slow protocols PDU; ran out of data"),
+            ParseError::InvalidTlv { kind, length } => write!(f, "bad slow protocols PDU; unexpected TLV of type {} with length {}", kind, length),
+        }
+    }
+}
+
+/// The protocol of a Slow Protocols PDU
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Subtype(pub u8);
+
+impl Subtype {
+    /// The link aggregation control protocol
+    pub const LACP: Subtype = Subtype(1);
+    /// The link aggregation marker protocol
+    pub const MARKER: Subtype = Subtype(2);
+    /// Ethernet OAM
+    pub const OAM: Subtype = Subtype(3);
+    /// The organization specific slow protocol
+    pub const OSSP: Subtype = Subtype(10);
+}
+
+/// The state of an actor or partner of an LACPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct State(u8);
+
+impl State {
+    /// No flags set
+    pub const NONE: State = State(0);
+    /// The port uses active LACP, rather than passive LACP
+    pub const ACTIVITY: State = State(0x01);
+    /// The port uses the short timeout, rather than the long timeout
+    pub const TIMEOUT: State = State(0x02);
+    /// The link can be aggregated, rather than being an individual link
+    pub const AGGREGATION: State = State(0x04);
+    /// The link is allocated to the correct link aggregation group
+    pub const SYNCHRONIZATION: State = State(0x08);
+    /// Collection of incoming frames is enabled
+    pub const COLLECTING: State = State(0x10);
+    /// Distribution of outgoing frames is enabled
+    pub const DISTRIBUTING: State = State(0x20);
+    /// The actor is using defaulted partner information, since no LACPDUs were received from the partner
+    pub const DEFAULTED: State = State(0x40);
+    /// The receive state machine of the actor is in the expired state
+    pub const EXPIRED: State = State(0x80);
+
+    /// Returns a new state from the specified value
+    pub const fn raw(value: u8) -> State {
+        State(value)
+    }
+
+    /// Gets whether all of the specified flags are set
+    pub const fn contains(self, other: State) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Gets whether the activity flag is set
+    pub const fn activity(self) -> bool { self.contains(Self::ACTIVITY) }
+    /// Gets whether the timeout flag is set
+    pub const fn timeout(self) -> bool { self.contains(Self::TIMEOUT) }
+    /// Gets whether the aggregation flag is set
+    pub const fn aggregation(self) -> bool { self.contains(Self::AGGREGATION) }
+    /// Gets whether the synchronization flag is set
+    pub const fn synchronization(self) -> bool { self.contains(Self::SYNCHRONIZATION) }
+    /// Gets whether the collecting flag is set
+    pub const fn collecting(self) -> bool { self.contains(Self::COLLECTING) }
+    /// Gets whether the distributing flag is set
+    pub const fn distributing(self) -> bool { self.contains(Self::DISTRIBUTING) }
+    /// Gets whether the defaulted flag is set
+    pub const fn defaulted(self) -> bool { self.contains(Self::DEFAULTED) }
+    /// Gets whether the expired flag is set
+    pub const fn expired(self) -> bool { self.contains(Self::EXPIRED) }
+
+    /// Gets the raw underlying value of the state
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl BitOr for State {
+    type Output = State;
+
+    fn bitor(self, rhs: State) -> State {
+        State(self.0 | rhs.0)
+    }
+}
+
+fn u16_at(bytes: &[u8], start: usize) -> u16 {
+    u16::from_be_bytes([bytes[start], bytes[start + 1]])
+}
+
+fn mac_at(bytes: &[u8], start: usize) -> MacAddr {
+    MacAddr::new(<[u8; 6]>::try_from(&bytes[start..start + 6]).unwrap())
+}
+
+/// Checks the type and length of the TLV at the start of the data, returning the TLV without its header
+fn expect_tlv(bytes: &[u8], kind: u8, length: u8) -> Result<&[u8], ParseError> {
+    let header = bytes.get(..2).ok_or(ParseError::Truncated)?;
+    if header[0] != kind || header[1] != length {
+        return Err(ParseError::InvalidTlv { kind: header[0], length: header[1] });
+    }
+    bytes.get(2..length as usize).ok_or(ParseError::Truncated)
+}
+
+/// The information of the actor or partner of an LACPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Info {
+    /// The priority of the system, where lower values are preferred
+    pub system_priority: u16,
+    /// The MAC address identifying the system
+    pub system: MacAddr,
+    /// The operational key of the port, which is shared by the ports that can aggregate together
+    pub key: u16,
+    /// The priority of the port, where lower values are preferred
+    pub port_priority: u16,
+    /// The number of the port
+    pub port: u16,
+    /// The state of the port
+    pub state: State
+}
+
+impl Info {
+    fn parse(info: &[u8]) -> Self {
+        Info {
+            system_priority: u16_at(info, 0),
+            system: mac_at(info, 2),
+            key: u16_at(info, 8),
+            port_priority: u16_at(info, 10),
+            port: u16_at(info, 12),
+            state: State(info[14])
+        }
+    }
+
+    fn write_tlv(&self, cursor: &mut Cursor, kind: u8) -> Result<(), physical::Error> {
+        cursor.write(&kind)?;
+        cursor.write(&INFO_LEN)?;
+        cursor.write(&self.system_priority)?;
+        cursor.write(&self.system)?;
+        cursor.write(&self.key)?;
+        cursor.write(&self.port_priority)?;
+        cursor.write(&self.port)?;
+        cursor.write(&self.state.0)?;
+        cursor.write(&[0u8; 3][..])
+    }
+}
+
+/// A link aggregation control protocol data unit
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Lacpdu {
+    /// The version of LACP, which is 1 for LACPDUs created with `new`
+    pub version: u8,
+    /// The information of the sender
+    pub actor: Info,
+    /// The information the sender has about its partner
+    pub partner: Info,
+    /// The maximum delay in tens of microseconds between receiving and delivering a frame
+    pub collector_max_delay: u16
+}
+
+impl Lacpdu {
+    /// Creates a new version 1 LACPDU
+    pub const fn new(actor: Info, partner: Info, collector_max_delay: u16) -> Self {
+        Lacpdu { version: 1, actor, partner, collector_max_delay }
+    }
+
+    /// Parses an LACPDU after the subtype. TLVs after the collector information TLV are ignored.
+    fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let version = *bytes.first().ok_or(ParseError::Truncated)?;
+        let actor = expect_tlv(&bytes[1..], ACTOR_TYPE, INFO_LEN)?;
+        let partner = expect_tlv(&bytes[21..], PARTNER_TYPE, INFO_LEN)?;
+        let collector = expect_tlv(&bytes[41..], COLLECTOR_TYPE, COLLECTOR_LEN)?;
+        Ok(Lacpdu {
+            version,
+            actor: Info::parse(actor),
+            partner: Info::parse(partner),
+            collector_max_delay: u16_at(collector, 0)
+        })
+    }
+
+    /// Gets whether the actor and partner agree on their information, meaning the link is in sync and can be used
+    pub fn is_synchronized(&self) -> bool {
+        self.actor.state.synchronization() && self.partner.state.synchronization()
+    }
+}
+
+/// Whether a Marker PDU is a marker or a response to a marker
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MarkerKind {
+    /// A marker sent by the requester
+    Information = 0x01,
+    /// A response to a marker
+    Response = 0x02
+}
+
+/// A link aggregation marker protocol PDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Marker {
+    /// The version of the marker protocol, which is 1 for markers created with `new`
+    pub version: u8,
+    /// Whether the PDU is a marker or a response
+    pub kind: MarkerKind,
+    /// The port number of the requester
+    pub requester_port: u16,
+    /// The MAC address identifying the system of the requester
+    pub requester_system: MacAddr,
+    /// The transaction ID of the requester, which is copied into the response
+    pub requester_transaction_id: u32
+}
+
+impl Marker {
+    /// Creates a new version 1 marker
+    pub const fn new(requester_port: u16, requester_system: MacAddr, requester_transaction_id: u32) -> Self {
+        Marker { version: 1, kind: MarkerKind::Information, requester_port, requester_system, requester_transaction_id }
+    }
+
+    /// Creates a response to this marker
+    pub const fn response(&self) -> Self {
+        Marker { kind: MarkerKind::Response, ..*self }
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, ParseError> {
+        let version = *bytes.first().ok_or(ParseError::Truncated)?;
+        let kind = match bytes.get(1) {
+            Some(0x02) => MarkerKind::Response,
+            _ => MarkerKind::Information,
+        };
+        let info = expect_tlv(&bytes[1..], kind as u8, MARKER_LEN)?;
+        Ok(Marker {
+            version,
+            kind,
+            requester_port: u16_at(info, 0),
+            requester_system: mac_at(info, 2),
+            requester_transaction_id: u32::from_be_bytes(<[u8; 4]>::try_from(&info[8..12]).unwrap())
+        })
+    }
+}
+
+/// A Slow Protocols PDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Pdu<'a> {
+    /// A link aggregation control protocol data unit
+    Lacp(Lacpdu),
+    /// A marker protocol PDU
+    Marker(Marker),
+    /// A PDU of another slow protocol, with the data after the subtype
+    Unknown(Subtype, &'a [u8])
+}
+
+impl<'a> Pdu<'a> {
+    /// Parses a Slow Protocols PDU. Data after the fixed part of an LACPDU or Marker PDU is ignored.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into().0;
+        let (&subtype, rest) = bytes.split_first().ok_or(ParseError::Truncated)?;
+        match Subtype(subtype) {
+            Subtype::LACP => Lacpdu::parse(rest).map(Pdu::Lacp),
+            Subtype::MARKER => Marker::parse(rest).map(Pdu::Marker),
+            subtype => Ok(Pdu::Unknown(subtype, rest)),
+        }
+    }
+
+    /// Gets the subtype of the PDU
+    pub fn subtype(&self) -> Subtype {
+        match self {
+            Pdu::Lacp(_) => Subtype::LACP,
+            Pdu::Marker(_) => Subtype::MARKER,
+            Pdu::Unknown(subtype, _) => *subtype,
+        }
+    }
+}
+
+impl Sealed for Pdu<'_> { }
+impl Size for Pdu<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Pdu::Lacp(_) | Pdu::Marker(_) => PDU_LEN,
+            Pdu::Unknown(_, data) => 1 + data.len(),
+        }
+    }
+}
+/// Writes the PDU, including the zeroed reserved octets at the end of LACPDUs and Marker PDUs
+impl Write for Pdu<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let output = output.get_mut(..self.size()).ok_or(physical::Error)?;
+        output.iter_mut().for_each(|b| *b = 0);
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.subtype().0)?;
+        match self {
+            Pdu::Lacp(lacpdu) => {
+                cursor.write(&lacpdu.version)?;
+                lacpdu.actor.write_tlv(&mut cursor, ACTOR_TYPE)?;
+                lacpdu.partner.write_tlv(&mut cursor, PARTNER_TYPE)?;
+                cursor.write(&COLLECTOR_TYPE)?;
+                cursor.write(&COLLECTOR_LEN)?;
+                cursor.write(&lacpdu.collector_max_delay)?;
+            },
+            Pdu::Marker(marker) => {
+                cursor.write(&marker.version)?;
+                cursor.write(&(marker.kind as u8))?;
+                cursor.write(&MARKER_LEN)?;
+                cursor.write(&marker.requester_port)?;
+                cursor.write(&marker.requester_system)?;
+                cursor.write(&marker.requester_transaction_id)?;
+            },
+            Pdu::Unknown(_, data) => cursor.write(*data)?,
+        }
+        // the rest of the PDU, including the terminator TLV, is zeroed
+        Ok(self.size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{dispatch, vlan, EtherType, Ethernet2, EthernetBase};
+
+    const ACTOR: MacAddr = MacAddr::new([0x00, 0x1B, 0x21, 0x3C, 0x9D, 0xF8]);
+    const PARTNER: MacAddr = MacAddr::new([0x00, 0x13, 0xC4, 0x12, 0x0F, 0x00]);
+
+    fn lacpdu() -> [u8; PDU_LEN] {
+        let mut pdu = [0; PDU_LEN];
+        pdu[..58].copy_from_slice(&[
+            0x01, 0x01,
+            0x01, 0x14, 0xFF, 0xFF, 0x00, 0x1B, 0x21, 0x3C, 0x9D, 0xF8, 0x00, 0x0F, 0x00, 0xFF, 0x00, 0x01, 0x3D, 0x00, 0x00, 0x00,
+            0x02, 0x14, 0x80, 0x00, 0x00, 0x13, 0xC4, 0x12, 0x0F, 0x00, 0x00, 0x01, 0x80, 0x00, 0x01, 0x06, 0x47, 0x00, 0x00, 0x00,
+            0x03, 0x10, 0x00, 0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+        ]);
+        pdu
+    }
+
+    #[test]
+    fn parse_lacpdu() {
+        let bytes = lacpdu();
+        let lacpdu = match Pdu::parse(&bytes[..]).unwrap() {
+            Pdu::Lacp(lacpdu) => lacpdu,
+            other => panic!("expected an LACPDU, got {:?}", other),
+        };
+        assert_eq!(lacpdu.version, 1);
+        assert_eq!(lacpdu.actor, Info {
+            system_priority: 0xFFFF,
+            system: ACTOR,
+            key: 15,
+            port_priority: 255,
+            port: 1,
+            state: State::ACTIVITY | State::AGGREGATION | State::SYNCHRONIZATION | State::COLLECTING | State::DISTRIBUTING
+        });
+        assert_eq!(lacpdu.partner.system, PARTNER);
+        assert_eq!((lacpdu.partner.key, lacpdu.partner.port), (1, 0x106));
+        assert!(lacpdu.partner.state.activity() && lacpdu.partner.state.timeout() && lacpdu.partner.state.defaulted());
+        assert!(!lacpdu.partner.state.synchronization() && !lacpdu.partner.state.expired());
+        assert!(!lacpdu.is_synchronized());
+        assert_eq!(lacpdu.collector_max_delay, 5);
+
+        let mut buf = [0xFF; PDU_LEN];
+        assert_eq!(Pdu::Lacp(lacpdu).write(&mut buf), Ok(PDU_LEN));
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn marker_through_dispatch() {
+        let marker = Marker::new(7, ACTOR, 0x1234_5678);
+        let frame = Ethernet2::new(SLOW_PROTOCOLS_ADDRESS, ACTOR, vlan::Empty, EtherType::SLOW_PROTOCOLS, Pdu::Marker(marker.response()));
+        let mut buf = [0; 14 + PDU_LEN];
+        assert_eq!(frame.write(&mut buf), Ok(buf.len()));
+        assert_eq!(&buf[14..20], &[0x02, 0x01, 0x02, 0x10, 0x00, 0x07]);
+
+        let parsed = EthernetBase::parse(&buf[..]).unwrap().parse_vlan().unwrap_ethernet2().parse_payload().unwrap();
+        match parsed.payload() {
+            dispatch::Payload::Slow(Pdu::Marker(parsed)) => {
+                assert_eq!(parsed.kind, MarkerKind::Response);
+                assert_eq!(*parsed, marker.response());
+            },
+            other => panic!("expected a marker response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let mut bytes = lacpdu();
+        assert_eq!(Pdu::parse(&bytes[..0]), Err(ParseError::Truncated));
+        assert_eq!(Pdu::parse(&bytes[..50]), Err(ParseError::Truncated));
+        bytes[23] = 0x10;
+        assert_eq!(Pdu::parse(&bytes[..]), Err(ParseError::InvalidTlv { kind: 0x02, length: 0x10 }));
+        assert_eq!(Pdu::parse(&[0x02, 0x01, 0x03, 0x10][..]), Err(ParseError::InvalidTlv { kind: 0x03, length: 0x10 }));
+        assert_eq!(Pdu::parse(&[0x03, 0x01, 0x02][..]), Ok(Pdu::Unknown(Subtype::OAM, &[0x01, 0x02])));
+    }
+}
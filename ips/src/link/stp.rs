@@ -0,0 +1,697 @@
+//! Types for reading and writing spanning tree bridge protocol data units
+//!
+//! BPDUs are sent in LLC unnumbered information PDUs between [`SPANNING_TREE`](../llc/struct.Sap.html#associatedconstant.SPANNING_TREE)
+//! service access points, usually to the [`BRIDGE_GROUP_ADDRESS`](constant.BRIDGE_GROUP_ADDRESS.html).
+//! This module supports configuration and topology change notification BPDUs of STP,
+//! rapid spanning tree BPDUs of RSTP, and multiple spanning tree BPDUs of MSTP.
+
+use crate::internal::Sealed;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::BitOr;
+use macress::{Address, Eui48};
+
+type MacAddr = Address<Eui48>;
+
+/// The destination address of BPDUs
+pub const BRIDGE_GROUP_ADDRESS: MacAddr = MacAddr::new([0x01, 0x80, 0xC2, 0x00, 0x00, 0x00]);
+
+/// The length of a configuration BPDU
+pub const CONFIG_LEN: usize = 35;
+/// The length of a topology change notification BPDU
+pub const TCN_LEN: usize = 4;
+/// The length of a rapid spanning tree BPDU
+pub const RST_LEN: usize = 36;
+/// The length of a multiple spanning tree BPDU with no MSTI configuration messages
+pub const MST_LEN: usize = 102;
+/// The length of an MSTI configuration message
+pub const MSTI_LEN: usize = 16;
+
+const CONFIG_TYPE: u8 = 0x00;
+const TCN_TYPE: u8 = 0x80;
+const RST_TYPE: u8 = 0x02;
+/// The length of an MST BPDU counted by its version 3 length field, excluding MSTI configuration messages
+const VERSION_3_BASE_LEN: usize = MST_LEN - 38;
+
+/// Gets whether a length is a whole number of MSTI configuration messages, whose length is a power of two
+fn is_msti_multiple(len: usize) -> bool {
+    len & (MSTI_LEN - 1) == 0
+}
+
+/// An error that occurs while parsing a BPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of the BPDU
+    Truncated,
+    /// The protocol identifier wasn't zero
+    UnsupportedProtocol(u16),
+    /// The BPDU type wasn't a configuration, topology change notification, or rapid spanning tree BPDU
+    UnknownType(u8),
+    /// The LLC service access points weren't the spanning tree service access point
+    UnexpectedSap
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad BPDU; ran out of data"),
+            ParseError::UnsupportedProtocol(id) => write!(f, "bad BPDU; unsupported protocol identifier {:#06X}", id),
+            ParseError::UnknownType(kind) => write!(f, "bad BPDU; unknown BPDU type {:#04X}", kind),
+            ParseError::UnexpectedSap => f.write_str("bad BPDU; the LLC service access points aren't spanning tree"),
+        }
+    }
+}
+
+/// The role of the port a BPDU was sent from
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PortRole {
+    /// The role is unknown, or the port is the master port of an MSTI
+    Master = 0,
+    /// The port is an alternate or backup port
+    AlternateOrBackup = 1,
+    /// The port is the root port of the bridge
+    Root = 2,
+    /// The port is a designated port
+    Designated = 3
+}
+
+/// The flags of a BPDU or MSTI configuration message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Flags(u8);
+
+impl Flags {
+    /// No flags set
+    pub const NONE: Flags = Flags(0);
+    /// The topology has changed
+    pub const TOPOLOGY_CHANGE: Flags = Flags(0x01);
+    /// The port proposes to become designated
+    pub const PROPOSAL: Flags = Flags(0x02);
+    /// The port is learning addresses
+    pub const LEARNING: Flags = Flags(0x10);
+    /// The port is forwarding frames
+    pub const FORWARDING: Flags = Flags(0x20);
+    /// The port agrees to a proposal
+    pub const AGREEMENT: Flags = Flags(0x40);
+    /// A topology change was acknowledged
+    pub const TOPOLOGY_CHANGE_ACK: Flags = Flags(0x80);
+
+    const ROLE_SHIFT: u8 = 2;
+    const ROLE_MASK: u8 = 0b11 << Self::ROLE_SHIFT;
+
+    /// Returns a new flags value from the specified value
+    pub const fn raw(value: u8) -> Flags {
+        Flags(value)
+    }
+
+    /// Returns a new flags value with the port role set to the specified role
+    pub const fn with_role(self, role: PortRole) -> Flags {
+        Flags((self.0 & !Self::ROLE_MASK) | ((role as u8) << Self::ROLE_SHIFT))
+    }
+
+    /// Gets whether all of the specified flags are set
+    pub const fn contains(self, other: Flags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Gets whether the topology change flag is set
+    pub const fn topology_change(self) -> bool { self.contains(Self::TOPOLOGY_CHANGE) }
+    /// Gets whether the proposal flag is set
+    pub const fn proposal(self) -> bool { self.contains(Self::PROPOSAL) }
+    /// Gets whether the learning flag is set
+    pub const fn learning(self) -> bool { self.contains(Self::LEARNING) }
+    /// Gets whether the forwarding flag is set
+    pub const fn forwarding(self) -> bool { self.contains(Self::FORWARDING) }
+    /// Gets whether the agreement flag is set
+    pub const fn agreement(self) -> bool { self.contains(Self::AGREEMENT) }
+    /// Gets whether the topology change acknowledgment flag is set
+    pub const fn topology_change_ack(self) -> bool { self.contains(Self::TOPOLOGY_CHANGE_ACK) }
+
+    /// Gets the role of the port the BPDU was sent from
+    pub const fn role(self) -> PortRole {
+        match (self.0 & Self::ROLE_MASK) >> Self::ROLE_SHIFT {
+            0 => PortRole::Master,
+            1 => PortRole::AlternateOrBackup,
+            2 => PortRole::Root,
+            _ => PortRole::Designated,
+        }
+    }
+
+    /// Gets the raw value of the flags
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, rhs: Flags) -> Flags {
+        Flags(self.0 | rhs.0)
+    }
+}
+
+/// A bridge identifier, which orders bridges by priority and then by address
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct BridgeId {
+    value: u16,
+    address: MacAddr
+}
+
+impl BridgeId {
+    const PRIORITY_MASK: u16 = 0xF000;
+    const EXTENSION_MASK: u16 = 0x0FFF;
+
+    /// Creates a new bridge identifier. The priority is rounded down to a multiple of 4096 and the system ID extension is truncated to 12 bits.
+    pub const fn new(priority: u16, system_id_extension: u16, address: MacAddr) -> Self {
+        BridgeId { value: (priority & Self::PRIORITY_MASK) | (system_id_extension & Self::EXTENSION_MASK), address }
+    }
+
+    /// Gets the priority of the bridge, which is a multiple of 4096
+    pub const fn priority(&self) -> u16 {
+        self.value & Self::PRIORITY_MASK
+    }
+
+    /// Gets the system ID extension, which is the VLAN or MSTI the identifier is used for
+    pub const fn system_id_extension(&self) -> u16 {
+        self.value & Self::EXTENSION_MASK
+    }
+
+    /// Gets the address of the bridge
+    pub const fn address(&self) -> MacAddr {
+        self.address
+    }
+
+    fn parse(bytes: &[u8]) -> Self {
+        BridgeId {
+            value: u16::from_be_bytes([bytes[0], bytes[1]]),
+            address: MacAddr::new(<[u8; 6]>::try_from(&bytes[2..8]).unwrap())
+        }
+    }
+}
+
+impl Sealed for BridgeId { }
+impl Size for BridgeId {
+    fn size(&self) -> usize { 8 }
+}
+impl Write for BridgeId {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.value)?;
+        cursor.write(&self.address)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// A port identifier, made of a 4 bit priority and a 12 bit port number
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct PortId(pub u16);
+
+impl PortId {
+    /// Creates a new port identifier. The priority is rounded down to a multiple of 16 and the port number is truncated to 12 bits.
+    pub const fn new(priority: u8, number: u16) -> Self {
+        PortId((((priority & 0xF0) as u16) << 8) | (number & 0x0FFF))
+    }
+
+    /// Gets the priority of the port, which is a multiple of 16
+    pub const fn priority(self) -> u8 {
+        ((self.0 >> 8) & 0xF0) as u8
+    }
+
+    /// Gets the number of the port
+    pub const fn number(self) -> u16 {
+        self.0 & 0x0FFF
+    }
+}
+
+/// The spanning tree priority vector and timers of a configuration or rapid spanning tree BPDU.
+/// Times are in units of 1/256 seconds.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Config {
+    /// The flags of the BPDU
+    pub flags: Flags,
+    /// The identifier of the root bridge, or the CIST root of a multiple spanning tree BPDU
+    pub root: BridgeId,
+    /// The cost of the path to the root bridge, or the external root path cost of a multiple spanning tree BPDU
+    pub root_path_cost: u32,
+    /// The identifier of the sending bridge, or the CIST regional root of a multiple spanning tree BPDU
+    pub bridge: BridgeId,
+    /// The identifier of the sending port
+    pub port: PortId,
+    /// The time since the root bridge sent the information in the BPDU, in units of 1/256 seconds
+    pub message_age: u16,
+    /// The age in units of 1/256 seconds at which the information in the BPDU is discarded
+    pub max_age: u16,
+    /// The time between configuration BPDUs sent by the root bridge, in units of 1/256 seconds
+    pub hello_time: u16,
+    /// The time a port spends in the listening and learning states, in units of 1/256 seconds
+    pub forward_delay: u16
+}
+
+impl Config {
+    /// Creates a new configuration with the default timers of 20 seconds max age, 2 seconds hello time, and 15 seconds forward delay
+    pub const fn new(root: BridgeId, root_path_cost: u32, bridge: BridgeId, port: PortId) -> Self {
+        Config {
+            flags: Flags::NONE,
+            root,
+            root_path_cost,
+            bridge,
+            port,
+            message_age: 0,
+            max_age: 20 * 256,
+            hello_time: 2 * 256,
+            forward_delay: 15 * 256
+        }
+    }
+
+    fn parse(bytes: &[u8]) -> Self {
+        let u16_at = |start: usize| u16::from_be_bytes([bytes[start], bytes[start + 1]]);
+        Config {
+            flags: Flags(bytes[4]),
+            root: BridgeId::parse(&bytes[5..13]),
+            root_path_cost: u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[13..17]).unwrap()),
+            bridge: BridgeId::parse(&bytes[17..25]),
+            port: PortId(u16_at(25)),
+            message_age: u16_at(27),
+            max_age: u16_at(29),
+            hello_time: u16_at(31),
+            forward_delay: u16_at(33)
+        }
+    }
+
+    fn write_to(&self, cursor: &mut Cursor) -> Result<(), physical::Error> {
+        cursor.write(&self.flags.0)?;
+        cursor.write(&self.root)?;
+        cursor.write(&self.root_path_cost)?;
+        cursor.write(&self.bridge)?;
+        cursor.write(&self.port.0)?;
+        cursor.write(&self.message_age)?;
+        cursor.write(&self.max_age)?;
+        cursor.write(&self.hello_time)?;
+        cursor.write(&self.forward_delay)
+    }
+}
+
+/// The MST configuration identifier of the region of the sending bridge
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ConfigurationId {
+    /// The format of the identifier, which is zero for MSTP
+    pub format_selector: u8,
+    /// The name of the region, padded with zeroes
+    pub name: [u8; 32],
+    /// The revision level of the configuration of the region
+    pub revision: u16,
+    /// The HMAC-MD5 digest of the VLAN to MSTI mapping of the region
+    pub digest: [u8; 16]
+}
+
+impl ConfigurationId {
+    /// Gets the name of the region without trailing zeroes
+    pub fn name(&self) -> &[u8] {
+        let len = self.name.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        &self.name[..len]
+    }
+}
+
+/// An MSTI configuration message, describing the spanning tree of a single MSTI
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MstiRecord {
+    /// The flags of the message
+    pub flags: Flags,
+    /// The identifier of the regional root of the MSTI, whose system ID extension is the MSTI ID
+    pub regional_root: BridgeId,
+    /// The cost of the path to the regional root of the MSTI
+    pub internal_root_path_cost: u32,
+    /// The priority of the sending bridge for the MSTI in the high 4 bits
+    pub bridge_priority: u8,
+    /// The priority of the sending port for the MSTI in the high 4 bits
+    pub port_priority: u8,
+    /// The number of hops left before the information in the message is discarded
+    pub remaining_hops: u8
+}
+
+impl MstiRecord {
+    /// Gets the ID of the MSTI
+    pub const fn msti(&self) -> u16 {
+        self.regional_root.system_id_extension()
+    }
+
+    fn parse(bytes: &[u8]) -> Self {
+        MstiRecord {
+            flags: Flags(bytes[0]),
+            regional_root: BridgeId::parse(&bytes[1..9]),
+            internal_root_path_cost: u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[9..13]).unwrap()),
+            bridge_priority: bytes[13],
+            port_priority: bytes[14],
+            remaining_hops: bytes[15]
+        }
+    }
+}
+
+impl Sealed for MstiRecord { }
+impl Size for MstiRecord {
+    fn size(&self) -> usize { MSTI_LEN }
+}
+impl Write for MstiRecord {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.flags.0)?;
+        cursor.write(&self.regional_root)?;
+        cursor.write(&self.internal_root_path_cost)?;
+        cursor.write(&self.bridge_priority)?;
+        cursor.write(&self.port_priority)?;
+        cursor.write(&self.remaining_hops)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// The MSTI configuration messages of a multiple spanning tree BPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Mstis<'a>(pub &'a [u8]);
+
+impl<'a> Mstis<'a> {
+    /// No MSTI configuration messages
+    pub const EMPTY: Mstis<'static> = Mstis(&[]);
+
+    /// Returns an iterator over the MSTI configuration messages, ignoring any incomplete message at the end
+    pub fn iter(&self) -> MstisIter<'a> {
+        MstisIter(self.0.chunks_exact(MSTI_LEN))
+    }
+}
+
+/// An iterator over MSTI configuration messages
+#[derive(Clone, Debug)]
+pub struct MstisIter<'a>(core::slice::ChunksExact<'a, u8>);
+
+impl Iterator for MstisIter<'_> {
+    type Item = MstiRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(MstiRecord::parse)
+    }
+}
+
+/// A multiple spanning tree BPDU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Mst<'a> {
+    /// The CIST priority vector and timers
+    pub cist: Config,
+    /// The MST configuration identifier of the region of the sending bridge
+    pub configuration: ConfigurationId,
+    /// The cost of the path to the CIST regional root
+    pub cist_internal_root_path_cost: u32,
+    /// The identifier of the sending bridge
+    pub cist_bridge: BridgeId,
+    /// The number of hops left before the CIST information in the BPDU is discarded
+    pub cist_remaining_hops: u8,
+    /// The MSTI configuration messages. Writing the BPDU fails if their length isn't a multiple of [`MSTI_LEN`](constant.MSTI_LEN.html).
+    pub mstis: Mstis<'a>
+}
+
+/// A bridge protocol data unit
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Bpdu<'a> {
+    /// An STP configuration BPDU
+    Config(Config),
+    /// An STP topology change notification BPDU
+    TopologyChangeNotification,
+    /// An RSTP rapid spanning tree BPDU
+    Rst {
+        /// The protocol version, which is 2 unless the BPDU was sent by a newer bridge
+        version: u8,
+        /// The priority vector and timers
+        config: Config
+    },
+    /// An MSTP multiple spanning tree BPDU
+    Mst(Mst<'a>)
+}
+
+impl<'a> Bpdu<'a> {
+    /// Parses a BPDU from the payload of an LLC PDU. Data after the end of the BPDU is ignored.
+    ///
+    /// Rapid spanning tree BPDUs with a version of 3 or more are parsed as multiple spanning tree BPDUs
+    /// if their version 3 length is valid, and as rapid spanning tree BPDUs that keep the received version otherwise.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into().0;
+        if bytes.len() < TCN_LEN {
+            return Err(ParseError::Truncated);
+        }
+        let protocol = u16::from_be_bytes([bytes[0], bytes[1]]);
+        if protocol != 0 {
+            return Err(ParseError::UnsupportedProtocol(protocol));
+        }
+
+        let (version, kind) = (bytes[2], bytes[3]);
+        match kind {
+            TCN_TYPE => Ok(Bpdu::TopologyChangeNotification),
+            CONFIG_TYPE if bytes.len() >= CONFIG_LEN => Ok(Bpdu::Config(Config::parse(bytes))),
+            RST_TYPE if bytes.len() >= RST_LEN => {
+                let cist = Config::parse(bytes);
+                let mstis_len = bytes.get(36..38)
+                    .and_then(|len| (u16::from_be_bytes([len[0], len[1]]) as usize).checked_sub(VERSION_3_BASE_LEN))
+                    .filter(|&len| is_msti_multiple(len) && bytes.len() >= MST_LEN + len);
+                match mstis_len {
+                    Some(len) if version >= 3 => {
+                        let mut name = [0; 32];
+                        name.copy_from_slice(&bytes[39..71]);
+                        let mut digest = [0; 16];
+                        digest.copy_from_slice(&bytes[73..89]);
+                        Ok(Bpdu::Mst(Mst {
+                            cist,
+                            configuration: ConfigurationId {
+                                format_selector: bytes[38],
+                                name,
+                                revision: u16::from_be_bytes([bytes[71], bytes[72]]),
+                                digest
+                            },
+                            cist_internal_root_path_cost: u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[89..93]).unwrap()),
+                            cist_bridge: BridgeId::parse(&bytes[93..101]),
+                            cist_remaining_hops: bytes[101],
+                            mstis: Mstis(&bytes[MST_LEN..MST_LEN + len])
+                        }))
+                    },
+                    _ => Ok(Bpdu::Rst { version, config: cist }),
+                }
+            },
+            CONFIG_TYPE | RST_TYPE => Err(ParseError::Truncated),
+            kind => Err(ParseError::UnknownType(kind)),
+        }
+    }
+
+    /// Gets the identifier of the root bridge advertised by the BPDU, or `None` for topology change notifications
+    pub fn root(&self) -> Option<BridgeId> {
+        self.config().map(|config| config.root)
+    }
+
+    /// Gets the configuration of the BPDU, or the CIST configuration of multiple spanning tree BPDUs
+    pub fn config(&self) -> Option<&Config> {
+        match self {
+            Bpdu::Config(config) | Bpdu::Rst { config, .. } => Some(config),
+            Bpdu::Mst(mst) => Some(&mst.cist),
+            Bpdu::TopologyChangeNotification => None,
+        }
+    }
+
+    fn version(&self) -> u8 {
+        match self {
+            Bpdu::Config(_) | Bpdu::TopologyChangeNotification => 0,
+            Bpdu::Rst { version, .. } => *version,
+            Bpdu::Mst(_) => 3,
+        }
+    }
+}
+
+impl Sealed for Bpdu<'_> { }
+impl Size for Bpdu<'_> {
+    fn size(&self) -> usize {
+        match self {
+            Bpdu::Config(_) => CONFIG_LEN,
+            Bpdu::TopologyChangeNotification => TCN_LEN,
+            Bpdu::Rst { .. } => RST_LEN,
+            Bpdu::Mst(mst) => MST_LEN + mst.mstis.0.len(),
+        }
+    }
+}
+impl Write for Bpdu<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&0u16)?;
+        cursor.write(&self.version())?;
+        match self {
+            Bpdu::Config(config) => {
+                cursor.write(&CONFIG_TYPE)?;
+                config.write_to(&mut cursor)?;
+            },
+            Bpdu::TopologyChangeNotification => cursor.write(&TCN_TYPE)?,
+            Bpdu::Rst { config, .. } => {
+                cursor.write(&RST_TYPE)?;
+                config.write_to(&mut cursor)?;
+                cursor.write(&0u8)?;
+            },
+            Bpdu::Mst(mst) => {
+                if !is_msti_multiple(mst.mstis.0.len()) {
+                    return Err(physical::Error);
+                }
+                let version_3_len = u16::try_from(VERSION_3_BASE_LEN + mst.mstis.0.len()).map_err(|_| physical::Error)?;
+                cursor.write(&RST_TYPE)?;
+                mst.cist.write_to(&mut cursor)?;
+                cursor.write(&0u8)?;
+                cursor.write(&version_3_len)?;
+                cursor.write(&mst.configuration.format_selector)?;
+                cursor.write(&mst.configuration.name[..])?;
+                cursor.write(&mst.configuration.revision)?;
+                cursor.write(&mst.configuration.digest[..])?;
+                cursor.write(&mst.cist_internal_root_path_cost)?;
+                cursor.write(&mst.cist_bridge)?;
+                cursor.write(&mst.cist_remaining_hops)?;
+                cursor.write(mst.mstis.0)?;
+            },
+        }
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{vlan, Ethernet, EthernetBase, Frame};
+    use crate::link::llc::{self, Llc, Sap};
+    use crate::payload;
+
+    const ROOT: MacAddr = MacAddr::new([0x00, 0x1C, 0x0E, 0x87, 0x78, 0x00]);
+    const BRIDGE: MacAddr = MacAddr::new([0x00, 0x1C, 0x0E, 0x87, 0x85, 0x00]);
+
+    // an 802.3 frame containing a configuration BPDU from a bridge with priority 32768 in VLAN 1
+    const CONFIG_FRAME: [u8; 60] = [
+        0x01, 0x80, 0xC2, 0x00, 0x00, 0x00, 0x00, 0x1C, 0x0E, 0x87, 0x85, 0x04, 0x00, 0x26,
+        0x42, 0x42, 0x03,
+        0x00, 0x00, 0x00, 0x00, 0x01,
+        0x60, 0x01, 0x00, 0x1C, 0x0E, 0x87, 0x78, 0x00, 0x00, 0x00, 0x00, 0x04,
+        0x80, 0x01, 0x00, 0x1C, 0x0E, 0x87, 0x85, 0x00, 0x80, 0x04,
+        0x01, 0x00, 0x14, 0x00, 0x02, 0x00, 0x0F, 0x00,
+        0, 0, 0, 0, 0, 0, 0, 0
+    ];
+
+    #[test]
+    fn parse_config() {
+        let frame = match EthernetBase::parse(&CONFIG_FRAME[..]).unwrap().classify().unwrap() {
+            Frame::Ethernet(frame) => frame.try_parse_llc().unwrap(),
+            other => panic!("expected an 802.3 frame, got {:?}", other),
+        };
+        assert_eq!(frame.destination, BRIDGE_GROUP_ADDRESS);
+        let bpdu = frame.payload().payload.try_parse_bpdu().unwrap();
+        let config = match bpdu.payload() {
+            Bpdu::Config(config) => *config,
+            other => panic!("expected a configuration BPDU, got {:?}", other),
+        };
+
+        assert!(config.flags.topology_change());
+        assert_eq!(config.root.priority(), 24576);
+        assert_eq!(config.root.system_id_extension(), 1);
+        assert_eq!(config.root.address(), ROOT);
+        assert_eq!(config.root_path_cost, 4);
+        assert_eq!((config.bridge.priority(), config.bridge.address()), (32768, BRIDGE));
+        assert_eq!((config.port.priority(), config.port.number()), (0x80, 4));
+        assert_eq!((config.message_age, config.max_age, config.hello_time, config.forward_delay), (256, 20 * 256, 2 * 256, 15 * 256));
+        assert!(config.root < config.bridge);
+        assert_eq!(bpdu.payload().root(), Some(config.root));
+
+        let mut buf = [0; CONFIG_LEN];
+        assert_eq!(bpdu.payload().write(&mut buf), Ok(CONFIG_LEN));
+        assert_eq!(&buf[..], &CONFIG_FRAME[17..52]);
+    }
+
+    #[test]
+    fn write_and_parse_mst() {
+        let root = BridgeId::new(4096, 0, ROOT);
+        let mut cist = Config::new(root, 20000, BridgeId::new(32768, 0, BRIDGE), PortId::new(0x80, 2));
+        cist.flags = Flags::LEARNING | Flags::FORWARDING | Flags::AGREEMENT | Flags::NONE.with_role(PortRole::Root);
+
+        let records = [
+            MstiRecord {
+                flags: Flags::FORWARDING.with_role(PortRole::Designated),
+                regional_root: BridgeId::new(8192, 10, BRIDGE),
+                internal_root_path_cost: 0,
+                bridge_priority: 0x20,
+                port_priority: 0x80,
+                remaining_hops: 20
+            },
+            MstiRecord {
+                flags: Flags::NONE.with_role(PortRole::AlternateOrBackup),
+                regional_root: BridgeId::new(4096, 20, ROOT),
+                internal_root_path_cost: 2000,
+                bridge_priority: 0x80,
+                port_priority: 0x80,
+                remaining_hops: 19
+            },
+        ];
+        let mut mstis = [0; 2 * MSTI_LEN];
+        records[0].write(&mut mstis).unwrap();
+        records[1].write(&mut mstis[MSTI_LEN..]).unwrap();
+
+        let mut name = [0; 32];
+        name[..3].copy_from_slice(b"lab");
+        let sent = Mst {
+            cist,
+            configuration: ConfigurationId { format_selector: 0, name, revision: 1, digest: [0xAC; 16] },
+            cist_internal_root_path_cost: 0,
+            cist_bridge: BridgeId::new(32768, 0, BRIDGE),
+            cist_remaining_hops: 20,
+            mstis: Mstis(&mstis)
+        };
+        let bpdu = Bpdu::Mst(sent);
+        let pdu = Llc::ui(Sap::SPANNING_TREE, Sap::SPANNING_TREE, bpdu);
+        assert_eq!(pdu.size(), 3 + MST_LEN + 32);
+
+        let mut buf = [0; 3 + MST_LEN + 32];
+        assert_eq!(pdu.write(&mut buf), Ok(buf.len()));
+        assert_eq!(&buf[3..7], &[0x00, 0x00, 0x03, 0x02]);
+        assert_eq!(&buf[39..41], &[0x00, 96]);
+
+        let parsed = llc::Parsed::parse(&buf[..]).unwrap().try_parse_bpdu().unwrap();
+        assert_eq!(parsed.payload(), &bpdu);
+        let mst = match parsed.payload() {
+            Bpdu::Mst(mst) => *mst,
+            other => panic!("expected an MST BPDU, got {:?}", other),
+        };
+        assert_eq!(mst.cist.flags.role(), PortRole::Root);
+        assert_eq!(mst.configuration.name(), b"lab");
+        assert_eq!(mst.mstis.iter().map(|record| record.msti()).collect::<alloc::vec::Vec<_>>(), [10, 20]);
+        assert_eq!(mst.mstis.iter().nth(1), Some(records[1]));
+
+        // an MST BPDU with an invalid version 3 length is an RST BPDU that keeps its version
+        buf[40] = 95;
+        let rst = Bpdu::Rst { version: 3, config: cist };
+        assert_eq!(llc::Parsed::parse(&buf[..]).unwrap().try_parse_bpdu().unwrap().payload(), &rst);
+
+        let mut rst_buf = [0; RST_LEN];
+        assert_eq!(rst.write(&mut rst_buf), Ok(RST_LEN));
+        assert_eq!(&rst_buf[..], &buf[3..3 + RST_LEN]);
+
+        let partial = Bpdu::Mst(Mst { mstis: Mstis(&mstis[..MSTI_LEN + 1]), ..sent });
+        assert_eq!(partial.write(&mut [0; MST_LEN + 32]), Err(physical::Error));
+    }
+
+    #[test]
+    fn tcn_and_errors() {
+        let frame = Ethernet::new(BRIDGE_GROUP_ADDRESS, BRIDGE, vlan::Empty, payload::Padded {
+            payload: Llc::ui(Sap::SPANNING_TREE, Sap::SPANNING_TREE, Bpdu::TopologyChangeNotification),
+            padding: payload::ValuePadding::zero(39)
+        });
+        let mut buf = [0; 60];
+        assert_eq!(frame.write(&mut buf), Ok(60));
+        assert_eq!(&buf[12..21], &[0x00, 0x07, 0x42, 0x42, 0x03, 0x00, 0x00, 0x00, 0x80]);
+        assert_eq!(Bpdu::parse(&buf[17..21]), Ok(Bpdu::TopologyChangeNotification));
+        assert_eq!(Bpdu::TopologyChangeNotification.root(), None);
+
+        assert_eq!(Bpdu::parse(&CONFIG_FRAME[17..20]), Err(ParseError::Truncated));
+        assert_eq!(Bpdu::parse(&CONFIG_FRAME[17..51]), Err(ParseError::Truncated));
+        assert_eq!(Bpdu::parse(&[0x00, 0x01, 0x00, 0x00][..]), Err(ParseError::UnsupportedProtocol(1)));
+        assert_eq!(Bpdu::parse(&[0x00, 0x00, 0x00, 0x01][..]), Err(ParseError::UnknownType(1)));
+
+        let snap = llc::Parsed::parse(&[0xAA, 0xAA, 0x03, 0, 0, 0, 0][..]).unwrap();
+        assert_eq!(snap.try_parse_bpdu().unwrap_err(), ParseError::UnexpectedSap);
+    }
+}
@@ -0,0 +1,609 @@
+//! Types for reading and writing ICMP messages for IPv4
+
+use crate::checksum::Checksum;
+use crate::internal::Sealed;
+use crate::network::ipv4;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::Ipv4Addr;
+
+/// The length of an ICMP header, including the type-specific rest of the header
+pub const HEADER_LEN: usize = 8;
+/// The length of the data of a timestamp message
+pub const TIMESTAMP_DATA_LEN: usize = 12;
+
+/// An error that occurs while parsing an ICMP message
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the ICMP header or the end of a fixed length message
+    Truncated
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad ICMP message; ran out of data"),
+        }
+    }
+}
+
+/// The code of a destination unreachable message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct UnreachableCode(pub u8);
+
+impl UnreachableCode {
+    /// The destination network is unreachable
+    pub const NETWORK: UnreachableCode = UnreachableCode(0);
+    /// The destination host is unreachable
+    pub const HOST: UnreachableCode = UnreachableCode(1);
+    /// The protocol isn't supported by the destination
+    pub const PROTOCOL: UnreachableCode = UnreachableCode(2);
+    /// No process is listening on the destination port
+    pub const PORT: UnreachableCode = UnreachableCode(3);
+    /// The packet needed to be fragmented, but the don't fragment flag was set
+    pub const FRAGMENTATION_NEEDED: UnreachableCode = UnreachableCode(4);
+    /// The source route of the packet failed
+    pub const SOURCE_ROUTE_FAILED: UnreachableCode = UnreachableCode(5);
+    /// The destination network is unknown
+    pub const NETWORK_UNKNOWN: UnreachableCode = UnreachableCode(6);
+    /// The destination host is unknown
+    pub const HOST_UNKNOWN: UnreachableCode = UnreachableCode(7);
+    /// The source host is isolated
+    pub const SOURCE_HOST_ISOLATED: UnreachableCode = UnreachableCode(8);
+    /// Communication with the destination network is administratively prohibited
+    pub const NETWORK_PROHIBITED: UnreachableCode = UnreachableCode(9);
+    /// Communication with the destination host is administratively prohibited
+    pub const HOST_PROHIBITED: UnreachableCode = UnreachableCode(10);
+    /// The destination network is unreachable for the type of service
+    pub const NETWORK_UNREACHABLE_FOR_TOS: UnreachableCode = UnreachableCode(11);
+    /// The destination host is unreachable for the type of service
+    pub const HOST_UNREACHABLE_FOR_TOS: UnreachableCode = UnreachableCode(12);
+    /// Communication is administratively prohibited by filtering
+    pub const COMMUNICATION_PROHIBITED: UnreachableCode = UnreachableCode(13);
+    /// The precedence of the packet isn't permitted for the source, destination, and port
+    pub const HOST_PRECEDENCE_VIOLATION: UnreachableCode = UnreachableCode(14);
+    /// The precedence of the packet was below the minimum level in effect
+    pub const PRECEDENCE_CUTOFF: UnreachableCode = UnreachableCode(15);
+}
+
+/// The code of a redirect message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct RedirectCode(pub u8);
+
+impl RedirectCode {
+    /// Redirect packets for the destination network
+    pub const NETWORK: RedirectCode = RedirectCode(0);
+    /// Redirect packets for the destination host
+    pub const HOST: RedirectCode = RedirectCode(1);
+    /// Redirect packets for the type of service and destination network
+    pub const TOS_NETWORK: RedirectCode = RedirectCode(2);
+    /// Redirect packets for the type of service and destination host
+    pub const TOS_HOST: RedirectCode = RedirectCode(3);
+}
+
+/// The code of a time exceeded message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct TimeExceededCode(pub u8);
+
+impl TimeExceededCode {
+    /// The time to live of the packet reached zero in transit
+    pub const TTL_EXCEEDED: TimeExceededCode = TimeExceededCode(0);
+    /// The fragments of the packet weren't reassembled in time
+    pub const REASSEMBLY_EXCEEDED: TimeExceededCode = TimeExceededCode(1);
+}
+
+/// The code of a parameter problem message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ParameterProblemCode(pub u8);
+
+impl ParameterProblemCode {
+    /// The pointer indicates the octet of the original packet with the error
+    pub const POINTER: ParameterProblemCode = ParameterProblemCode(0);
+    /// A required option is missing
+    pub const MISSING_OPTION: ParameterProblemCode = ParameterProblemCode(1);
+    /// The length of the original packet is bad
+    pub const BAD_LENGTH: ParameterProblemCode = ParameterProblemCode(2);
+}
+
+/// The data of an echo request or reply
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Echo<'a> {
+    /// An identifier used to match replies with requests
+    pub identifier: u16,
+    /// A sequence number used to match replies with requests
+    pub sequence: u16,
+    /// The data of the message, which is echoed back in replies
+    pub data: &'a [u8]
+}
+
+/// The data of a timestamp request or reply
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Timestamp<'a> {
+    /// An identifier used to match replies with requests
+    pub identifier: u16,
+    /// A sequence number used to match replies with requests
+    pub sequence: u16,
+    /// The time the sender last touched the request, in milliseconds since midnight UT
+    pub originate: u32,
+    /// The time the echoer first touched the request, in milliseconds since midnight UT
+    pub receive: u32,
+    /// The time the echoer last touched the reply, in milliseconds since midnight UT
+    pub transmit: u32,
+    /// Any data after the timestamps, which is usually empty but is still covered by the checksum
+    pub data: &'a [u8]
+}
+
+/// The original packet quoted in an ICMP error message, containing the IP header and at least the first 8 octets of its payload
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Original<'a>(pub &'a [u8]);
+
+impl<'a> Original<'a> {
+    /// Quotes the IP header and first 8 octets of the payload of the specified packet, or the entire packet if it's shorter
+    pub fn quote(packet: &'a [u8]) -> Self {
+        let header_len = packet.first().map_or(0, |b| (b & 0x0F) as usize * 4);
+        Original(&packet[..packet.len().min(header_len + 8)])
+    }
+
+    /// Parses the original IPv4 header. The payload of the parsed packet is the quoted part of the original payload.
+    pub fn parse(&self) -> Result<ipv4::Partial<'a>, ipv4::ParseError> {
+        ipv4::Partial::parse(self.0)
+    }
+}
+
+/// A typed ICMP message
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Message<'a> {
+    /// A reply to an echo request
+    EchoReply(Echo<'a>),
+    /// The original packet couldn't be delivered
+    DestinationUnreachable {
+        /// Why the packet couldn't be delivered
+        code: UnreachableCode,
+        /// The raw rest of the header, containing the original length and the next hop MTU
+        rest: [u8; 4],
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// Packets to the original destination should be sent to a different gateway
+    Redirect {
+        /// Which packets should be redirected
+        code: RedirectCode,
+        /// The address of the gateway packets should be sent to
+        gateway: Ipv4Addr,
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// A request to echo the data back to the sender
+    EchoRequest(Echo<'a>),
+    /// The original packet was discarded because its time to live or reassembly time was exceeded
+    TimeExceeded {
+        /// Which time was exceeded
+        code: TimeExceededCode,
+        /// The raw rest of the header, containing the original length
+        rest: [u8; 4],
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// The original packet was discarded because of a problem with its header
+    ParameterProblem {
+        /// The kind of problem
+        code: ParameterProblemCode,
+        /// The raw rest of the header, containing the pointer and the original length
+        rest: [u8; 4],
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// A request for the timestamps of the receiver
+    TimestampRequest(Timestamp<'a>),
+    /// A reply to a timestamp request
+    TimestampReply(Timestamp<'a>),
+    /// A message of an unknown type, or a known type with an unknown code
+    Unknown {
+        /// The type of the message
+        kind: u8,
+        /// The code of the message
+        code: u8,
+        /// The type-specific rest of the header
+        rest: [u8; 4],
+        /// The data after the header
+        data: &'a [u8]
+    }
+}
+
+impl<'a> Message<'a> {
+    /// The echo reply message type
+    pub const ECHO_REPLY: u8 = 0;
+    /// The destination unreachable message type
+    pub const DESTINATION_UNREACHABLE: u8 = 3;
+    /// The redirect message type
+    pub const REDIRECT: u8 = 5;
+    /// The echo request message type
+    pub const ECHO_REQUEST: u8 = 8;
+    /// The time exceeded message type
+    pub const TIME_EXCEEDED: u8 = 11;
+    /// The parameter problem message type
+    pub const PARAMETER_PROBLEM: u8 = 12;
+    /// The timestamp request message type
+    pub const TIMESTAMP_REQUEST: u8 = 13;
+    /// The timestamp reply message type
+    pub const TIMESTAMP_REPLY: u8 = 14;
+
+    /// Gets the type of the message
+    pub fn kind(&self) -> u8 {
+        match self {
+            Message::EchoReply(_) => Self::ECHO_REPLY,
+            Message::DestinationUnreachable { .. } => Self::DESTINATION_UNREACHABLE,
+            Message::Redirect { .. } => Self::REDIRECT,
+            Message::EchoRequest(_) => Self::ECHO_REQUEST,
+            Message::TimeExceeded { .. } => Self::TIME_EXCEEDED,
+            Message::ParameterProblem { .. } => Self::PARAMETER_PROBLEM,
+            Message::TimestampRequest(_) => Self::TIMESTAMP_REQUEST,
+            Message::TimestampReply(_) => Self::TIMESTAMP_REPLY,
+            Message::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    /// Gets the code of the message
+    pub fn code(&self) -> u8 {
+        match self {
+            Message::DestinationUnreachable { code, .. } => code.0,
+            Message::Redirect { code, .. } => code.0,
+            Message::TimeExceeded { code, .. } => code.0,
+            Message::ParameterProblem { code, .. } => code.0,
+            Message::Unknown { code, .. } => *code,
+            _ => 0,
+        }
+    }
+
+    /// Returns whether this message is an error message, which quotes the original packet that caused the error
+    pub fn is_error(&self) -> bool {
+        self.original().is_some()
+    }
+
+    /// Gets the original packet quoted by this message, if this is an error message
+    pub fn original(&self) -> Option<Original<'a>> {
+        match *self {
+            Message::DestinationUnreachable { original, .. } |
+            Message::Redirect { original, .. } |
+            Message::TimeExceeded { original, .. } |
+            Message::ParameterProblem { original, .. } => Some(original),
+            _ => None,
+        }
+    }
+
+    /// Gets the length of the original datagram in 32-bit words, as defined by RFC 4884.
+    ///
+    /// Returns `None` if this message can't carry the length, and `Some(0)` if the sender didn't include extensions.
+    pub fn original_length(&self) -> Option<u8> {
+        match self {
+            Message::DestinationUnreachable { rest, .. } |
+            Message::TimeExceeded { rest, .. } |
+            Message::ParameterProblem { rest, .. } => Some(rest[1]),
+            _ => None,
+        }
+    }
+
+    /// Gets the MTU of the next hop from a destination unreachable message, which is only meaningful if the code is
+    /// [`FRAGMENTATION_NEEDED`](struct.UnreachableCode.html#associatedconstant.FRAGMENTATION_NEEDED).
+    ///
+    /// Returns `None` if this isn't a destination unreachable message.
+    pub fn next_hop_mtu(&self) -> Option<u16> {
+        match self {
+            Message::DestinationUnreachable { rest, .. } => Some(u16::from_be_bytes([rest[2], rest[3]])),
+            _ => None,
+        }
+    }
+
+    /// Gets the octet of the original packet where the error was found from a parameter problem message.
+    ///
+    /// Returns `None` if this isn't a parameter problem message.
+    pub fn pointer(&self) -> Option<u8> {
+        match self {
+            Message::ParameterProblem { rest, .. } => Some(rest[0]),
+            _ => None,
+        }
+    }
+
+    /// Creates an echo reply to this message, returning `None` if this message isn't an echo request
+    pub fn echo_reply(&self) -> Option<Self> {
+        match *self {
+            Message::EchoRequest(echo) => Some(Message::EchoReply(echo)),
+            _ => None,
+        }
+    }
+
+    fn rest(&self) -> [u8; 4] {
+        match self {
+            Message::EchoReply(echo) | Message::EchoRequest(echo) => pair(echo.identifier, echo.sequence),
+            Message::TimestampRequest(ts) | Message::TimestampReply(ts) => pair(ts.identifier, ts.sequence),
+            Message::Redirect { gateway, .. } => gateway.octets(),
+            Message::DestinationUnreachable { rest, .. } |
+            Message::TimeExceeded { rest, .. } |
+            Message::ParameterProblem { rest, .. } |
+            Message::Unknown { rest, .. } => *rest,
+        }
+    }
+
+    fn data(&self) -> &'a [u8] {
+        match *self {
+            Message::EchoReply(echo) | Message::EchoRequest(echo) => echo.data,
+            Message::TimestampRequest(ts) | Message::TimestampReply(ts) => ts.data,
+            Message::Unknown { data, .. } => data,
+            _ => self.original().map_or(&[], |original| original.0),
+        }
+    }
+
+    fn timestamps(&self) -> Option<[u8; TIMESTAMP_DATA_LEN]> {
+        match self {
+            Message::TimestampRequest(ts) | Message::TimestampReply(ts) => {
+                let mut data = [0; TIMESTAMP_DATA_LEN];
+                data[..4].copy_from_slice(&ts.originate.to_be_bytes());
+                data[4..8].copy_from_slice(&ts.receive.to_be_bytes());
+                data[8..].copy_from_slice(&ts.transmit.to_be_bytes());
+                Some(data)
+            },
+            _ => None,
+        }
+    }
+}
+
+fn pair(first: u16, second: u16) -> [u8; 4] {
+    let [a, b] = first.to_be_bytes();
+    let [c, d] = second.to_be_bytes();
+    [a, b, c, d]
+}
+
+/// An ICMP message
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Icmp<'a> {
+    /// The checksum of the message
+    pub checksum: u16,
+    /// The typed contents of the message
+    pub message: Message<'a>
+}
+
+impl<'a> Icmp<'a> {
+    /// Creates a new ICMP message with a checksum of zero
+    pub fn new(message: Message<'a>) -> Self {
+        Icmp { checksum: 0, message }
+    }
+
+    /// Creates a new echo request with the specified identifier, sequence number, and data
+    pub fn echo_request(identifier: u16, sequence: u16, data: &'a [u8]) -> Self {
+        Icmp::new(Message::EchoRequest(Echo { identifier, sequence, data }))
+    }
+
+    /// Parses an ICMP message from a slice of bytes. The message covers the entire slice.
+    ///
+    /// Messages with a known type but an unknown code are parsed as [`Unknown`](enum.Message.html#variant.Unknown) messages.
+    /// Any data after the timestamps of a timestamp message is kept in its [`data`](struct.Timestamp.html#structfield.data) field.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into().0;
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let (kind, code) = (bytes[0], bytes[1]);
+        let checksum = u16::from_be_bytes([bytes[2], bytes[3]]);
+        let rest = <[u8; 4]>::try_from(&bytes[4..8]).unwrap();
+        let data = &bytes[HEADER_LEN..];
+        let high = u16::from_be_bytes([rest[0], rest[1]]);
+        let low = u16::from_be_bytes([rest[2], rest[3]]);
+        let u32_at = |start: usize| u32::from_be_bytes(<[u8; 4]>::try_from(&data[start..start + 4]).unwrap());
+
+        let message = match (kind, code) {
+            (Message::ECHO_REPLY, 0) => Message::EchoReply(Echo { identifier: high, sequence: low, data }),
+            (Message::ECHO_REQUEST, 0) => Message::EchoRequest(Echo { identifier: high, sequence: low, data }),
+            (Message::DESTINATION_UNREACHABLE, 0..=15) =>
+                Message::DestinationUnreachable { code: UnreachableCode(code), rest, original: Original(data) },
+            (Message::REDIRECT, 0..=3) =>
+                Message::Redirect { code: RedirectCode(code), gateway: Ipv4Addr::from(rest), original: Original(data) },
+            (Message::TIME_EXCEEDED, 0..=1) => Message::TimeExceeded { code: TimeExceededCode(code), rest, original: Original(data) },
+            (Message::PARAMETER_PROBLEM, 0..=2) =>
+                Message::ParameterProblem { code: ParameterProblemCode(code), rest, original: Original(data) },
+            (Message::TIMESTAMP_REQUEST, 0) | (Message::TIMESTAMP_REPLY, 0) => {
+                if data.len() < TIMESTAMP_DATA_LEN {
+                    return Err(ParseError::Truncated);
+                }
+                let timestamp = Timestamp {
+                    identifier: high,
+                    sequence: low,
+                    originate: u32_at(0),
+                    receive: u32_at(4),
+                    transmit: u32_at(8),
+                    data: &data[TIMESTAMP_DATA_LEN..]
+                };
+                if kind == Message::TIMESTAMP_REQUEST {
+                    Message::TimestampRequest(timestamp)
+                } else {
+                    Message::TimestampReply(timestamp)
+                }
+            },
+            _ => Message::Unknown { kind, code, rest, data },
+        };
+
+        Ok(Icmp { checksum, message })
+    }
+
+    /// Computes the checksum of the message, ignoring the current value of the [`checksum`](#structfield.checksum) field
+    pub fn compute_checksum(&self) -> u16 {
+        let mut checksum = Checksum::new();
+        checksum
+            .add_u16(u16::from_be_bytes([self.message.kind(), self.message.code()]))
+            .add_bytes(&self.message.rest());
+        if let Some(timestamps) = self.message.timestamps() {
+            checksum.add_bytes(&timestamps);
+        }
+        checksum.add_bytes(self.message.data()).finish()
+    }
+
+    /// Returns whether the [`checksum`](#structfield.checksum) field matches the checksum of the message
+    pub fn verify_checksum(&self) -> bool {
+        self.checksum == self.compute_checksum()
+    }
+
+    /// Writes the message to the output, replacing the [`checksum`](#structfield.checksum) field with the checksum of the message
+    pub fn write_with_checksum(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        Icmp { checksum: self.compute_checksum(), message: self.message }.write(output)
+    }
+}
+
+impl Sealed for Icmp<'_> { }
+impl Size for Icmp<'_> {
+    fn size(&self) -> usize {
+        let timestamps = self.message.timestamps().map_or(0, |timestamps| timestamps.len());
+        HEADER_LEN + timestamps + self.message.data().len()
+    }
+}
+/// Writes the message using the checksum field as-is
+impl Write for Icmp<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.message.kind())?;
+        cursor.write(&self.message.code())?;
+        cursor.write(&self.checksum)?;
+        cursor.write(&self.message.rest()[..])?;
+        if let Some(timestamps) = self.message.timestamps() {
+            cursor.write(&timestamps[..])?;
+        }
+        cursor.write(self.message.data())?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::network::Protocol;
+
+    #[test]
+    fn echo() {
+        let request = Icmp::echo_request(0x1234, 7, b"ping");
+        let mut buf = [0; 12];
+        assert_eq!(request.write_with_checksum(&mut buf), Ok(12));
+        assert!(checksum::verify(&buf));
+        assert_eq!(&buf[..2], &[Message::ECHO_REQUEST, 0]);
+
+        let parsed = Icmp::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum());
+        assert_eq!(parsed.message, Message::EchoRequest(Echo { identifier: 0x1234, sequence: 7, data: b"ping" }));
+
+        let reply = Icmp::new(parsed.message.echo_reply().unwrap());
+        assert_eq!(reply.message.echo_reply(), None);
+        let mut reply_buf = [0; 12];
+        assert_eq!(reply.write_with_checksum(&mut reply_buf), Ok(12));
+        assert!(checksum::verify(&reply_buf));
+        assert_eq!(Icmp::parse(&reply_buf[..]).unwrap().message, Message::EchoReply(Echo { identifier: 0x1234, sequence: 7, data: b"ping" }));
+    }
+
+    #[test]
+    fn time_exceeded() {
+        // an IPv4 packet with a UDP header and 4 bytes of data, which are cut off in the quote
+        let packet = [
+            0x45, 0x00, 0x00, 0x20, 0x00, 0x01, 0x00, 0x00, 0x01, 0x11, 0x00, 0x00,
+            0x0A, 0x00, 0x00, 0x01, 0x08, 0x08, 0x08, 0x08,
+            0x82, 0x9B, 0x82, 0x9B, 0x00, 0x0C, 0x00, 0x00, 0xDE, 0xAD, 0xBE, 0xEF
+        ];
+        let original = Original::quote(&packet);
+        assert_eq!(original.0.len(), 28);
+
+        let message = Icmp::new(Message::TimeExceeded { code: TimeExceededCode::TTL_EXCEEDED, rest: [0; 4], original });
+        let mut buf = [0; 36];
+        assert_eq!(message.write_with_checksum(&mut buf), Ok(36));
+
+        let parsed = Icmp::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum());
+        assert!(parsed.message.is_error());
+        let quoted = parsed.message.original().unwrap().parse().unwrap();
+        assert_eq!(quoted.protocol, Protocol::UDP);
+        assert_eq!(quoted.destination, Ipv4Addr::new(8, 8, 8, 8));
+        assert_eq!(quoted.total_length, 32);
+        assert_eq!(quoted.payload().0, &packet[20..28]);
+    }
+
+    #[test]
+    fn time_exceeded_with_length() {
+        // the original datagram is padded to 32 bytes and followed by an empty extension structure
+        let mut bytes = [0; 44];
+        bytes[..8].copy_from_slice(&[11, 0, 0, 0, 0, 8, 0, 0]);
+        bytes[8..28].copy_from_slice(&[0x45, 0, 0, 20, 0, 0, 0, 0, 1, 17, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2]);
+        bytes[40..].copy_from_slice(&[0x20, 0, 0, 0]);
+        let checksum = checksum::compute(&bytes).to_be_bytes();
+        bytes[2..4].copy_from_slice(&checksum);
+
+        let parsed = Icmp::parse(&bytes[..]).unwrap();
+        assert!(parsed.verify_checksum());
+        assert_eq!(parsed.message.original_length(), Some(8));
+        assert_eq!(parsed.message.original(), Some(Original(&bytes[8..])));
+
+        let mut buf = [0; 44];
+        assert_eq!(parsed.write(&mut buf), Ok(44));
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn error_messages() {
+        let quote = [0x45, 0, 0, 20, 0, 0, 0, 0, 64, 6, 0, 0, 10, 0, 0, 1, 10, 0, 0, 2];
+        let bytes = [[3, 4, 0, 0, 0, 0, 0x05, 0xDC], [5, 1, 0, 0, 10, 0, 0, 254], [12, 0, 0, 0, 9, 5, 0, 0]];
+        let expected = [
+            Message::DestinationUnreachable { code: UnreachableCode::FRAGMENTATION_NEEDED, rest: [0, 0, 0x05, 0xDC], original: Original(&quote) },
+            Message::Redirect { code: RedirectCode::HOST, gateway: Ipv4Addr::new(10, 0, 0, 254), original: Original(&quote) },
+            Message::ParameterProblem { code: ParameterProblemCode::POINTER, rest: [9, 5, 0, 0], original: Original(&quote) },
+        ];
+
+        for (header, expected) in bytes.iter().zip(expected.iter()) {
+            let mut message = [0; 28];
+            message[..8].copy_from_slice(header);
+            message[8..].copy_from_slice(&quote);
+
+            let parsed = Icmp::parse(&message[..]).unwrap();
+            assert_eq!(parsed.message, *expected);
+
+            let mut buf = [0; 28];
+            assert_eq!(parsed.write(&mut buf), Ok(28));
+            assert_eq!(buf, message);
+        }
+        assert_eq!(expected[0].next_hop_mtu(), Some(1500));
+        assert_eq!(expected[2].pointer(), Some(9));
+        assert_eq!(expected[1].pointer(), None);
+    }
+
+    #[test]
+    fn timestamp() {
+        let timestamp = Timestamp { identifier: 1, sequence: 2, originate: 1000, receive: 2000, transmit: 3000, data: &[] };
+        let message = Icmp::new(Message::TimestampReply(timestamp));
+        assert_eq!(message.size(), 20);
+
+        let mut buf = [0; 20];
+        assert_eq!(message.write_with_checksum(&mut buf), Ok(20));
+        assert!(checksum::verify(&buf));
+
+        let parsed = Icmp::parse(&buf[..]).unwrap();
+        assert_eq!(parsed.message, Message::TimestampReply(timestamp));
+        assert!(parsed.verify_checksum());
+        assert_eq!(Icmp::parse(&buf[..19]), Err(ParseError::Truncated));
+
+        let padded = Icmp::new(Message::TimestampReply(Timestamp { data: &[0xAB; 6], ..timestamp }));
+        let mut buf = [0; 26];
+        assert_eq!(padded.write_with_checksum(&mut buf), Ok(26));
+        let parsed = Icmp::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum());
+        assert_eq!(parsed.message, padded.message);
+    }
+
+    #[test]
+    fn unknown() {
+        let bytes = [3, 16, 0xAB, 0xCD, 1, 2, 3, 4, 5];
+        let parsed = Icmp::parse(&bytes[..]).unwrap();
+        assert_eq!(parsed.message, Message::Unknown { kind: 3, code: 16, rest: [1, 2, 3, 4], data: &[5] });
+
+        let mut buf = [0; 9];
+        assert_eq!(parsed.write(&mut buf), Ok(9));
+        assert_eq!(buf, bytes);
+        assert_eq!(Icmp::parse(&bytes[..7]), Err(ParseError::Truncated));
+    }
+}
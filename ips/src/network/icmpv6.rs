@@ -0,0 +1,1001 @@
+//! Types for reading and writing ICMPv6 messages, including Neighbor Discovery Protocol messages
+
+use crate::checksum::PseudoHeader;
+use crate::internal::Sealed;
+use crate::network::icmp::Echo;
+use crate::network::{ipv6, Protocol};
+use crate::payload;
+use crate::physical::{self, Cursor, Read, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::Ipv6Addr;
+use macress::{Address, Eui48};
+
+type MacAddr = Address<Eui48>;
+
+/// The length of an ICMPv6 header, including the type-specific rest of the header
+pub const HEADER_LEN: usize = 8;
+/// The hop limit required on all Neighbor Discovery Protocol messages
+pub const NDP_HOP_LIMIT: u8 = 255;
+
+/// An error that occurs while parsing an ICMPv6 message or its options
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the ICMPv6 header, a fixed length message body, or an option
+    Truncated,
+    /// An option had a length that isn't valid for its kind
+    InvalidOptionLength {
+        /// The kind of the option
+        kind: u8,
+        /// The length of the option in units of 8 octets
+        length: u8
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad ICMPv6 message; ran out of data"),
+            ParseError::InvalidOptionLength { kind, length } => write!(f, "bad NDP option; invalid length {} for option kind {}", length, kind),
+        }
+    }
+}
+
+/// Gets the solicited-node multicast address of the specified address, used as the destination of neighbor solicitations
+pub fn solicited_node(address: Ipv6Addr) -> Ipv6Addr {
+    let octets = address.octets();
+    Ipv6Addr::new(0xFF02, 0, 0, 0, 0, 1, 0xFF00 | octets[13] as u16, u16::from_be_bytes([octets[14], octets[15]]))
+}
+
+/// The code of a destination unreachable message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct UnreachableCode(pub u8);
+
+impl UnreachableCode {
+    /// There is no route to the destination
+    pub const NO_ROUTE: UnreachableCode = UnreachableCode(0);
+    /// Communication with the destination is administratively prohibited
+    pub const PROHIBITED: UnreachableCode = UnreachableCode(1);
+    /// The destination is beyond the scope of the source address
+    pub const BEYOND_SCOPE: UnreachableCode = UnreachableCode(2);
+    /// The destination address is unreachable
+    pub const ADDRESS: UnreachableCode = UnreachableCode(3);
+    /// No process is listening on the destination port
+    pub const PORT: UnreachableCode = UnreachableCode(4);
+    /// The source address failed an ingress or egress policy
+    pub const SOURCE_POLICY_FAILED: UnreachableCode = UnreachableCode(5);
+    /// The route to the destination is a reject route
+    pub const REJECT_ROUTE: UnreachableCode = UnreachableCode(6);
+    /// There was an error in the source routing header
+    pub const SOURCE_ROUTING_HEADER: UnreachableCode = UnreachableCode(7);
+}
+
+/// The code of a time exceeded message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct TimeExceededCode(pub u8);
+
+impl TimeExceededCode {
+    /// The hop limit of the packet reached zero in transit
+    pub const HOP_LIMIT_EXCEEDED: TimeExceededCode = TimeExceededCode(0);
+    /// The fragments of the packet weren't reassembled in time
+    pub const REASSEMBLY_EXCEEDED: TimeExceededCode = TimeExceededCode(1);
+}
+
+/// The code of a parameter problem message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ParameterProblemCode(pub u8);
+
+impl ParameterProblemCode {
+    /// An erroneous header field was encountered
+    pub const HEADER_FIELD: ParameterProblemCode = ParameterProblemCode(0);
+    /// An unrecognized next header type was encountered
+    pub const NEXT_HEADER: ParameterProblemCode = ParameterProblemCode(1);
+    /// An unrecognized IPv6 option was encountered
+    pub const OPTION: ParameterProblemCode = ParameterProblemCode(2);
+    /// The first fragment doesn't contain the entire header chain
+    pub const INCOMPLETE_HEADER_CHAIN: ParameterProblemCode = ParameterProblemCode(3);
+}
+
+/// The flags of a router advertisement
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct RouterFlags(u8);
+
+impl RouterFlags {
+    /// No flags set
+    pub const NONE: RouterFlags = RouterFlags(0);
+    /// Addresses are available from DHCPv6
+    pub const MANAGED: RouterFlags = RouterFlags(0x80);
+    /// Other configuration is available from DHCPv6
+    pub const OTHER: RouterFlags = RouterFlags(0x40);
+
+    /// Returns new flags from the specified value
+    pub const fn raw(value: u8) -> RouterFlags {
+        RouterFlags(value)
+    }
+    /// Gets whether all of the specified flags are set
+    pub const fn contains(self, other: RouterFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+    /// Gets whether the managed address configuration flag is set
+    pub const fn managed(self) -> bool { self.contains(Self::MANAGED) }
+    /// Gets whether the other configuration flag is set
+    pub const fn other(self) -> bool { self.contains(Self::OTHER) }
+    /// Gets the raw underlying value of these flags
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// The flags of a neighbor advertisement
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct NeighborFlags(u8);
+
+impl NeighborFlags {
+    /// No flags set
+    pub const NONE: NeighborFlags = NeighborFlags(0);
+    /// The sender is a router
+    pub const ROUTER: NeighborFlags = NeighborFlags(0x80);
+    /// The advertisement was sent in response to a neighbor solicitation
+    pub const SOLICITED: NeighborFlags = NeighborFlags(0x40);
+    /// The advertisement should override an existing cache entry
+    pub const OVERRIDE: NeighborFlags = NeighborFlags(0x20);
+
+    /// Returns new flags from the top 3 bits of the specified value
+    pub const fn raw(value: u8) -> NeighborFlags {
+        NeighborFlags(value & 0xE0)
+    }
+    /// Gets whether all of the specified flags are set
+    pub const fn contains(self, other: NeighborFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+    /// Gets whether the router flag is set
+    pub const fn router(self) -> bool { self.contains(Self::ROUTER) }
+    /// Gets whether the solicited flag is set
+    pub const fn solicited(self) -> bool { self.contains(Self::SOLICITED) }
+    /// Gets whether the override flag is set
+    pub const fn override_entry(self) -> bool { self.contains(Self::OVERRIDE) }
+    /// Gets the raw underlying value of these flags
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// The flags of a prefix information option
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct PrefixFlags(u8);
+
+impl PrefixFlags {
+    /// No flags set
+    pub const NONE: PrefixFlags = PrefixFlags(0);
+    /// The prefix can be used for on-link determination
+    pub const ON_LINK: PrefixFlags = PrefixFlags(0x80);
+    /// The prefix can be used for stateless address autoconfiguration
+    pub const AUTONOMOUS: PrefixFlags = PrefixFlags(0x40);
+
+    /// Returns new flags from the specified value
+    pub const fn raw(value: u8) -> PrefixFlags {
+        PrefixFlags(value)
+    }
+    /// Gets whether all of the specified flags are set
+    pub const fn contains(self, other: PrefixFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+    /// Gets whether the on-link flag is set
+    pub const fn on_link(self) -> bool { self.contains(Self::ON_LINK) }
+    /// Gets whether the autonomous address configuration flag is set
+    pub const fn autonomous(self) -> bool { self.contains(Self::AUTONOMOUS) }
+    /// Gets the raw underlying value of these flags
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// The contents of a prefix information option
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PrefixInformation {
+    /// The number of leading bits in the prefix that are valid
+    pub prefix_length: u8,
+    /// The flags of the prefix
+    pub flags: PrefixFlags,
+    /// The number of seconds the prefix is valid for on-link determination
+    pub valid_lifetime: u32,
+    /// The number of seconds addresses generated from the prefix remain preferred
+    pub preferred_lifetime: u32,
+    /// The prefix
+    pub prefix: Ipv6Addr
+}
+
+/// The original packet quoted in an ICMPv6 error message or redirected header option,
+/// containing as much of the packet as fits without exceeding the minimum IPv6 MTU
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Original<'a>(pub &'a [u8]);
+
+impl<'a> Original<'a> {
+    /// The maximum length of a quoted packet in an error message, so the message fits in the minimum IPv6 MTU
+    pub const MAX_LEN: usize = 1280 - ipv6::HEADER_LEN - HEADER_LEN;
+
+    /// Quotes as much of the specified packet as fits in an error message
+    pub fn quote(packet: &'a [u8]) -> Self {
+        Original(&packet[..packet.len().min(Self::MAX_LEN)])
+    }
+
+    /// Parses the original IPv6 header. The payload of the parsed packet is the quoted part of the original payload.
+    pub fn parse(&self) -> Result<ipv6::Partial<'a>, ipv6::ParseError> {
+        ipv6::Partial::parse(self.0)
+    }
+}
+
+/// A Neighbor Discovery Protocol option
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OptionValue<'a> {
+    /// The ethernet address of the sender
+    SourceLinkLayerAddress(MacAddr),
+    /// The ethernet address of the target
+    TargetLinkLayerAddress(MacAddr),
+    /// A prefix advertised by a router
+    PrefixInformation(PrefixInformation),
+    /// The packet that caused a redirect
+    RedirectedHeader(Original<'a>),
+    /// The MTU of the link
+    Mtu(u32),
+    /// An option of an unknown kind, or a link-layer address option for an address that isn't an ethernet address
+    Unknown {
+        /// The kind of the option
+        kind: u8,
+        /// The contents of the option following the kind and length octets
+        data: &'a [u8]
+    }
+}
+
+impl OptionValue<'_> {
+    /// The source link-layer address option kind
+    pub const SOURCE_LINK_LAYER_ADDRESS: u8 = 1;
+    /// The target link-layer address option kind
+    pub const TARGET_LINK_LAYER_ADDRESS: u8 = 2;
+    /// The prefix information option kind
+    pub const PREFIX_INFORMATION: u8 = 3;
+    /// The redirected header option kind
+    pub const REDIRECTED_HEADER: u8 = 4;
+    /// The MTU option kind
+    pub const MTU: u8 = 5;
+
+    /// Gets the kind of the option
+    pub fn kind(&self) -> u8 {
+        match self {
+            OptionValue::SourceLinkLayerAddress(_) => Self::SOURCE_LINK_LAYER_ADDRESS,
+            OptionValue::TargetLinkLayerAddress(_) => Self::TARGET_LINK_LAYER_ADDRESS,
+            OptionValue::PrefixInformation(_) => Self::PREFIX_INFORMATION,
+            OptionValue::RedirectedHeader(_) => Self::REDIRECTED_HEADER,
+            OptionValue::Mtu(_) => Self::MTU,
+            OptionValue::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    fn content_len(&self) -> usize {
+        match self {
+            OptionValue::SourceLinkLayerAddress(_) | OptionValue::TargetLinkLayerAddress(_) => 6,
+            OptionValue::PrefixInformation(_) => 30,
+            OptionValue::RedirectedHeader(original) => 6 + original.0.len(),
+            OptionValue::Mtu(_) => 6,
+            OptionValue::Unknown { data, .. } => data.len(),
+        }
+    }
+}
+
+impl Sealed for OptionValue<'_> { }
+/// The size of the option including the padding used to align it to an 8 octet boundary
+impl Size for OptionValue<'_> {
+    fn size(&self) -> usize {
+        (2 + self.content_len() + 7) & !7
+    }
+}
+/// Writes the option followed by zeroed padding to align it to an 8 octet boundary
+impl Write for OptionValue<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let size = self.size();
+        let length = u8::try_from(size / 8).map_err(|_| physical::Error)?;
+        let output = output.get_mut(..size).ok_or(physical::Error)?;
+        output.iter_mut().for_each(|b| *b = 0);
+
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.kind())?;
+        cursor.write(&length)?;
+        match self {
+            OptionValue::SourceLinkLayerAddress(address) | OptionValue::TargetLinkLayerAddress(address) => cursor.write(address)?,
+            OptionValue::PrefixInformation(info) => {
+                cursor.write(&info.prefix_length)?;
+                cursor.write(&info.flags.get())?;
+                cursor.write(&info.valid_lifetime)?;
+                cursor.write(&info.preferred_lifetime)?;
+                cursor.write(&0u32)?;
+                cursor.write(&info.prefix)?;
+            },
+            OptionValue::RedirectedHeader(original) => {
+                cursor.write(&[0; 6][..])?;
+                cursor.write(original.0)?;
+            },
+            OptionValue::Mtu(mtu) => {
+                cursor.write(&0u16)?;
+                cursor.write(mtu)?;
+            },
+            OptionValue::Unknown { data, .. } => cursor.write(*data)?,
+        }
+        Ok(size)
+    }
+}
+
+/// The unparsed options of a Neighbor Discovery Protocol message
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Options<'a>(pub &'a [u8]);
+
+impl<'a> Options<'a> {
+    /// An empty options list
+    pub const EMPTY: Options<'static> = Options(&[]);
+
+    /// Returns an iterator over the options in the list, or returns an error if an option is malformed
+    pub fn iter(&self) -> OptionsIter<'a> {
+        OptionsIter(self.0)
+    }
+
+    /// Gets the first source link-layer address option in the list, skipping malformed options
+    pub fn source_link_layer_address(&self) -> Option<MacAddr> {
+        self.iter().find_map(|option| match option {
+            Ok(OptionValue::SourceLinkLayerAddress(address)) => Some(address),
+            _ => None,
+        })
+    }
+
+    /// Gets the first target link-layer address option in the list, skipping malformed options
+    pub fn target_link_layer_address(&self) -> Option<MacAddr> {
+        self.iter().find_map(|option| match option {
+            Ok(OptionValue::TargetLinkLayerAddress(address)) => Some(address),
+            _ => None,
+        })
+    }
+}
+
+/// An iterator over the options in a Neighbor Discovery Protocol message
+#[derive(Clone, Debug)]
+pub struct OptionsIter<'a>(&'a [u8]);
+
+impl<'a> OptionsIter<'a> {
+    fn parse_option(&mut self) -> Result<OptionValue<'a>, ParseError> {
+        let (kind, length) = match self.0 {
+            [kind, length, ..] => (*kind, *length),
+            _ => return Err(ParseError::Truncated),
+        };
+        let invalid = ParseError::InvalidOptionLength { kind, length };
+        if length == 0 {
+            return Err(invalid);
+        }
+        let option = self.0.get(..length as usize * 8).ok_or(ParseError::Truncated)?;
+        self.0 = &self.0[option.len()..];
+
+        let data = &option[2..];
+        let u32_at = |start: usize| u32::from_be_bytes(<[u8; 4]>::try_from(&data[start..start + 4]).unwrap());
+        match (kind, length) {
+            (OptionValue::SOURCE_LINK_LAYER_ADDRESS, 1) => Ok(OptionValue::SourceLinkLayerAddress(MacAddr::read(data).unwrap())),
+            (OptionValue::TARGET_LINK_LAYER_ADDRESS, 1) => Ok(OptionValue::TargetLinkLayerAddress(MacAddr::read(data).unwrap())),
+            (OptionValue::PREFIX_INFORMATION, 4) => Ok(OptionValue::PrefixInformation(PrefixInformation {
+                prefix_length: data[0],
+                flags: PrefixFlags::raw(data[1]),
+                valid_lifetime: u32_at(2),
+                preferred_lifetime: u32_at(6),
+                prefix: Ipv6Addr::read(&data[14..]).unwrap()
+            })),
+            (OptionValue::REDIRECTED_HEADER, _) => Ok(OptionValue::RedirectedHeader(Original(&data[6..]))),
+            (OptionValue::MTU, 1) => Ok(OptionValue::Mtu(u32_at(2))),
+            (OptionValue::PREFIX_INFORMATION, _) | (OptionValue::MTU, _) => Err(invalid),
+            (kind, _) => Ok(OptionValue::Unknown { kind, data }),
+        }
+    }
+}
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = Result<OptionValue<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let option = self.parse_option();
+        if option.is_err() {
+            // the rest of the options can't be found without a valid length
+            self.0 = &[];
+        }
+        Some(option)
+    }
+}
+
+/// A typed ICMPv6 message
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Message<'a> {
+    /// The original packet couldn't be delivered
+    DestinationUnreachable {
+        /// Why the packet couldn't be delivered
+        code: UnreachableCode,
+        /// The raw rest of the header, containing the original length
+        rest: [u8; 4],
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// The original packet was larger than the MTU of the next link
+    PacketTooBig {
+        /// The MTU of the next link
+        mtu: u32,
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// The original packet was discarded because its hop limit or reassembly time was exceeded
+    TimeExceeded {
+        /// Which limit was exceeded
+        code: TimeExceededCode,
+        /// The raw rest of the header, containing the original length
+        rest: [u8; 4],
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// The original packet was discarded because of a problem with its headers
+    ParameterProblem {
+        /// The kind of problem
+        code: ParameterProblemCode,
+        /// The octet offset of the original packet where the error was found
+        pointer: u32,
+        /// The quoted original packet
+        original: Original<'a>
+    },
+    /// A request to echo the data back to the sender
+    EchoRequest(Echo<'a>),
+    /// A reply to an echo request
+    EchoReply(Echo<'a>),
+    /// A request for routers to send advertisements
+    RouterSolicitation {
+        /// The raw reserved rest of the header
+        rest: [u8; 4],
+        /// The options of the solicitation
+        options: Options<'a>
+    },
+    /// An advertisement of a router and the configuration of the link
+    RouterAdvertisement {
+        /// The default hop limit for packets sent on the link, or zero if unspecified
+        hop_limit: u8,
+        /// The flags of the advertisement
+        flags: RouterFlags,
+        /// The number of seconds the router can be used as a default router
+        router_lifetime: u16,
+        /// The number of milliseconds a neighbor is reachable after a reachability confirmation, or zero if unspecified
+        reachable_time: u32,
+        /// The number of milliseconds between neighbor solicitations, or zero if unspecified
+        retransmit_timer: u32,
+        /// The options of the advertisement
+        options: Options<'a>
+    },
+    /// A request for the link-layer address of the target
+    NeighborSolicitation {
+        /// The raw reserved rest of the header
+        rest: [u8; 4],
+        /// The address whose link-layer address is requested
+        target: Ipv6Addr,
+        /// The options of the solicitation
+        options: Options<'a>
+    },
+    /// An advertisement of the link-layer address of the target
+    NeighborAdvertisement {
+        /// The raw rest of the header, with the flags in the top 3 bits
+        rest: [u8; 4],
+        /// The address whose link-layer address is advertised
+        target: Ipv6Addr,
+        /// The options of the advertisement
+        options: Options<'a>
+    },
+    /// Packets to the destination should be sent to a better first hop target
+    Redirect {
+        /// The raw reserved rest of the header
+        rest: [u8; 4],
+        /// The better first hop to send packets to
+        target: Ipv6Addr,
+        /// The destination of the redirected packets
+        destination: Ipv6Addr,
+        /// The options of the redirect
+        options: Options<'a>
+    },
+    /// A message of an unknown type, or a known type with an unknown code
+    Unknown {
+        /// The type of the message
+        kind: u8,
+        /// The code of the message
+        code: u8,
+        /// The type-specific rest of the header
+        rest: [u8; 4],
+        /// The data after the header
+        data: &'a [u8]
+    }
+}
+
+impl<'a> Message<'a> {
+    /// The destination unreachable message type
+    pub const DESTINATION_UNREACHABLE: u8 = 1;
+    /// The packet too big message type
+    pub const PACKET_TOO_BIG: u8 = 2;
+    /// The time exceeded message type
+    pub const TIME_EXCEEDED: u8 = 3;
+    /// The parameter problem message type
+    pub const PARAMETER_PROBLEM: u8 = 4;
+    /// The echo request message type
+    pub const ECHO_REQUEST: u8 = 128;
+    /// The echo reply message type
+    pub const ECHO_REPLY: u8 = 129;
+    /// The router solicitation message type
+    pub const ROUTER_SOLICITATION: u8 = 133;
+    /// The router advertisement message type
+    pub const ROUTER_ADVERTISEMENT: u8 = 134;
+    /// The neighbor solicitation message type
+    pub const NEIGHBOR_SOLICITATION: u8 = 135;
+    /// The neighbor advertisement message type
+    pub const NEIGHBOR_ADVERTISEMENT: u8 = 136;
+    /// The redirect message type
+    pub const REDIRECT: u8 = 137;
+
+    /// Gets the type of the message
+    pub fn kind(&self) -> u8 {
+        match self {
+            Message::DestinationUnreachable { .. } => Self::DESTINATION_UNREACHABLE,
+            Message::PacketTooBig { .. } => Self::PACKET_TOO_BIG,
+            Message::TimeExceeded { .. } => Self::TIME_EXCEEDED,
+            Message::ParameterProblem { .. } => Self::PARAMETER_PROBLEM,
+            Message::EchoRequest(_) => Self::ECHO_REQUEST,
+            Message::EchoReply(_) => Self::ECHO_REPLY,
+            Message::RouterSolicitation { .. } => Self::ROUTER_SOLICITATION,
+            Message::RouterAdvertisement { .. } => Self::ROUTER_ADVERTISEMENT,
+            Message::NeighborSolicitation { .. } => Self::NEIGHBOR_SOLICITATION,
+            Message::NeighborAdvertisement { .. } => Self::NEIGHBOR_ADVERTISEMENT,
+            Message::Redirect { .. } => Self::REDIRECT,
+            Message::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    /// Gets the code of the message
+    pub fn code(&self) -> u8 {
+        match self {
+            Message::DestinationUnreachable { code, .. } => code.0,
+            Message::TimeExceeded { code, .. } => code.0,
+            Message::ParameterProblem { code, .. } => code.0,
+            Message::Unknown { code, .. } => *code,
+            _ => 0,
+        }
+    }
+
+    /// Returns whether this message is an error message. All error messages have a type less than 128.
+    pub fn is_error(&self) -> bool {
+        self.kind() < 128
+    }
+
+    /// Returns whether this message is a Neighbor Discovery Protocol message
+    pub fn is_ndp(&self) -> bool {
+        self.options().is_some()
+    }
+
+    /// Gets the original packet quoted by this message, if this is a known error message
+    pub fn original(&self) -> Option<Original<'a>> {
+        match *self {
+            Message::DestinationUnreachable { original, .. } |
+            Message::PacketTooBig { original, .. } |
+            Message::TimeExceeded { original, .. } |
+            Message::ParameterProblem { original, .. } => Some(original),
+            _ => None,
+        }
+    }
+
+    /// Gets the options of this message, if this is a Neighbor Discovery Protocol message
+    pub fn options(&self) -> Option<Options<'a>> {
+        match *self {
+            Message::RouterSolicitation { options, .. } |
+            Message::RouterAdvertisement { options, .. } |
+            Message::NeighborSolicitation { options, .. } |
+            Message::NeighborAdvertisement { options, .. } |
+            Message::Redirect { options, .. } => Some(options),
+            _ => None,
+        }
+    }
+
+    /// Gets the length of the original packet in 64-bit words, as defined by RFC 4884.
+    ///
+    /// Returns `None` if this message can't carry the length, and `Some(0)` if the sender didn't include extensions.
+    pub fn original_length(&self) -> Option<u8> {
+        match self {
+            Message::DestinationUnreachable { rest, .. } | Message::TimeExceeded { rest, .. } => Some(rest[0]),
+            _ => None,
+        }
+    }
+
+    /// Gets the flags of a neighbor advertisement, returning `None` if this message isn't a neighbor advertisement
+    pub fn neighbor_flags(&self) -> Option<NeighborFlags> {
+        match self {
+            Message::NeighborAdvertisement { rest, .. } => Some(NeighborFlags::raw(rest[0])),
+            _ => None,
+        }
+    }
+
+    /// Creates an echo reply to this message, returning `None` if this message isn't an echo request
+    pub fn echo_reply(&self) -> Option<Self> {
+        match *self {
+            Message::EchoRequest(echo) => Some(Message::EchoReply(echo)),
+            _ => None,
+        }
+    }
+
+    fn rest(&self) -> [u8; 4] {
+        match self {
+            Message::PacketTooBig { mtu, .. } => mtu.to_be_bytes(),
+            Message::ParameterProblem { pointer, .. } => pointer.to_be_bytes(),
+            Message::EchoRequest(echo) | Message::EchoReply(echo) => {
+                let [a, b] = echo.identifier.to_be_bytes();
+                let [c, d] = echo.sequence.to_be_bytes();
+                [a, b, c, d]
+            },
+            Message::RouterAdvertisement { hop_limit, flags, router_lifetime, .. } => {
+                let [c, d] = router_lifetime.to_be_bytes();
+                [*hop_limit, flags.get(), c, d]
+            },
+            Message::DestinationUnreachable { rest, .. } |
+            Message::TimeExceeded { rest, .. } |
+            Message::RouterSolicitation { rest, .. } |
+            Message::NeighborSolicitation { rest, .. } |
+            Message::NeighborAdvertisement { rest, .. } |
+            Message::Redirect { rest, .. } |
+            Message::Unknown { rest, .. } => *rest,
+        }
+    }
+
+    /// Gets the fixed length part of the message body that comes before the variable length data
+    fn fixed(&self) -> ([u8; 32], usize) {
+        let mut fixed = [0; 32];
+        let len = match self {
+            Message::RouterAdvertisement { reachable_time, retransmit_timer, .. } => {
+                fixed[..4].copy_from_slice(&reachable_time.to_be_bytes());
+                fixed[4..8].copy_from_slice(&retransmit_timer.to_be_bytes());
+                8
+            },
+            Message::NeighborSolicitation { target, .. } | Message::NeighborAdvertisement { target, .. } => {
+                fixed[..16].copy_from_slice(&target.octets());
+                16
+            },
+            Message::Redirect { target, destination, .. } => {
+                fixed[..16].copy_from_slice(&target.octets());
+                fixed[16..].copy_from_slice(&destination.octets());
+                32
+            },
+            _ => 0,
+        };
+        (fixed, len)
+    }
+
+    /// Gets the variable length data at the end of the message body
+    fn data(&self) -> &'a [u8] {
+        match *self {
+            Message::EchoRequest(echo) | Message::EchoReply(echo) => echo.data,
+            Message::Unknown { data, .. } => data,
+            _ => match (self.original(), self.options()) {
+                (Some(original), _) => original.0,
+                (_, Some(options)) => options.0,
+                _ => &[],
+            },
+        }
+    }
+}
+
+/// An ICMPv6 message
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Icmpv6<'a> {
+    /// The checksum of the message
+    pub checksum: u16,
+    /// The typed contents of the message
+    pub message: Message<'a>
+}
+
+impl<'a> Icmpv6<'a> {
+    /// Creates a new ICMPv6 message with a checksum of zero
+    pub fn new(message: Message<'a>) -> Self {
+        Icmpv6 { checksum: 0, message }
+    }
+
+    /// Creates a new echo request with the specified identifier, sequence number, and data
+    pub fn echo_request(identifier: u16, sequence: u16, data: &'a [u8]) -> Self {
+        Icmpv6::new(Message::EchoRequest(Echo { identifier, sequence, data }))
+    }
+
+    /// Parses an ICMPv6 message from a slice of bytes. The message covers the entire slice.
+    ///
+    /// Messages with a known type but an unknown code are parsed as [`Unknown`](enum.Message.html#variant.Unknown) messages.
+    /// The options of Neighbor Discovery Protocol messages are parsed when they're iterated.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into().0;
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let (kind, code) = (bytes[0], bytes[1]);
+        let checksum = u16::from_be_bytes([bytes[2], bytes[3]]);
+        let rest = <[u8; 4]>::try_from(&bytes[4..8]).unwrap();
+        let data = &bytes[HEADER_LEN..];
+        let high = u16::from_be_bytes([rest[0], rest[1]]);
+        let low = u16::from_be_bytes([rest[2], rest[3]]);
+        let fixed = |len: usize| data.get(..len).map(|_| Options(&data[len..])).ok_or(ParseError::Truncated);
+        let address = |start: usize| Ipv6Addr::read(&data[start..]).unwrap();
+
+        let message = match (kind, code) {
+            (Message::DESTINATION_UNREACHABLE, 0..=7) => Message::DestinationUnreachable { code: UnreachableCode(code), rest, original: Original(data) },
+            (Message::PACKET_TOO_BIG, 0) => Message::PacketTooBig { mtu: u32::from_be_bytes(rest), original: Original(data) },
+            (Message::TIME_EXCEEDED, 0..=1) => Message::TimeExceeded { code: TimeExceededCode(code), rest, original: Original(data) },
+            (Message::PARAMETER_PROBLEM, 0..=3) =>
+                Message::ParameterProblem { code: ParameterProblemCode(code), pointer: u32::from_be_bytes(rest), original: Original(data) },
+            (Message::ECHO_REQUEST, 0) => Message::EchoRequest(Echo { identifier: high, sequence: low, data }),
+            (Message::ECHO_REPLY, 0) => Message::EchoReply(Echo { identifier: high, sequence: low, data }),
+            (Message::ROUTER_SOLICITATION, 0) => Message::RouterSolicitation { rest, options: Options(data) },
+            (Message::ROUTER_ADVERTISEMENT, 0) => {
+                let options = fixed(8)?;
+                Message::RouterAdvertisement {
+                    hop_limit: rest[0],
+                    flags: RouterFlags::raw(rest[1]),
+                    router_lifetime: low,
+                    reachable_time: u32::read(data).unwrap(),
+                    retransmit_timer: u32::read(&data[4..]).unwrap(),
+                    options
+                }
+            },
+            (Message::NEIGHBOR_SOLICITATION, 0) => {
+                let options = fixed(16)?;
+                Message::NeighborSolicitation { rest, target: address(0), options }
+            },
+            (Message::NEIGHBOR_ADVERTISEMENT, 0) => {
+                let options = fixed(16)?;
+                Message::NeighborAdvertisement { rest, target: address(0), options }
+            },
+            (Message::REDIRECT, 0) => {
+                let options = fixed(32)?;
+                Message::Redirect { rest, target: address(0), destination: address(16), options }
+            },
+            _ => Message::Unknown { kind, code, rest, data },
+        };
+
+        Ok(Icmpv6 { checksum, message })
+    }
+
+    /// Computes the checksum of the message using the specified pseudo-header, ignoring the current value of the [`checksum`](#structfield.checksum) field
+    pub fn compute_checksum(&self, pseudo_header: PseudoHeader) -> u16 {
+        let (fixed, len) = self.message.fixed();
+        let mut checksum = pseudo_header.checksum(Protocol::ICMPV6, self.size() as u32);
+        checksum
+            .add_u16(u16::from_be_bytes([self.message.kind(), self.message.code()]))
+            .add_bytes(&self.message.rest())
+            .add_bytes(&fixed[..len])
+            .add_bytes(self.message.data());
+        checksum.finish()
+    }
+
+    /// Returns whether the [`checksum`](#structfield.checksum) field matches the checksum of the message
+    pub fn verify_checksum(&self, pseudo_header: PseudoHeader) -> bool {
+        self.checksum == self.compute_checksum(pseudo_header)
+    }
+
+    /// Writes the message to the output, replacing the [`checksum`](#structfield.checksum) field with a checksum
+    /// computed using the specified pseudo-header
+    pub fn write_with_checksum(&self, pseudo_header: PseudoHeader, output: &mut [u8]) -> Result<usize, physical::Error> {
+        Icmpv6 { checksum: self.compute_checksum(pseudo_header), message: self.message }.write(output)
+    }
+}
+
+impl Sealed for Icmpv6<'_> { }
+impl Size for Icmpv6<'_> {
+    fn size(&self) -> usize {
+        HEADER_LEN + self.message.fixed().1 + self.message.data().len()
+    }
+}
+/// Writes the message using the checksum field as-is
+impl Write for Icmpv6<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let (fixed, len) = self.message.fixed();
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.message.kind())?;
+        cursor.write(&self.message.code())?;
+        cursor.write(&self.checksum)?;
+        cursor.write(&self.message.rest()[..])?;
+        cursor.write(&fixed[..len])?;
+        cursor.write(self.message.data())?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: MacAddr = Address::new([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    const SOURCE: Ipv6Addr = Ipv6Addr::new(0xFE80, 0, 0, 0, 0, 0, 0, 1);
+    const TARGET: Ipv6Addr = Ipv6Addr::new(0xFE80, 0, 0, 0, 0, 0, 0x12, 0x3456);
+
+    fn pseudo_header(destination: Ipv6Addr) -> PseudoHeader {
+        PseudoHeader::V6 { source: SOURCE, destination }
+    }
+
+    #[test]
+    fn echo() {
+        let v6 = pseudo_header(TARGET);
+        let request = Icmpv6::echo_request(1, 2, b"ping");
+        let mut buf = [0; 12];
+        assert_eq!(request.write_with_checksum(v6, &mut buf), Ok(12));
+
+        let mut expected = v6.checksum(Protocol::ICMPV6, 12);
+        expected.add_bytes(&buf);
+        assert_eq!(expected.finish(), 0);
+
+        let parsed = Icmpv6::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum(v6));
+        assert!(!parsed.message.is_error() && !parsed.message.is_ndp());
+        assert_eq!(parsed.message.echo_reply(), Some(Message::EchoReply(Echo { identifier: 1, sequence: 2, data: b"ping" })));
+    }
+
+    #[test]
+    fn neighbor_solicitation() {
+        let destination = solicited_node(TARGET);
+        assert_eq!(destination, Ipv6Addr::new(0xFF02, 0, 0, 0, 0, 1, 0xFF12, 0x3456));
+
+        let mut options = [0; 8];
+        assert_eq!(OptionValue::SourceLinkLayerAddress(HOST).write(&mut options), Ok(8));
+        assert_eq!(options, [1, 1, 0x02, 0, 0, 0, 0, 1]);
+
+        let solicitation = Icmpv6::new(Message::NeighborSolicitation { rest: [0; 4], target: TARGET, options: Options(&options) });
+        let mut buf = [0; 32];
+        assert_eq!(solicitation.write_with_checksum(pseudo_header(destination), &mut buf), Ok(32));
+        assert_eq!(&buf[..2], &[Message::NEIGHBOR_SOLICITATION, 0]);
+        assert_eq!(&buf[8..24], &TARGET.octets());
+
+        let parsed = Icmpv6::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum(pseudo_header(destination)));
+        assert!(parsed.message.is_ndp());
+        assert_eq!(parsed.message, solicitation.message);
+        assert_eq!(parsed.message.options().unwrap().source_link_layer_address(), Some(HOST));
+        assert_eq!(Icmpv6::parse(&buf[..23]), Err(ParseError::Truncated));
+    }
+
+    #[test]
+    fn neighbor_advertisement() {
+        let bytes = [
+            136, 0, 0, 0, 0xE1, 0, 0, 0x2A,
+            0xFE, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x12, 0x34, 0x56,
+            2, 1, 0x02, 0, 0, 0, 0, 1
+        ];
+        let parsed = Icmpv6::parse(&bytes[..]).unwrap();
+        let flags = parsed.message.neighbor_flags().unwrap();
+        assert!(flags.router() && flags.solicited() && flags.override_entry());
+        match parsed.message {
+            Message::NeighborAdvertisement { target, options, .. } => {
+                assert_eq!(target, TARGET);
+                assert_eq!(options.target_link_layer_address(), Some(HOST));
+                assert_eq!(options.source_link_layer_address(), None);
+            },
+            other => panic!("expected a neighbor advertisement, found {:?}", other),
+        }
+
+        let mut buf = [0; 32];
+        assert_eq!(parsed.write(&mut buf), Ok(32));
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn router_advertisement() {
+        let prefix = PrefixInformation {
+            prefix_length: 64,
+            flags: PrefixFlags::ON_LINK,
+            valid_lifetime: 86400,
+            preferred_lifetime: 14400,
+            prefix: Ipv6Addr::new(0x2001, 0xDB8, 0, 0, 0, 0, 0, 0)
+        };
+        let mut options = [0; 48];
+        let mut written = OptionValue::PrefixInformation(prefix).write(&mut options).unwrap();
+        written += OptionValue::Mtu(1500).write(&mut options[written..]).unwrap();
+        written += OptionValue::SourceLinkLayerAddress(HOST).write(&mut options[written..]).unwrap();
+        assert_eq!(written, 48);
+
+        let advertisement = Icmpv6::new(Message::RouterAdvertisement {
+            hop_limit: 64,
+            flags: RouterFlags::MANAGED,
+            router_lifetime: 1800,
+            reachable_time: 0,
+            retransmit_timer: 0,
+            options: Options(&options)
+        });
+        let mut buf = [0; 64];
+        let all_nodes = Ipv6Addr::new(0xFF02, 0, 0, 0, 0, 0, 0, 1);
+        assert_eq!(advertisement.write_with_checksum(pseudo_header(all_nodes), &mut buf), Ok(64));
+
+        let parsed = Icmpv6::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum(pseudo_header(all_nodes)));
+        assert_eq!(parsed.message, advertisement.message);
+
+        let mut iter = parsed.message.options().unwrap().iter();
+        assert_eq!(iter.next(), Some(Ok(OptionValue::PrefixInformation(prefix))));
+        assert_eq!(iter.next(), Some(Ok(OptionValue::Mtu(1500))));
+        assert_eq!(iter.next(), Some(Ok(OptionValue::SourceLinkLayerAddress(HOST))));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn redirect() {
+        let original = [0x60, 0, 0, 0, 0, 0, 59, 64];
+        let mut options = [0; 16];
+        assert_eq!(OptionValue::RedirectedHeader(Original(&original)).write(&mut options), Ok(16));
+
+        let redirect = Icmpv6::new(Message::Redirect { rest: [0; 4], target: TARGET, destination: SOURCE, options: Options(&options) });
+        let mut buf = [0; 56];
+        assert_eq!(redirect.write(&mut buf), Ok(56));
+
+        let parsed = Icmpv6::parse(&buf[..]).unwrap();
+        assert_eq!(parsed.message, redirect.message);
+        let mut iter = parsed.message.options().unwrap().iter();
+        assert_eq!(iter.next(), Some(Ok(OptionValue::RedirectedHeader(Original(&original)))));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn packet_too_big() {
+        let quoted = [
+            0x60, 0, 0, 0, 0x05, 0xDC, 17, 64,
+            0xFE, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+            0xFE, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x12, 0x34, 0x56,
+            0x82, 0x9B, 0x00, 0x35, 0x05, 0xDC, 0x00, 0x00
+        ];
+        let message = Icmpv6::new(Message::PacketTooBig { mtu: 1280, original: Original::quote(&quoted) });
+        let mut buf = [0; 56];
+        assert_eq!(message.write_with_checksum(pseudo_header(TARGET), &mut buf), Ok(56));
+
+        let parsed = Icmpv6::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum(pseudo_header(TARGET)));
+        assert!(parsed.message.is_error());
+        let original = parsed.message.original().unwrap().parse().unwrap();
+        assert_eq!(original.next_header, Protocol::UDP);
+        assert_eq!(original.payload_length, 1500);
+        assert_eq!(original.payload().0, &quoted[40..]);
+    }
+
+    #[test]
+    fn time_exceeded_with_length() {
+        // the original packet is a bare 40 byte header followed by an empty extension structure
+        let mut bytes = [0; 52];
+        bytes[..8].copy_from_slice(&[3, 0, 0, 0, 5, 0, 0, 0]);
+        bytes[8..16].copy_from_slice(&[0x60, 0, 0, 0, 0, 0, 59, 1]);
+        bytes[16..32].copy_from_slice(&SOURCE.octets());
+        bytes[32..48].copy_from_slice(&TARGET.octets());
+        bytes[48..].copy_from_slice(&[0x20, 0, 0, 0]);
+        let mut checksum = pseudo_header(SOURCE).checksum(Protocol::ICMPV6, 52);
+        checksum.add_bytes(&bytes);
+        bytes[2..4].copy_from_slice(&checksum.finish().to_be_bytes());
+
+        let parsed = Icmpv6::parse(&bytes[..]).unwrap();
+        assert!(parsed.verify_checksum(pseudo_header(SOURCE)));
+        assert_eq!(parsed.message.original_length(), Some(5));
+
+        let mut buf = [0; 52];
+        assert_eq!(parsed.write(&mut buf), Ok(52));
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn option_errors() {
+        let mut iter = Options(&[1, 0, 0, 0, 0, 0, 0, 0]).iter();
+        assert_eq!(iter.next(), Some(Err(ParseError::InvalidOptionLength { kind: 1, length: 0 })));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = Options(&[5, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).iter();
+        assert_eq!(iter.next(), Some(Err(ParseError::InvalidOptionLength { kind: 5, length: 2 })));
+
+        let mut iter = Options(&[3, 4, 0, 0]).iter();
+        assert_eq!(iter.next(), Some(Err(ParseError::Truncated)));
+        assert_eq!(iter.next(), None);
+
+        // link-layer addresses that aren't ethernet addresses are unknown options
+        let mut iter = Options(&[1, 2, 1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 0, 0]).iter();
+        assert_eq!(iter.next(), Some(Ok(OptionValue::Unknown { kind: 1, data: &[1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 0, 0] })));
+    }
+}
@@ -0,0 +1,560 @@
+//! Types for reading and writing IPv4 packets
+
+use crate::checksum::{Checksum, PseudoHeader};
+use crate::internal::Sealed;
+use crate::network::Protocol;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::Ipv4Addr;
+
+/// The minimum length of an IPv4 header
+pub const MIN_HEADER_LEN: usize = 20;
+/// The maximum length of an IPv4 header
+pub const MAX_HEADER_LEN: usize = 60;
+/// The maximum length of the options in an IPv4 header
+pub const MAX_OPTIONS_LEN: usize = MAX_HEADER_LEN - MIN_HEADER_LEN;
+
+/// An IPv4 packet parsed from a slice of bytes, where any data after the total length of the packet is padding
+pub type Parsed<'a> = Ipv4<'a, payload::Padded<&'a [u8], payload::Any<'a>>>;
+/// An IPv4 packet parsed from a slice of bytes that may end before the total length of the packet,
+/// such as the original packet quoted in an ICMP error message
+pub type Partial<'a> = Ipv4<'a, payload::Unknown<'a>>;
+
+/// An error that occurs while parsing an IPv4 packet
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the IPv4 header or the end of the packet specified by the total length
+    Truncated,
+    /// The version field of the header was not 4
+    InvalidVersion(u8),
+    /// The internet header length was less than the minimum header length of 5 words
+    InvalidHeaderLength(u8),
+    /// The total length was less than the length of the header
+    InvalidTotalLength(u16)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad IPv4 packet; ran out of data"),
+            ParseError::InvalidVersion(v) => write!(f, "bad IPv4 packet; expected version 4, found {}", v),
+            ParseError::InvalidHeaderLength(ihl) => write!(f, "bad IPv4 packet; header length of {} words is too small", ihl),
+            ParseError::InvalidTotalLength(len) => write!(f, "bad IPv4 packet; total length of {} is smaller than the header", len),
+        }
+    }
+}
+
+/// The explicit congestion notification of an IPv4 packet
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Ecn {
+    /// The transport isn't capable of explicit congestion notification
+    #[default]
+    NotCapable = 0,
+    /// The transport is capable of explicit congestion notification (ECT(1))
+    Capable1 = 1,
+    /// The transport is capable of explicit congestion notification (ECT(0))
+    Capable0 = 2,
+    /// Congestion was encountered
+    CongestionExperienced = 3
+}
+
+impl Ecn {
+    /// Gets the ECN value from the low 2 bits of the specified value
+    pub const fn from_bits(value: u8) -> Ecn {
+        match value & 0b11 {
+            0 => Ecn::NotCapable,
+            1 => Ecn::Capable1,
+            2 => Ecn::Capable0,
+            _ => Ecn::CongestionExperienced
+        }
+    }
+}
+
+/// The 3-bit flags field of an IPv4 header
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Flags(u8);
+
+impl Flags {
+    const RESERVED_MASK: u8 = 0b100;
+    const DF_MASK: u8 = 0b010;
+    const MF_MASK: u8 = 0b001;
+
+    /// No flags set
+    pub const NONE: Flags = Flags(0);
+    /// The don't fragment flag
+    pub const DONT_FRAGMENT: Flags = Flags(Self::DF_MASK);
+    /// The more fragments flag
+    pub const MORE_FRAGMENTS: Flags = Flags(Self::MF_MASK);
+
+    /// Creates a new flags value using the specified flag bits
+    pub const fn new(reserved: bool, dont_fragment: bool, more_fragments: bool) -> Flags {
+        Flags(((reserved as u8) << 2) | ((dont_fragment as u8) << 1) | (more_fragments as u8))
+    }
+
+    /// Returns a new flags value from the low 3 bits of the specified value
+    pub const fn raw(value: u8) -> Flags {
+        Flags(value & 0b111)
+    }
+
+    /// Gets whether the reserved flag is set. This should always be unset.
+    pub const fn reserved(self) -> bool {
+        (self.0 & Self::RESERVED_MASK) != 0
+    }
+
+    /// Gets whether the packet may not be fragmented
+    pub const fn dont_fragment(self) -> bool {
+        (self.0 & Self::DF_MASK) != 0
+    }
+
+    /// Gets whether the packet is a fragment followed by more fragments
+    pub const fn more_fragments(self) -> bool {
+        (self.0 & Self::MF_MASK) != 0
+    }
+
+    /// Gets the raw underlying value of these flags
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+/// A single unparsed IPv4 option
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OptionValue<'a> {
+    /// The option type octet, containing the copied flag, class, and number of the option
+    pub kind: u8,
+    /// The data of the option, excluding the type and length octets
+    pub data: &'a [u8]
+}
+
+impl OptionValue<'_> {
+    /// The end of options list option type
+    pub const END: u8 = 0;
+    /// The no operation option type
+    pub const NOP: u8 = 1;
+
+    /// Gets whether the option should be copied into all fragments of the packet
+    pub fn copied(&self) -> bool {
+        (self.kind & 0x80) != 0
+    }
+    /// Gets the class of the option
+    pub fn class(&self) -> u8 {
+        (self.kind >> 5) & 0b11
+    }
+    /// Gets the number of the option
+    pub fn number(&self) -> u8 {
+        self.kind & 0x1F
+    }
+}
+
+/// The unparsed options of an IPv4 header
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Options<'a>(pub &'a [u8]);
+
+impl<'a> Options<'a> {
+    /// An empty options list
+    pub const EMPTY: Options<'static> = Options(&[]);
+
+    /// Returns an iterator over the options in the list.
+    /// The iterator ends after an end of options list option, or returns an error if an option is truncated.
+    pub fn iter(&self) -> OptionsIter<'a> {
+        OptionsIter(self.0)
+    }
+}
+
+impl Sealed for Options<'_> { }
+/// The size of the options including the padding used to align the header to a 32-bit boundary
+impl Size for Options<'_> {
+    fn size(&self) -> usize {
+        (self.0.len() + 3) & !3
+    }
+}
+/// Writes the options followed by zeroed padding to align the header to a 32-bit boundary
+impl Write for Options<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let size = self.size();
+        let output = output.get_mut(..size).ok_or(physical::Error)?;
+        let (options, padding) = output.split_at_mut(self.0.len());
+        options.copy_from_slice(self.0);
+        padding.iter_mut().for_each(|b| *b = 0);
+        Ok(size)
+    }
+}
+
+/// An iterator over the options in an IPv4 header
+#[derive(Clone, Debug)]
+pub struct OptionsIter<'a>(&'a [u8]);
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = Result<OptionValue<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let kind = *self.0.first()?;
+        match kind {
+            OptionValue::END => {
+                self.0 = &[];
+                None
+            },
+            OptionValue::NOP => {
+                self.0 = &self.0[1..];
+                Some(Ok(OptionValue { kind, data: &[] }))
+            },
+            _ => {
+                let option = self.0.get(1)
+                    .map(|&len| len as usize)
+                    .filter(|&len| len >= 2)
+                    .and_then(|len| self.0.get(..len));
+                match option {
+                    Some(option) => {
+                        self.0 = &self.0[option.len()..];
+                        Some(Ok(OptionValue { kind, data: &option[2..] }))
+                    },
+                    None => {
+                        self.0 = &[];
+                        Some(Err(ParseError::Truncated))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An IPv4 packet
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ipv4<'a, P> {
+    /// The differentiated services code point of the packet. Only the low 6 bits are written.
+    pub dscp: u8,
+    /// The explicit congestion notification of the packet
+    pub ecn: Ecn,
+    /// The length of the entire packet, including the header and payload
+    pub total_length: u16,
+    /// The identification value used to group fragments of a packet
+    pub identification: u16,
+    /// The fragmentation flags of the packet
+    pub flags: Flags,
+    /// The offset of this fragment in 8 octet blocks. Only the low 13 bits are written.
+    pub fragment_offset: u16,
+    /// The time to live of the packet
+    pub ttl: u8,
+    /// The protocol of the payload
+    pub protocol: Protocol,
+    /// The header checksum
+    pub checksum: u16,
+    /// The source address of the packet
+    pub source: Ipv4Addr,
+    /// The destination address of the packet
+    pub destination: Ipv4Addr,
+    /// The options of the packet header
+    pub options: Options<'a>,
+    payload: P
+}
+
+impl<'a, P> Ipv4<'a, P> {
+    /// The version of all IPv4 packets
+    pub const VERSION: u8 = 4;
+    /// The default time to live of new packets
+    pub const DEFAULT_TTL: u8 = 64;
+
+    /// Creates a new packet with no options and the specified addresses, protocol, and payload.
+    /// The total length is set to the length of the header and payload without any padding, and the checksum is zero.
+    ///
+    /// Returns an error if the size of the packet is too large for the total length field.
+    pub fn new(source: Ipv4Addr, destination: Ipv4Addr, protocol: Protocol, payload: P) -> Result<Self, physical::Error> where P: Size {
+        Self::new_with_options(source, destination, protocol, Options::EMPTY, payload)
+    }
+    /// Creates a new packet with the specified addresses, protocol, options, and payload.
+    /// The total length is set to the length of the header, options, and payload without any padding, and the checksum is zero.
+    ///
+    /// Returns an error if the options are longer than [`MAX_OPTIONS_LEN`](constant.MAX_OPTIONS_LEN.html)
+    /// or the size of the packet is too large for the total length field.
+    pub fn new_with_options(source: Ipv4Addr, destination: Ipv4Addr, protocol: Protocol, options: Options<'a>, payload: P) -> Result<Self, physical::Error>
+        where P: Size
+    {
+        if options.size() > MAX_OPTIONS_LEN {
+            return Err(physical::Error);
+        }
+        let total_length = u16::try_from(MIN_HEADER_LEN + options.size() + payload.unpadded_size()).map_err(|_| physical::Error)?;
+        Ok(Ipv4 {
+            dscp: 0,
+            ecn: Ecn::NotCapable,
+            total_length,
+            identification: 0,
+            flags: Flags::NONE,
+            fragment_offset: 0,
+            ttl: Self::DEFAULT_TTL,
+            protocol,
+            checksum: 0,
+            source,
+            destination,
+            options,
+            payload
+        })
+    }
+    /// Gets the length of the header in 32-bit words
+    pub fn ihl(&self) -> u8 {
+        (self.header_len() / 4) as u8
+    }
+    /// Gets the length of the header in octets
+    pub fn header_len(&self) -> usize {
+        MIN_HEADER_LEN + self.options.size()
+    }
+    /// Computes the header checksum from the other header fields, ignoring the current value of the [`checksum`](#structfield.checksum) field
+    pub fn compute_checksum(&self) -> u16 {
+        let mut checksum = Checksum::new();
+        checksum
+            .add_u16(u16::from_be_bytes(self.version_and_tos()))
+            .add_u16(self.total_length)
+            .add_u16(self.identification)
+            .add_u16(self.flags_and_offset())
+            .add_u16(u16::from_be_bytes([self.ttl, self.protocol.0]))
+            .add_bytes(&self.source.octets())
+            .add_bytes(&self.destination.octets())
+            .add_bytes(self.options.0);
+        checksum.finish()
+    }
+    /// Returns whether the [`checksum`](#structfield.checksum) field matches the checksum of the other header fields
+    pub fn verify_checksum(&self) -> bool {
+        self.compute_checksum() == self.checksum
+    }
+    fn version_and_tos(&self) -> [u8; 2] {
+        [(Self::VERSION << 4) | self.ihl(), (self.dscp << 2) | self.ecn as u8]
+    }
+    fn flags_and_offset(&self) -> u16 {
+        ((self.flags.get() as u16) << 13) | (self.fragment_offset & 0x1FFF)
+    }
+    /// Gets the pseudo-header of this packet, used to compute the checksums of upper-layer protocols
+    pub fn pseudo_header(&self) -> PseudoHeader {
+        PseudoHeader::V4 { source: self.source, destination: self.destination }
+    }
+    /// Gets a reference to the payload of this packet
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Ipv4<'a, Q> {
+        let Ipv4 {
+            dscp, ecn, total_length, identification, flags, fragment_offset, ttl, protocol, checksum, source, destination, options,
+            payload
+        } = self;
+
+        let payload = f(payload);
+
+        Ipv4 { dscp, ecn, total_length, identification, flags, fragment_offset, ttl, protocol, checksum, source, destination, options, payload }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Ipv4<'a, Q>, E> {
+        let Ipv4 {
+            dscp, ecn, total_length, identification, flags, fragment_offset, ttl, protocol, checksum, source, destination, options,
+            payload
+        } = self;
+
+        let payload = f(payload)?;
+
+        Ok(Ipv4 { dscp, ecn, total_length, identification, flags, fragment_offset, ttl, protocol, checksum, source, destination, options, payload })
+    }
+}
+
+impl<'a> Partial<'a> {
+    /// Parses the header of an IPv4 packet from a slice of bytes.
+    /// The payload of the packet is the data following the header up to the total length of the packet,
+    /// which may be shorter than the total length specifies.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < MIN_HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let version = bytes[0] >> 4;
+        if version != Self::VERSION {
+            return Err(ParseError::InvalidVersion(version));
+        }
+        let ihl = bytes[0] & 0x0F;
+        let header_len = ihl as usize * 4;
+        if header_len < MIN_HEADER_LEN {
+            return Err(ParseError::InvalidHeaderLength(ihl));
+        }
+        let total_length = u16::from_be_bytes([bytes[2], bytes[3]]);
+        if (total_length as usize) < header_len {
+            return Err(ParseError::InvalidTotalLength(total_length));
+        }
+        if bytes.len() < header_len {
+            return Err(ParseError::Truncated);
+        }
+
+        let flags_and_offset = u16::from_be_bytes([bytes[6], bytes[7]]);
+        let address = |start: usize| Ipv4Addr::from(<[u8; 4]>::try_from(&bytes[start..start + 4]).unwrap());
+        let end = bytes.len().min(total_length as usize);
+
+        Ok(Ipv4 {
+            dscp: bytes[1] >> 2,
+            ecn: Ecn::from_bits(bytes[1]),
+            total_length,
+            identification: u16::from_be_bytes([bytes[4], bytes[5]]),
+            flags: Flags::raw((flags_and_offset >> 13) as u8),
+            fragment_offset: flags_and_offset & 0x1FFF,
+            ttl: bytes[8],
+            protocol: Protocol(bytes[9]),
+            checksum: u16::from_be_bytes([bytes[10], bytes[11]]),
+            source: address(12),
+            destination: address(16),
+            options: Options(&bytes.0[MIN_HEADER_LEN..header_len]),
+            payload: payload::Unknown(&bytes.0[header_len..end])
+        })
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses an IPv4 packet from a slice of bytes.
+    /// The payload of the packet is the data following the header up to the total length of the packet,
+    /// and any data after the total length is padding.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        let packet = Partial::parse(bytes)?;
+        let total_length = packet.total_length as usize;
+        if bytes.len() < total_length {
+            return Err(ParseError::Truncated);
+        }
+
+        let header_len = packet.header_len();
+        Ok(packet.map_payload(|_| bytes.consume(header_len).as_padded_any(total_length - header_len)))
+    }
+}
+
+impl<P> Sealed for Ipv4<'_, P> { }
+impl<P: Size> Size for Ipv4<'_, P> {
+    fn size(&self) -> usize {
+        self.header_len() + self.payload.size()
+    }
+}
+/// Writes the packet using the total length and checksum fields as-is
+impl<P: Write> Write for Ipv4<'_, P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        if self.header_len() > MAX_HEADER_LEN {
+            return Err(physical::Error);
+        }
+
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.version_and_tos()[..])?;
+        cursor.write(&self.total_length)?;
+        cursor.write(&self.identification)?;
+        cursor.write(&self.flags_and_offset())?;
+        cursor.write(&self.ttl)?;
+        cursor.write(&self.protocol)?;
+        cursor.write(&self.checksum)?;
+        cursor.write(&self.source)?;
+        cursor.write(&self.destination)?;
+        cursor.write(&self.options)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::{EthernetBase, EtherType, PayloadError};
+
+    const PACKET: [u8; 30] = [
+        0x45, 0x00, 0x00, 0x1C,
+        0x00, 0x01, 0x40, 0x00,
+        0x40, 0x11, 0xB9, 0x7C,
+        0xC0, 0xA8, 0x00, 0x01,
+        0xC0, 0xA8, 0x00, 0x02,
+        0x30, 0x39, 0x00, 0x35, 0x00, 0x08, 0x00, 0x00,
+        0xFF, 0xFF
+    ];
+
+    #[test]
+    fn parse() {
+        let packet = Parsed::parse(&PACKET[..]).unwrap();
+        assert_eq!(packet.ihl(), 5);
+        assert_eq!(packet.total_length, 28);
+        assert_eq!(packet.identification, 1);
+        assert!(packet.flags.dont_fragment());
+        assert!(!packet.flags.more_fragments());
+        assert_eq!(packet.fragment_offset, 0);
+        assert_eq!(packet.ttl, 64);
+        assert_eq!(packet.protocol, Protocol::UDP);
+        assert_eq!(packet.checksum, 0xB97C);
+        assert!(packet.verify_checksum());
+        assert_eq!(packet.source, Ipv4Addr::new(192, 168, 0, 1));
+        assert_eq!(packet.destination, Ipv4Addr::new(192, 168, 0, 2));
+        assert_eq!(packet.payload().payload.0, &PACKET[20..28]);
+        assert_eq!(packet.payload().padding, &[0xFF, 0xFF]);
+
+        let mut buf = [0; 30];
+        assert_eq!(packet.size(), 30);
+        assert_eq!(packet.write(&mut buf), Ok(30));
+        assert_eq!(buf, PACKET);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Parsed::parse(&PACKET[..19]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&PACKET[..27]), Err(ParseError::Truncated));
+
+        let mut bad = PACKET;
+        bad[0] = 0x65;
+        assert_eq!(Parsed::parse(&bad[..]), Err(ParseError::InvalidVersion(6)));
+        bad[0] = 0x44;
+        assert_eq!(Parsed::parse(&bad[..]), Err(ParseError::InvalidHeaderLength(4)));
+        bad[0] = 0x48;
+        assert_eq!(Parsed::parse(&bad[..]), Err(ParseError::InvalidTotalLength(28)));
+    }
+
+    #[test]
+    fn options() {
+        let options = Options(&[OptionValue::NOP, 0x94, 0x04, 0x00, 0x00, OptionValue::END, 0xAA]);
+        let mut iter = options.iter();
+        assert_eq!(iter.next(), Some(Ok(OptionValue { kind: OptionValue::NOP, data: &[] })));
+        let router_alert = iter.next().unwrap().unwrap();
+        assert!(router_alert.copied());
+        assert_eq!(router_alert.number(), 20);
+        assert_eq!(router_alert.data, &[0, 0]);
+        assert_eq!(iter.next(), None);
+        assert_eq!(options.size(), 8);
+
+        let truncated = Options(&[0x94, 0x04, 0x00]);
+        assert_eq!(truncated.iter().next(), Some(Err(ParseError::Truncated)));
+    }
+
+    #[test]
+    fn new_with_options() {
+        let options = Options(&[0x94, 0x04, 0x00, 0x00, OptionValue::NOP]);
+        let mut packet = Ipv4::new_with_options(Ipv4Addr::LOCALHOST, Ipv4Addr::BROADCAST, Protocol::ICMP, options, payload::Any(&[1, 2])).unwrap();
+        assert_eq!(packet.total_length, 30);
+        packet.checksum = packet.compute_checksum();
+        assert_eq!(packet.header_len(), 28);
+        assert_eq!(packet.size(), 30);
+
+        let mut buf = [0xAA; 30];
+        assert_eq!(packet.write(&mut buf), Ok(30));
+        assert_eq!(buf[0], 0x47);
+        assert!(crate::checksum::verify(&buf[..28]));
+        assert_eq!(&buf[20..], &[0x94, 0x04, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 1, 2]);
+
+        let padded = payload::Padded { payload: payload::Any(&[1, 2]), padding: &[0; 4][..] };
+        let packet = Ipv4::new(Ipv4Addr::LOCALHOST, Ipv4Addr::BROADCAST, Protocol::ICMP, padded).unwrap();
+        assert_eq!(packet.total_length, 22);
+        assert_eq!(packet.size(), 26);
+
+        let long = Options(&[OptionValue::NOP; MAX_OPTIONS_LEN + 1]);
+        assert_eq!(Ipv4::new_with_options(Ipv4Addr::LOCALHOST, Ipv4Addr::BROADCAST, Protocol::ICMP, long, payload::Empty), Err(physical::Error));
+    }
+
+    #[test]
+    fn from_ethernet2() {
+        let mut frame = [0; 44];
+        frame[12..14].copy_from_slice(&[0x08, 0x00]);
+        frame[14..].copy_from_slice(&PACKET);
+
+        let ethernet = EthernetBase::parse(&frame[..]).unwrap().try_parse_vlan().unwrap().unwrap_ethernet2();
+        assert_eq!(ethernet.etype, EtherType::IPV4);
+        let packet = ethernet.try_parse_ipv4().unwrap();
+        assert_eq!(packet.payload().protocol, Protocol::UDP);
+
+        frame[12] = 0x86;
+        let ethernet = EthernetBase::parse(&frame[..]).unwrap().try_parse_vlan().unwrap().unwrap_ethernet2();
+        assert_eq!(ethernet.try_parse_ipv4().unwrap_err(), PayloadError::UnexpectedEtherType(EtherType(0x8600)));
+    }
+}
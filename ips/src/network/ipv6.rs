@@ -0,0 +1,560 @@
+//! Types for reading and writing IPv6 packets and extension headers
+
+use crate::checksum::PseudoHeader;
+use crate::internal::Sealed;
+use crate::network::Protocol;
+use crate::network::ipv4::Ecn;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::Ipv6Addr;
+
+/// The length of the fixed IPv6 header
+pub const HEADER_LEN: usize = 40;
+
+/// An IPv6 packet parsed from a slice of bytes, where any data after the payload length of the packet is padding
+pub type Parsed<'a> = Ipv6<payload::Padded<&'a [u8], payload::Any<'a>>>;
+/// An IPv6 packet parsed from a slice of bytes that may end before the end of the payload,
+/// such as the original packet quoted in an ICMPv6 error message
+pub type Partial<'a> = Ipv6<payload::Unknown<'a>>;
+
+/// An error that occurs while parsing an IPv6 packet or extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the IPv6 header, an extension header, or the end of the packet specified by the payload length
+    Truncated,
+    /// The version field of the header was not 6
+    InvalidVersion(u8)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad IPv6 packet; ran out of data"),
+            ParseError::InvalidVersion(v) => write!(f, "bad IPv6 packet; expected version 6, found {}", v),
+        }
+    }
+}
+
+/// An IPv6 packet
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ipv6<P> {
+    /// The traffic class of the packet, containing the differentiated services code point and explicit congestion notification
+    pub traffic_class: u8,
+    /// The flow label of the packet. Only the low 20 bits are written.
+    pub flow_label: u32,
+    /// The length of the payload, including any extension headers
+    pub payload_length: u16,
+    /// The protocol of the first extension header or the payload
+    pub next_header: Protocol,
+    /// The hop limit of the packet
+    pub hop_limit: u8,
+    /// The source address of the packet
+    pub source: Ipv6Addr,
+    /// The destination address of the packet
+    pub destination: Ipv6Addr,
+    payload: P
+}
+
+impl<P> Ipv6<P> {
+    /// The version of all IPv6 packets
+    pub const VERSION: u8 = 6;
+    /// The default hop limit of new packets
+    pub const DEFAULT_HOP_LIMIT: u8 = 64;
+
+    /// Creates a new packet with the specified addresses, next header, and payload.
+    /// The payload length is set to the size of the payload without any padding.
+    ///
+    /// Returns an error if the size of the payload is too large for the payload length field.
+    pub fn new(source: Ipv6Addr, destination: Ipv6Addr, next_header: Protocol, payload: P) -> Result<Self, physical::Error> where P: Size {
+        let payload_length = u16::try_from(payload.unpadded_size()).map_err(|_| physical::Error)?;
+        Ok(Ipv6 {
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length,
+            next_header,
+            hop_limit: Self::DEFAULT_HOP_LIMIT,
+            source,
+            destination,
+            payload
+        })
+    }
+    /// Gets the differentiated services code point from the traffic class
+    pub fn dscp(&self) -> u8 {
+        self.traffic_class >> 2
+    }
+    /// Gets the explicit congestion notification from the traffic class
+    pub fn ecn(&self) -> Ecn {
+        Ecn::from_bits(self.traffic_class)
+    }
+    /// Gets the pseudo-header of this packet, used to compute the checksums of upper-layer protocols
+    pub fn pseudo_header(&self) -> PseudoHeader {
+        PseudoHeader::V6 { source: self.source, destination: self.destination }
+    }
+    /// Gets a reference to the payload of this packet
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Ipv6<Q> {
+        let Ipv6 { traffic_class, flow_label, payload_length, next_header, hop_limit, source, destination, payload } = self;
+
+        let payload = f(payload);
+
+        Ipv6 { traffic_class, flow_label, payload_length, next_header, hop_limit, source, destination, payload }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Ipv6<Q>, E> {
+        let Ipv6 { traffic_class, flow_label, payload_length, next_header, hop_limit, source, destination, payload } = self;
+
+        let payload = f(payload)?;
+
+        Ok(Ipv6 { traffic_class, flow_label, payload_length, next_header, hop_limit, source, destination, payload })
+    }
+}
+
+impl<'a> Partial<'a> {
+    /// Parses the header of an IPv6 packet from a slice of bytes.
+    /// The payload of the packet is the data following the header up to the payload length of the packet,
+    /// which may be shorter than the payload length specifies.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let first = u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[0..4]).unwrap());
+        let version = (first >> 28) as u8;
+        if version != Self::VERSION {
+            return Err(ParseError::InvalidVersion(version));
+        }
+        let payload_length = u16::from_be_bytes([bytes[4], bytes[5]]);
+        let end = bytes.len().min(HEADER_LEN + payload_length as usize);
+
+        let address = |start: usize| Ipv6Addr::from(<[u8; 16]>::try_from(&bytes[start..start + 16]).unwrap());
+
+        Ok(Ipv6 {
+            traffic_class: (first >> 20) as u8,
+            flow_label: first & 0x000F_FFFF,
+            payload_length,
+            next_header: Protocol(bytes[6]),
+            hop_limit: bytes[7],
+            source: address(8),
+            destination: address(24),
+            payload: payload::Unknown(&bytes.0[HEADER_LEN..end])
+        })
+    }
+
+    /// Returns an iterator over the extension headers in the payload of this packet
+    pub fn extensions(&self) -> ExtensionHeaders<'a> {
+        ExtensionHeaders::new(self.next_header, self.payload.0)
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses an IPv6 packet from a slice of bytes.
+    /// The payload of the packet is the data following the header up to the payload length of the packet,
+    /// and any data after the payload length is padding.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        let packet = Partial::parse(bytes)?;
+        let payload_length = packet.payload_length as usize;
+        if bytes.len() - HEADER_LEN < payload_length {
+            return Err(ParseError::Truncated);
+        }
+
+        Ok(packet.map_payload(|_| bytes.consume(HEADER_LEN).as_padded_any(payload_length)))
+    }
+
+    /// Returns an iterator over the extension headers in the payload of this packet
+    pub fn extensions(&self) -> ExtensionHeaders<'a> {
+        ExtensionHeaders::new(self.next_header, self.payload.payload.0)
+    }
+}
+
+impl<P> Sealed for Ipv6<P> { }
+impl<P: Size> Size for Ipv6<P> {
+    fn size(&self) -> usize {
+        HEADER_LEN + self.payload.size()
+    }
+}
+/// Writes the packet using the payload length field as-is
+impl<P: Write> Write for Ipv6<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let first = ((Self::VERSION as u32) << 28) | ((self.traffic_class as u32) << 20) | (self.flow_label & 0x000F_FFFF);
+
+        let mut cursor = Cursor::new(output);
+        cursor.write(&first)?;
+        cursor.write(&self.payload_length)?;
+        cursor.write(&self.next_header)?;
+        cursor.write(&self.hop_limit)?;
+        cursor.write(&self.source)?;
+        cursor.write(&self.destination)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+/// A single unparsed option in a Hop-by-Hop or Destination Options extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OptionValue<'a> {
+    /// The option type, containing the unrecognized action, the change flag, and the option number
+    pub kind: u8,
+    /// The data of the option, excluding the type and length octets
+    pub data: &'a [u8]
+}
+
+impl OptionValue<'_> {
+    /// The single octet padding option type
+    pub const PAD1: u8 = 0;
+    /// The multiple octet padding option type
+    pub const PADN: u8 = 1;
+    /// The router alert option type
+    pub const ROUTER_ALERT: u8 = 5;
+    /// The jumbo payload option type
+    pub const JUMBO_PAYLOAD: u8 = 0xC2;
+
+    /// Gets the action that must be taken if the option isn't recognized
+    pub fn action(&self) -> u8 {
+        self.kind >> 6
+    }
+    /// Gets whether the option data may change en route to the destination
+    pub fn may_change(&self) -> bool {
+        (self.kind & 0x20) != 0
+    }
+}
+
+/// The unparsed options of a Hop-by-Hop or Destination Options extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Options<'a>(pub &'a [u8]);
+
+impl<'a> Options<'a> {
+    /// Returns an iterator over the options, including padding options.
+    /// The iterator returns an error if an option is truncated.
+    pub fn iter(&self) -> OptionsIter<'a> {
+        OptionsIter(self.0)
+    }
+}
+
+/// An iterator over the options in a Hop-by-Hop or Destination Options extension header
+#[derive(Clone, Debug)]
+pub struct OptionsIter<'a>(&'a [u8]);
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = Result<OptionValue<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let kind = *self.0.first()?;
+        if kind == OptionValue::PAD1 {
+            self.0 = &self.0[1..];
+            return Some(Ok(OptionValue { kind, data: &[] }));
+        }
+
+        match self.0.get(1).and_then(|&len| self.0.get(2..2 + len as usize)) {
+            Some(data) => {
+                self.0 = &self.0[2 + data.len()..];
+                Some(Ok(OptionValue { kind, data }))
+            },
+            None => {
+                self.0 = &[];
+                Some(Err(ParseError::Truncated))
+            }
+        }
+    }
+}
+
+/// A Routing extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Routing<'a> {
+    /// The routing header variant
+    pub routing_type: u8,
+    /// The number of route segments remaining before the destination is reached
+    pub segments_left: u8,
+    /// The type-specific data of the header
+    pub data: &'a [u8]
+}
+
+/// A Fragment extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Fragment {
+    /// The offset of this fragment in 8 octet blocks
+    pub fragment_offset: u16,
+    /// Whether this fragment is followed by more fragments
+    pub more_fragments: bool,
+    /// The identification value used to group fragments of a packet
+    pub identification: u32
+}
+
+/// An Authentication Header extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Authentication<'a> {
+    /// The security parameters index
+    pub spi: u32,
+    /// The sequence number
+    pub sequence: u32,
+    /// The integrity check value
+    pub icv: &'a [u8]
+}
+
+/// The unencrypted start of an Encapsulating Security Payload.
+/// The rest of the extension header chain is encrypted, so the chain ends after this header.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Esp<'a> {
+    /// The security parameters index
+    pub spi: u32,
+    /// The sequence number
+    pub sequence: u32,
+    /// The encrypted payload data, padding, trailer, and integrity check value
+    pub data: payload::Unknown<'a>
+}
+
+/// An IPv6 extension header
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ExtensionHeader<'a> {
+    /// Options examined by every node along the path of the packet
+    HopByHop(Options<'a>),
+    /// A list of intermediate nodes the packet should visit
+    Routing(Routing<'a>),
+    /// A fragment of a packet
+    Fragment(Fragment),
+    /// Options examined by the destination or the next node in the routing header
+    DestinationOptions(Options<'a>),
+    /// An IPsec authentication header
+    Authentication(Authentication<'a>),
+    /// An IPsec encapsulating security payload, which ends the extension header chain
+    Esp(Esp<'a>)
+}
+
+impl ExtensionHeader<'_> {
+    /// Gets the protocol number of this extension header
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            ExtensionHeader::HopByHop(_) => Protocol::HOPOPT,
+            ExtensionHeader::Routing(_) => Protocol::IPV6_ROUTE,
+            ExtensionHeader::Fragment(_) => Protocol::IPV6_FRAG,
+            ExtensionHeader::DestinationOptions(_) => Protocol::IPV6_OPTS,
+            ExtensionHeader::Authentication(_) => Protocol::AH,
+            ExtensionHeader::Esp(_) => Protocol::ESP,
+        }
+    }
+}
+
+/// The size of the extension header, including the next header and length fields
+impl Size for ExtensionHeader<'_> {
+    fn size(&self) -> usize {
+        match self {
+            ExtensionHeader::HopByHop(Options(data)) | ExtensionHeader::DestinationOptions(Options(data)) => 2 + data.len(),
+            ExtensionHeader::Routing(Routing { data, .. }) => 4 + data.len(),
+            ExtensionHeader::Fragment(_) => 8,
+            ExtensionHeader::Authentication(Authentication { icv, .. }) => 12 + icv.len(),
+            ExtensionHeader::Esp(Esp { data, .. }) => 8 + data.len(),
+        }
+    }
+}
+
+/// An iterator over a chain of IPv6 extension headers.
+///
+/// The iterator ends when it reaches a protocol that isn't an extension header.
+/// The protocol and payload following the chain can then be retrieved with [`upper_layer`](#method.upper_layer).
+#[derive(Clone, Debug)]
+pub struct ExtensionHeaders<'a> {
+    next: Protocol,
+    data: payload::Unknown<'a>
+}
+
+impl<'a> ExtensionHeaders<'a> {
+    /// Creates a new iterator over the extension headers starting with the specified protocol
+    pub fn new<P: Into<payload::Unknown<'a>>>(next_header: Protocol, payload: P) -> Self {
+        ExtensionHeaders { next: next_header, data: payload.into() }
+    }
+
+    /// Gets the protocol of the next header in the chain
+    pub fn next_header(&self) -> Protocol {
+        self.next
+    }
+
+    /// Consumes the rest of the extension header chain, returning the upper-layer protocol and its payload
+    pub fn upper_layer(mut self) -> Result<(Protocol, payload::Unknown<'a>), ParseError> {
+        for header in self.by_ref() {
+            header?;
+        }
+        Ok((self.next, self.data))
+    }
+
+    fn parse_next(&mut self) -> Result<ExtensionHeader<'a>, ParseError> {
+        let data = self.data.0;
+        let word = |start: usize| u32::from_be_bytes(<[u8; 4]>::try_from(&data[start..start + 4]).unwrap());
+
+        let (header, next, len) = match self.next {
+            Protocol::ESP => {
+                if data.len() < 8 {
+                    return Err(ParseError::Truncated);
+                }
+                let esp = Esp { spi: word(0), sequence: word(4), data: payload::Unknown(&data[8..]) };
+                (ExtensionHeader::Esp(esp), Protocol::IPV6_NONXT, data.len())
+            },
+            Protocol::AH => {
+                let len = data.get(1).map(|&len| (len as usize + 2) * 4).filter(|&len| len >= 12 && len <= data.len()).ok_or(ParseError::Truncated)?;
+                let ah = Authentication { spi: word(4), sequence: word(8), icv: &data[12..len] };
+                (ExtensionHeader::Authentication(ah), Protocol(data[0]), len)
+            },
+            Protocol::IPV6_FRAG => {
+                if data.len() < 8 {
+                    return Err(ParseError::Truncated);
+                }
+                let offset_and_flags = u16::from_be_bytes([data[2], data[3]]);
+                let fragment = Fragment {
+                    fragment_offset: offset_and_flags >> 3,
+                    more_fragments: (offset_and_flags & 1) != 0,
+                    identification: word(4)
+                };
+                (ExtensionHeader::Fragment(fragment), Protocol(data[0]), 8)
+            },
+            protocol @ Protocol::HOPOPT | protocol @ Protocol::IPV6_ROUTE | protocol @ Protocol::IPV6_OPTS => {
+                let len = data.get(1).map(|&len| (len as usize + 1) * 8).filter(|&len| len <= data.len()).ok_or(ParseError::Truncated)?;
+                let header = match protocol {
+                    Protocol::HOPOPT => ExtensionHeader::HopByHop(Options(&data[2..len])),
+                    Protocol::IPV6_OPTS => ExtensionHeader::DestinationOptions(Options(&data[2..len])),
+                    _ => ExtensionHeader::Routing(Routing { routing_type: data[2], segments_left: data[3], data: &data[4..len] }),
+                };
+                (header, Protocol(data[0]), len)
+            },
+            _ => unreachable!()
+        };
+
+        self.next = next;
+        self.data = self.data.consume(len);
+        Ok(header)
+    }
+}
+
+impl<'a> Iterator for ExtensionHeaders<'a> {
+    type Item = Result<ExtensionHeader<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next {
+            Protocol::HOPOPT | Protocol::IPV6_ROUTE | Protocol::IPV6_FRAG | Protocol::IPV6_OPTS | Protocol::AH | Protocol::ESP => {
+                let header = self.parse_next();
+                if header.is_err() {
+                    // stop at the malformed header; the chain can't be followed past it
+                    self.next = Protocol::IPV6_NONXT;
+                }
+                Some(header)
+            },
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::EthernetBase;
+
+    const PACKET: [u8; 66] = [
+        0x6B, 0x81, 0x23, 0x45,
+        0x00, 0x18, 0x00, 0x40,
+        0xFE, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01,
+        0xFE, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x02,
+        // hop-by-hop options with a PadN option
+        0x2C, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00,
+        // fragment
+        0x11, 0x00, 0x00, 0x09, 0xDE, 0xAD, 0xBE, 0xEF,
+        // udp
+        0x30, 0x39, 0x00, 0x35, 0x00, 0x08, 0x00, 0x00,
+        // padding
+        0xFF, 0xFF
+    ];
+
+    #[test]
+    fn parse() {
+        let packet = Parsed::parse(&PACKET[..]).unwrap();
+        assert_eq!(packet.traffic_class, 0xB8);
+        assert_eq!(packet.dscp(), 46);
+        assert_eq!(packet.flow_label, 0x12345);
+        assert_eq!(packet.payload_length, 24);
+        assert_eq!(packet.next_header, Protocol::HOPOPT);
+        assert_eq!(packet.hop_limit, 64);
+        assert_eq!(packet.source, Ipv6Addr::new(0xFE80, 0, 0, 0, 0, 0, 0, 1));
+        assert_eq!(packet.destination, Ipv6Addr::new(0xFE80, 0, 0, 0, 0, 0, 0, 2));
+        assert_eq!(packet.payload().padding, &[0xFF, 0xFF]);
+
+        let mut buf = [0; 66];
+        assert_eq!(packet.size(), 66);
+        assert_eq!(packet.write(&mut buf), Ok(66));
+        assert_eq!(buf, PACKET);
+    }
+
+    #[test]
+    fn new() {
+        let padded = payload::Padded { payload: payload::Any(&[1, 2, 3, 4][..]), padding: &[0; 2][..] };
+        let packet = Ipv6::new(Ipv6Addr::LOCALHOST, Ipv6Addr::LOCALHOST, Protocol::UDP, padded).unwrap();
+        assert_eq!(packet.payload_length, 4);
+        assert_eq!(packet.size(), 46);
+
+        let data = [0; 65536];
+        assert_eq!(Ipv6::new(Ipv6Addr::LOCALHOST, Ipv6Addr::LOCALHOST, Protocol::UDP, payload::Any(&data[..])), Err(physical::Error));
+    }
+
+    #[test]
+    fn extensions() {
+        let packet = Parsed::parse(&PACKET[..]).unwrap();
+        let mut extensions = packet.extensions();
+
+        let hop_by_hop = extensions.next().unwrap().unwrap();
+        assert_eq!(hop_by_hop.protocol(), Protocol::HOPOPT);
+        assert_eq!(hop_by_hop.size(), 8);
+        match hop_by_hop {
+            ExtensionHeader::HopByHop(options) => {
+                let mut options = options.iter();
+                assert_eq!(options.next(), Some(Ok(OptionValue { kind: OptionValue::PADN, data: &[0, 0, 0, 0] })));
+                assert_eq!(options.next(), None);
+            },
+            other => panic!("expected hop-by-hop header, found {:?}", other)
+        }
+
+        let fragment = Fragment { fragment_offset: 1, more_fragments: true, identification: 0xDEADBEEF };
+        assert_eq!(extensions.next(), Some(Ok(ExtensionHeader::Fragment(fragment))));
+        assert_eq!(extensions.next_header(), Protocol::UDP);
+        assert_eq!(extensions.clone().next(), None);
+
+        let (protocol, payload) = extensions.upper_layer().unwrap();
+        assert_eq!(protocol, Protocol::UDP);
+        assert_eq!(payload.0, &PACKET[56..64]);
+    }
+
+    #[test]
+    fn extension_errors() {
+        let truncated = ExtensionHeaders::new(Protocol::HOPOPT, &PACKET[40..46]);
+        assert_eq!(truncated.upper_layer(), Err(ParseError::Truncated));
+
+        let mut esp = ExtensionHeaders::new(Protocol::ESP, &[0, 0, 1, 0, 0, 0, 0, 7, 0xAA, 0xBB][..]);
+        let expected = Esp { spi: 256, sequence: 7, data: payload::Unknown(&[0xAA, 0xBB]) };
+        assert_eq!(esp.next(), Some(Ok(ExtensionHeader::Esp(expected))));
+        assert_eq!(esp.upper_layer(), Ok((Protocol::IPV6_NONXT, payload::Unknown(&[]))));
+
+        let ah = [0x3B, 0x04, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+        let mut ah = ExtensionHeaders::new(Protocol::AH, &ah[..]);
+        let expected = Authentication { spi: 1, sequence: 2, icv: &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12] };
+        assert_eq!(ah.next(), Some(Ok(ExtensionHeader::Authentication(expected))));
+        assert_eq!(ah.next_header(), Protocol::IPV6_NONXT);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Parsed::parse(&PACKET[..39]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&PACKET[..63]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&PACKET[1..]), Err(ParseError::InvalidVersion(8)));
+    }
+
+    #[test]
+    fn from_ethernet2() {
+        let mut frame = [0; 80];
+        frame[12..14].copy_from_slice(&[0x86, 0xDD]);
+        frame[14..].copy_from_slice(&PACKET);
+
+        let ethernet = EthernetBase::parse(&frame[..]).unwrap().try_parse_vlan().unwrap().unwrap_ethernet2();
+        let packet = ethernet.try_parse_ipv6().unwrap();
+        assert_eq!(packet.payload().hop_limit, 64);
+    }
+}
@@ -0,0 +1,63 @@
+//! A module containing network layer types for the internet protocol suite
+
+pub mod icmp;
+pub mod icmpv6;
+pub mod ipv4;
+pub mod ipv6;
+
+use crate::internal::Sealed;
+use crate::physical::{self, Read, Size, Write};
+
+/// An IP protocol number used to identify the protocol of an IPv4 payload or the next header of an IPv6 packet
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Protocol(pub u8);
+
+impl Protocol {
+    /// The protocol number of the IPv6 Hop-by-Hop Options extension header
+    pub const HOPOPT: Protocol = Protocol(0);
+    /// The protocol number used for Internet Control Message Protocol payloads
+    pub const ICMP: Protocol = Protocol(1);
+    /// The protocol number used for Internet Group Management Protocol payloads
+    pub const IGMP: Protocol = Protocol(2);
+    /// The protocol number used for IPv4 encapsulated payloads
+    pub const IPV4: Protocol = Protocol(4);
+    /// The protocol number used for Transmission Control Protocol payloads
+    pub const TCP: Protocol = Protocol(6);
+    /// The protocol number used for User Datagram Protocol payloads
+    pub const UDP: Protocol = Protocol(17);
+    /// The protocol number used for IPv6 encapsulated payloads
+    pub const IPV6: Protocol = Protocol(41);
+    /// The protocol number of the IPv6 Routing extension header
+    pub const IPV6_ROUTE: Protocol = Protocol(43);
+    /// The protocol number of the IPv6 Fragment extension header
+    pub const IPV6_FRAG: Protocol = Protocol(44);
+    /// The protocol number used for Generic Routing Encapsulation payloads
+    pub const GRE: Protocol = Protocol(47);
+    /// The protocol number used for Encapsulating Security Payloads
+    pub const ESP: Protocol = Protocol(50);
+    /// The protocol number used for Authentication Header payloads
+    pub const AH: Protocol = Protocol(51);
+    /// The protocol number used for ICMP for IPv6 payloads
+    pub const ICMPV6: Protocol = Protocol(58);
+    /// The protocol number used to signal that an IPv6 packet has no next header
+    pub const IPV6_NONXT: Protocol = Protocol(59);
+    /// The protocol number of the IPv6 Destination Options extension header
+    pub const IPV6_OPTS: Protocol = Protocol(60);
+    /// The protocol number used for Stream Control Transmission Protocol payloads
+    pub const SCTP: Protocol = Protocol(132);
+}
+
+impl Sealed for Protocol { }
+impl Size for Protocol {
+    fn size(&self) -> usize { 1 }
+}
+impl Write for Protocol {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+impl Read<'_> for Protocol {
+    fn read(input: &[u8]) -> Result<Self, physical::Error> {
+        u8::read(input).map(Protocol)
+    }
+}
@@ -1,6 +1,7 @@
 //! Types for managing and representing byte payloads
 
-use crate::physical::Size;
+use crate::internal::Sealed;
+use crate::physical::{self, Cursor, Read, Size, Write};
 
 use core::ops::Deref;
 
@@ -8,9 +9,20 @@ use core::ops::Deref;
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
 pub struct Empty;
 
+impl Sealed for Empty { }
 impl Size for Empty {
     fn size(&self) -> usize { 0 }
 }
+impl Write for Empty {
+    fn write(&self, _: &mut [u8]) -> Result<usize, physical::Error> {
+        Ok(0)
+    }
+}
+impl Read<'_> for Empty {
+    fn read(_: &[u8]) -> Result<Self, physical::Error> {
+        Ok(Empty)
+    }
+}
 
 /// A structure used to signal that it's undetermined where the start or end of the payload is. This may have padded data, unparsed header data, or any other data.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -26,8 +38,13 @@ impl<'a> Unknown<'a> {
         }
     }
 
+    /// Advances the buffer by the specified length without performing length checks.
+    /// 
+    /// # Safety
+    /// 
+    /// This will cause undefined behavior if `amnt > len`
     pub unsafe fn consume_unchecked(self, amnt: usize) -> Self {
-        Unknown(self.0.get_unchecked(..amnt))
+        Unknown(self.0.get_unchecked(amnt..))
     }
 
     /// Converts this [`Unknown`] payload into a [`Padded`] payload with [`Any`] unparsed value where the payload is of the specified length, or
@@ -95,8 +112,24 @@ impl<'a> Deref for Unknown<'a> {
         self.0
     }
 }
+impl Sealed for Unknown<'_> { }
+impl Size for Unknown<'_> {
+    fn size(&self) -> usize { self.0.len() }
+}
+impl Write for Unknown<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+/// Reads the entire input
+impl<'a> Read<'a> for Unknown<'a> {
+    fn read(input: &'a [u8]) -> Result<Self, physical::Error> {
+        Ok(Unknown(input))
+    }
+}
 
 /// A structure used to contain an unparsed payload value
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Any<'a>(pub &'a [u8]);
 
 impl<'a> From<Unknown<'a>> for Any<'a> {
@@ -104,11 +137,24 @@ impl<'a> From<Unknown<'a>> for Any<'a> {
         Any(u.0)
     }
 }
+impl Sealed for Any<'_> { }
 impl Size for Any<'_> {
     fn size(&self) -> usize { self.0.len() }
 }
+impl Write for Any<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        self.0.write(output)
+    }
+}
+/// Reads the entire input
+impl<'a> Read<'a> for Any<'a> {
+    fn read(input: &'a [u8]) -> Result<Self, physical::Error> {
+        Ok(Any(input))
+    }
+}
 
 /// A padding value that can be written to an output
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct ValuePadding<T> {
     pub value: T,
     pub length: usize
@@ -119,14 +165,44 @@ impl ValuePadding<u8> {
         ValuePadding { value: 0, length }
     }
 }
+impl<T> Sealed for ValuePadding<T> { }
 impl<T: Size> Size for ValuePadding<T> {
     fn size(&self) -> usize {
         self.value.size() * self.length
     }
 }
+impl<T: Write> Write for ValuePadding<T> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        for _ in 0..self.length {
+            cursor.write(&self.value)?;
+        }
+        Ok(cursor.finish())
+    }
+}
 
 /// Represents a possibly padded value
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Padded<P, T> {
     pub payload: T,
     pub padding: P
-}
\ No newline at end of file
+}
+
+impl<P, T> Sealed for Padded<P, T> { }
+impl<P: Size, T: Size> Size for Padded<P, T> {
+    fn size(&self) -> usize {
+        self.payload.size() + self.padding.size()
+    }
+    fn unpadded_size(&self) -> usize {
+        self.payload.size()
+    }
+}
+/// Writes the payload followed by the padding
+impl<P: Write, T: Write> Write for Padded<P, T> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.payload)?;
+        cursor.write(&self.padding)?;
+        Ok(cursor.finish())
+    }
+}
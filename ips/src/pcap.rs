@@ -0,0 +1,449 @@
+//! Types for reading and writing classic libpcap capture files
+//!
+//! Capture files are read from a slice of bytes, such as a file read into memory, and records are written to slices of bytes
+//! one at a time, so they can be streamed to a file or socket.
+
+use crate::internal::Sealed;
+use crate::payload;
+use crate::physical::{self, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The length of the global header at the start of a capture file
+pub const HEADER_LEN: usize = 24;
+/// The length of the header before each record in a capture file
+pub const RECORD_HEADER_LEN: usize = 16;
+
+/// The magic number of capture files with microsecond timestamps
+const MAGIC_MICROSECONDS: u32 = 0xA1B2_C3D4;
+/// The magic number of capture files with nanosecond timestamps
+const MAGIC_NANOSECONDS: u32 = 0xA1B2_3C4D;
+
+/// An error that occurs while reading a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of the global header or a record
+    Truncated,
+    /// The magic number at the start of the file wasn't a pcap magic number in either byte order
+    InvalidMagic(u32),
+    /// The captured length of a record was larger than the snapshot length of the file
+    InvalidCapturedLength {
+        /// The captured length of the record
+        captured: u32,
+        /// The snapshot length of the file
+        snaplen: u32
+    },
+    /// The link type of the file wasn't the expected link type
+    UnexpectedLinkType(LinkType)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad pcap file; ran out of data"),
+            ParseError::InvalidMagic(magic) => write!(f, "bad pcap file; invalid magic number {:#010X}", magic),
+            ParseError::InvalidCapturedLength { captured, snaplen } =>
+                write!(f, "bad pcap record; captured length of {} is larger than the snapshot length of {}", captured, snaplen),
+            ParseError::UnexpectedLinkType(link_type) => write!(f, "bad pcap file; unexpected link type {}", link_type.0),
+        }
+    }
+}
+
+/// The link-layer header type of the packets in a capture
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct LinkType(pub u32);
+
+impl LinkType {
+    /// BSD loopback encapsulation
+    pub const NULL: LinkType = LinkType(0);
+    /// IEEE 802.3 ethernet
+    pub const ETHERNET: LinkType = LinkType(1);
+    /// Raw IPv4 or IPv6 packets with no link-layer header
+    pub const RAW: LinkType = LinkType(101);
+    /// IEEE 802.11 wireless LAN
+    pub const IEEE802_11: LinkType = LinkType(105);
+    /// Linux "cooked" capture encapsulation
+    pub const LINUX_SLL: LinkType = LinkType(113);
+    /// Raw IPv4 packets with no link-layer header
+    pub const IPV4: LinkType = LinkType(228);
+    /// Raw IPv6 packets with no link-layer header
+    pub const IPV6: LinkType = LinkType(229);
+    /// Linux "cooked" capture encapsulation version 2
+    pub const LINUX_SLL2: LinkType = LinkType(276);
+}
+
+/// The byte order of the fields in a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Endianness {
+    /// Big endian, or network byte order
+    Big,
+    /// Little endian
+    #[default]
+    Little
+}
+
+impl Endianness {
+    pub(crate) fn u16(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endianness::Big => u16::from_be_bytes(bytes),
+            Endianness::Little => u16::from_le_bytes(bytes),
+        }
+    }
+    pub(crate) fn u32(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Big => u32::from_be_bytes(bytes),
+            Endianness::Little => u32::from_le_bytes(bytes),
+        }
+    }
+    pub(crate) fn u16_bytes(self, value: u16) -> [u8; 2] {
+        match self {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        }
+    }
+    pub(crate) fn u32_bytes(self, value: u32) -> [u8; 4] {
+        match self {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        }
+    }
+}
+
+/// The precision of the timestamps in a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Precision {
+    /// Timestamps have a fractional part in microseconds
+    #[default]
+    Microseconds,
+    /// Timestamps have a fractional part in nanoseconds
+    Nanoseconds
+}
+
+/// The time a packet was captured
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Default)]
+pub struct Timestamp {
+    /// The number of seconds since the unix epoch
+    pub seconds: u32,
+    /// The number of nanoseconds since the start of the second
+    pub nanoseconds: u32
+}
+
+/// The global header at the start of a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Header {
+    /// The byte order of the file
+    pub endianness: Endianness,
+    /// The precision of the timestamps in the file
+    pub precision: Precision,
+    /// The major version of the file format
+    pub version_major: u16,
+    /// The minor version of the file format
+    pub version_minor: u16,
+    /// The offset of the timestamps from UTC in seconds, which is zero in practice
+    pub thiszone: i32,
+    /// The accuracy of the timestamps, which is zero in practice
+    pub sigfigs: u32,
+    /// The maximum number of bytes captured from each packet
+    pub snaplen: u32,
+    /// The link-layer header type of the packets in the file
+    pub link_type: LinkType
+}
+
+impl Header {
+    /// The major version of the current file format
+    pub const VERSION_MAJOR: u16 = 2;
+    /// The minor version of the current file format
+    pub const VERSION_MINOR: u16 = 4;
+
+    /// Creates a new little endian header with microsecond timestamps and the specified link type and snapshot length
+    pub fn new(link_type: LinkType, snaplen: u32) -> Self {
+        Header {
+            endianness: Endianness::Little,
+            precision: Precision::Microseconds,
+            version_major: Self::VERSION_MAJOR,
+            version_minor: Self::VERSION_MINOR,
+            thiszone: 0,
+            sigfigs: 0,
+            snaplen,
+            link_type
+        }
+    }
+
+    /// Parses the global header at the start of a capture file
+    pub fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        let bytes = input.get(..HEADER_LEN).ok_or(ParseError::Truncated)?;
+        let magic = <[u8; 4]>::try_from(&bytes[..4]).unwrap();
+        let (endianness, precision) = match (u32::from_be_bytes(magic), u32::from_le_bytes(magic)) {
+            (MAGIC_MICROSECONDS, _) => (Endianness::Big, Precision::Microseconds),
+            (MAGIC_NANOSECONDS, _) => (Endianness::Big, Precision::Nanoseconds),
+            (_, MAGIC_MICROSECONDS) => (Endianness::Little, Precision::Microseconds),
+            (_, MAGIC_NANOSECONDS) => (Endianness::Little, Precision::Nanoseconds),
+            (magic, _) => return Err(ParseError::InvalidMagic(magic)),
+        };
+        let u32_at = |start: usize| endianness.u32(<[u8; 4]>::try_from(&bytes[start..start + 4]).unwrap());
+
+        Ok(Header {
+            endianness,
+            precision,
+            version_major: endianness.u16([bytes[4], bytes[5]]),
+            version_minor: endianness.u16([bytes[6], bytes[7]]),
+            thiszone: u32_at(8) as i32,
+            sigfigs: u32_at(12),
+            snaplen: u32_at(16),
+            link_type: LinkType(u32_at(20))
+        })
+    }
+
+    /// Gets the number of bytes of a packet of the specified length that are captured in a record, limited by the snapshot length
+    pub fn captured_len(&self, len: usize) -> usize {
+        match self.snaplen {
+            0 => len,
+            snaplen => len.min(snaplen as usize),
+        }
+    }
+
+    /// Gets the size of the record when written with this header, including the record header
+    pub fn record_size(&self, record: &Record) -> usize {
+        RECORD_HEADER_LEN + self.captured_len(record.data.len())
+    }
+
+    /// Writes a record using the byte order and timestamp precision of this header.
+    /// If the data of the record is longer than the snapshot length, only the first [`snaplen`](#structfield.snaplen) bytes are written.
+    pub fn write_record(&self, record: &Record, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let size = self.record_size(record);
+        let output = output.get_mut(..size).ok_or(physical::Error)?;
+        let subsec = match self.precision {
+            Precision::Microseconds => record.timestamp.nanoseconds / 1000,
+            Precision::Nanoseconds => record.timestamp.nanoseconds,
+        };
+        let captured = size - RECORD_HEADER_LEN;
+
+        output[0..4].copy_from_slice(&self.endianness.u32_bytes(record.timestamp.seconds));
+        output[4..8].copy_from_slice(&self.endianness.u32_bytes(subsec));
+        output[8..12].copy_from_slice(&self.endianness.u32_bytes(captured as u32));
+        output[12..16].copy_from_slice(&self.endianness.u32_bytes(record.original_length));
+        output[16..].copy_from_slice(&record.data[..captured]);
+        Ok(size)
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Header::new(LinkType::ETHERNET, 262_144)
+    }
+}
+
+impl Sealed for Header { }
+impl Size for Header {
+    fn size(&self) -> usize {
+        HEADER_LEN
+    }
+}
+/// Writes the header using its byte order and timestamp precision
+impl Write for Header {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let output = output.get_mut(..HEADER_LEN).ok_or(physical::Error)?;
+        let magic = match self.precision {
+            Precision::Microseconds => MAGIC_MICROSECONDS,
+            Precision::Nanoseconds => MAGIC_NANOSECONDS,
+        };
+        let e = self.endianness;
+        output[0..4].copy_from_slice(&e.u32_bytes(magic));
+        output[4..6].copy_from_slice(&e.u16_bytes(self.version_major));
+        output[6..8].copy_from_slice(&e.u16_bytes(self.version_minor));
+        output[8..12].copy_from_slice(&e.u32_bytes(self.thiszone as u32));
+        output[12..16].copy_from_slice(&e.u32_bytes(self.sigfigs));
+        output[16..20].copy_from_slice(&e.u32_bytes(self.snaplen));
+        output[20..24].copy_from_slice(&e.u32_bytes(self.link_type.0));
+        Ok(HEADER_LEN)
+    }
+}
+
+/// A packet record in a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Record<'a> {
+    /// The time the packet was captured
+    pub timestamp: Timestamp,
+    /// The length of the packet on the wire, which may be longer than the captured data
+    pub original_length: u32,
+    /// The captured data of the packet
+    pub data: payload::Unknown<'a>
+}
+
+impl<'a> Record<'a> {
+    /// Creates a new record for an entire packet captured at the specified time
+    pub fn new(timestamp: Timestamp, data: &'a [u8]) -> Self {
+        Record { timestamp, original_length: data.len() as u32, data: payload::Unknown(data) }
+    }
+
+    /// Returns whether the captured data is shorter than the original packet
+    pub fn is_truncated(&self) -> bool {
+        (self.data.len() as u64) < self.original_length as u64
+    }
+}
+
+/// A reader over the records of a capture file in a slice of bytes
+#[derive(Clone, Debug)]
+pub struct Reader<'a> {
+    header: Header,
+    remaining: &'a [u8]
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new reader over a capture file, parsing the global header at the start of the data
+    pub fn new(data: &'a [u8]) -> Result<Self, ParseError> {
+        let header = Header::parse(data)?;
+        Ok(Reader { header, remaining: &data[HEADER_LEN..] })
+    }
+
+    /// Returns an error if the link type of the file isn't the specified link type
+    pub fn expect_link_type(self, link_type: LinkType) -> Result<Self, ParseError> {
+        if self.header.link_type != link_type {
+            return Err(ParseError::UnexpectedLinkType(self.header.link_type));
+        }
+        Ok(self)
+    }
+
+    /// Gets the global header of the file
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn read_record(&mut self) -> Result<Record<'a>, ParseError> {
+        let bytes = self.remaining.get(..RECORD_HEADER_LEN).ok_or(ParseError::Truncated)?;
+        let e = self.header.endianness;
+        let u32_at = |start: usize| e.u32(<[u8; 4]>::try_from(&bytes[start..start + 4]).unwrap());
+
+        let captured = u32_at(8);
+        if self.header.snaplen != 0 && captured > self.header.snaplen {
+            return Err(ParseError::InvalidCapturedLength { captured, snaplen: self.header.snaplen });
+        }
+        let end = RECORD_HEADER_LEN + captured as usize;
+        let data = self.remaining.get(RECORD_HEADER_LEN..end).ok_or(ParseError::Truncated)?;
+
+        let subsec = u32_at(4);
+        let nanoseconds = match self.header.precision {
+            Precision::Microseconds => subsec.saturating_mul(1000),
+            Precision::Nanoseconds => subsec,
+        };
+        let record = Record {
+            timestamp: Timestamp { seconds: u32_at(0), nanoseconds },
+            original_length: u32_at(12),
+            data: payload::Unknown(data)
+        };
+        self.remaining = &self.remaining[end..];
+        Ok(record)
+    }
+}
+
+/// Reads each record in the file. The iterator ends after the first error.
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Record<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let record = self.read_record();
+        if record.is_err() {
+            self.remaining = &[];
+        }
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::EthernetBase;
+
+    const FRAME: [u8; 16] = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02, 0, 0, 0, 0, 1, 0x08, 0x06, 0xAB, 0xCD];
+
+    // a big endian file with nanosecond timestamps containing one ethernet frame
+    const BIG_NANOS: [u8; 56] = [
+        0xA1, 0xB2, 0x3C, 0x4D, 0x00, 0x02, 0x00, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x00, 0xFF, 0xFF, 0, 0, 0, 1,
+        0x5F, 0x5E, 0x10, 0x00, 0x00, 0x00, 0x00, 0x07, 0, 0, 0, 16, 0, 0, 0, 60,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02, 0, 0, 0, 0, 1, 0x08, 0x06, 0xAB, 0xCD
+    ];
+
+    #[test]
+    fn read_big_endian_nanoseconds() {
+        let mut reader = Reader::new(&BIG_NANOS).unwrap().expect_link_type(LinkType::ETHERNET).unwrap();
+        let header = *reader.header();
+        assert_eq!(header.endianness, Endianness::Big);
+        assert_eq!(header.precision, Precision::Nanoseconds);
+        assert_eq!((header.version_major, header.version_minor), (2, 4));
+        assert_eq!(header.snaplen, 65535);
+
+        let record = reader.next().unwrap().unwrap();
+        assert_eq!(record.timestamp, Timestamp { seconds: 0x5F5E_1000, nanoseconds: 7 });
+        assert_eq!(record.original_length, 60);
+        assert!(record.is_truncated());
+        let frame = EthernetBase::parse(record.data).unwrap();
+        assert_eq!(frame.type_or_length, 0x0806);
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn write_and_read() {
+        for &endianness in &[Endianness::Big, Endianness::Little] {
+            let mut header = Header::new(LinkType::ETHERNET, 14);
+            header.endianness = endianness;
+            let records = [
+                Record::new(Timestamp { seconds: 1, nanoseconds: 5_000 }, &FRAME),
+                Record::new(Timestamp { seconds: 2, nanoseconds: 999_999_000 }, &FRAME[..10]),
+            ];
+
+            let mut buf = [0; 128];
+            let mut written = header.write(&mut buf).unwrap();
+            for record in &records {
+                assert_eq!(header.record_size(record), RECORD_HEADER_LEN + 14.min(record.data.len()));
+                written += header.write_record(record, &mut buf[written..]).unwrap();
+            }
+            assert_eq!(written, HEADER_LEN + 30 + 26);
+
+            let mut reader = Reader::new(&buf[..written]).unwrap();
+            assert_eq!(*reader.header(), header);
+
+            let first = reader.next().unwrap().unwrap();
+            assert_eq!(first.timestamp, records[0].timestamp);
+            assert_eq!(first.original_length, 16);
+            assert_eq!(first.data.0, &FRAME[..14]);
+
+            let second = reader.next().unwrap().unwrap();
+            assert_eq!(second, records[1]);
+            assert_eq!(reader.next(), None);
+        }
+    }
+
+    #[test]
+    fn microsecond_precision() {
+        let header = Header::new(LinkType::RAW, 0);
+        let record = Record::new(Timestamp { seconds: 10, nanoseconds: 123_456_789 }, &[0x45]);
+
+        let mut buf = [0; 41];
+        let written = header.write(&mut buf).unwrap();
+        assert_eq!(header.write_record(&record, &mut buf[written..]), Ok(17));
+        assert_eq!(&buf[..4], &[0xD4, 0xC3, 0xB2, 0xA1]);
+
+        let read = Reader::new(&buf).unwrap().next().unwrap().unwrap();
+        assert_eq!(read.timestamp, Timestamp { seconds: 10, nanoseconds: 123_456_000 });
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Reader::new(&BIG_NANOS[..23]).unwrap_err(), ParseError::Truncated);
+        assert_eq!(Reader::new(&[0; 24]).unwrap_err(), ParseError::InvalidMagic(0));
+        assert_eq!(Reader::new(&BIG_NANOS).unwrap().expect_link_type(LinkType::RAW).unwrap_err(), ParseError::UnexpectedLinkType(LinkType::ETHERNET));
+
+        let mut reader = Reader::new(&BIG_NANOS[..55]).unwrap();
+        assert_eq!(reader.next(), Some(Err(ParseError::Truncated)));
+        assert_eq!(reader.next(), None);
+
+        let mut small_snaplen = BIG_NANOS;
+        small_snaplen[16..20].copy_from_slice(&[0, 0, 0, 8]);
+        let mut reader = Reader::new(&small_snaplen).unwrap();
+        assert_eq!(reader.next(), Some(Err(ParseError::InvalidCapturedLength { captured: 16, snaplen: 8 })));
+    }
+}
@@ -0,0 +1,1301 @@
+//! Types for reading and writing pcapng capture files
+//!
+//! Like [`pcap`](../pcap/index.html) files, capture files are read from a slice of bytes and blocks are written to slices of bytes one at a time.
+//! A file is made of one or more sections, each starting with a section header block that sets the byte order of the section.
+//! Packets refer to the interfaces described earlier in the same section, which determine their link type and timestamp resolution.
+
+use crate::payload;
+use crate::pcap::{Endianness, LinkType, Timestamp};
+use crate::physical;
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// The length of the block type, the block length, and the trailing block length of every block
+pub const BLOCK_OVERHEAD: usize = 12;
+
+/// The magic number used to determine the byte order of a section
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+/// An error that occurs while reading a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The data ended before the end of a block, option, or record
+    Truncated,
+    /// The byte order magic of a section header wasn't valid in either byte order
+    InvalidMagic(u32),
+    /// A block length was too small, not a multiple of 4, or didn't match the trailing block length
+    InvalidBlockLength(u32),
+    /// The major version of a section wasn't 1
+    UnsupportedVersion(u16),
+    /// A block other than a section header appeared before the first section header
+    MissingSectionHeader,
+    /// A packet referred to an interface that wasn't described in the current section
+    UnknownInterface(u32)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad pcapng file; ran out of data"),
+            ParseError::InvalidMagic(magic) => write!(f, "bad pcapng section; invalid byte order magic {:#010X}", magic),
+            ParseError::InvalidBlockLength(len) => write!(f, "bad pcapng block; invalid block length {}", len),
+            ParseError::UnsupportedVersion(major) => write!(f, "bad pcapng section; unsupported major version {}", major),
+            ParseError::MissingSectionHeader => f.write_str("bad pcapng file; expected a section header block"),
+            ParseError::UnknownInterface(id) => write!(f, "bad pcapng packet; unknown interface {}", id),
+        }
+    }
+}
+
+/// Gets the length of data padded to 32 bits, or `None` if it overflows
+fn padded_len(len: usize) -> Option<usize> {
+    len.checked_add(3).map(|len| len & !3)
+}
+
+/// The resolution of the timestamps of an interface
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Resolution {
+    /// Timestamps are in units of 10 to the negative power of the value
+    Decimal(u8),
+    /// Timestamps are in units of 2 to the negative power of the value
+    Binary(u8)
+}
+
+impl Resolution {
+    /// The default resolution of timestamps
+    pub const MICROSECONDS: Resolution = Resolution::Decimal(6);
+    /// A resolution of nanoseconds
+    pub const NANOSECONDS: Resolution = Resolution::Decimal(9);
+
+    /// Gets the resolution from the value of an `if_tsresol` option
+    pub fn from_option(value: u8) -> Self {
+        if value & 0x80 == 0 {
+            Resolution::Decimal(value)
+        } else {
+            Resolution::Binary(value & 0x7F)
+        }
+    }
+
+    /// Gets the value of an `if_tsresol` option for this resolution, or `None` if the exponent doesn't fit in 7 bits
+    pub fn to_option(self) -> Option<u8> {
+        match self {
+            Resolution::Decimal(exp) if exp <= 0x7F => Some(exp),
+            Resolution::Binary(exp) if exp <= 0x7F => Some(0x80 | exp),
+            _ => None,
+        }
+    }
+
+    /// Gets the number of timestamp units in a second, or `None` if it's too large to represent
+    pub fn units_per_second(self) -> Option<u64> {
+        match self {
+            Resolution::Decimal(exp) => 10u64.checked_pow(exp as u32),
+            Resolution::Binary(exp) => 1u64.checked_shl(exp as u32),
+        }
+    }
+
+    /// Converts a timestamp in units of this resolution to seconds and nanoseconds
+    pub fn to_timestamp(self, units: u64) -> Option<Timestamp> {
+        let per_second = self.units_per_second()?;
+        let seconds = u32::try_from(units / per_second).ok()?;
+        let nanoseconds = ((units % per_second) as u128 * 1_000_000_000 / per_second as u128) as u32;
+        Some(Timestamp { seconds, nanoseconds })
+    }
+
+    /// Converts seconds and nanoseconds to a timestamp in units of this resolution
+    pub fn from_timestamp(self, timestamp: Timestamp) -> Option<u64> {
+        let per_second = self.units_per_second()? as u128;
+        let units = timestamp.seconds as u128 * per_second + timestamp.nanoseconds as u128 * per_second / 1_000_000_000;
+        u64::try_from(units).ok()
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Resolution::MICROSECONDS
+    }
+}
+
+/// An option of a block, with a value in the byte order of its section
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct OptionValue<'a> {
+    /// The option code, whose meaning depends on the type of block
+    pub code: u16,
+    /// The value of the option, excluding padding
+    pub value: &'a [u8]
+}
+
+impl<'a> OptionValue<'a> {
+    /// The code of comment options, which can appear in any block
+    pub const COMMENT: u16 = 1;
+    /// The code of custom options containing a UTF-8 string that can be copied to new files
+    pub const CUSTOM_STRING: u16 = 2988;
+    /// The code of custom options containing binary data that can be copied to new files
+    pub const CUSTOM_BINARY: u16 = 2989;
+
+    /// Creates a new comment option
+    pub fn comment(comment: &'a str) -> Self {
+        OptionValue { code: Self::COMMENT, value: comment.as_bytes() }
+    }
+
+    /// Gets the value of the option as a UTF-8 string
+    pub fn as_str(&self) -> Option<&'a str> {
+        core::str::from_utf8(self.value).ok()
+    }
+}
+
+/// The options of a block
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Options<'a> {
+    /// Options parsed from a block, which are read as they're iterated
+    Raw {
+        /// The data of the options
+        data: &'a [u8],
+        /// The byte order of the section the block is in
+        endianness: Endianness
+    },
+    /// A list of options used to build a block
+    List(&'a [OptionValue<'a>])
+}
+
+impl<'a> Options<'a> {
+    /// An empty options list
+    pub const EMPTY: Options<'static> = Options::List(&[]);
+
+    /// Returns an iterator over the options, which ends at the end of options option.
+    /// If an option is truncated, the iterator returns an error.
+    pub fn iter(&self) -> OptionsIter<'a> {
+        match *self {
+            Options::Raw { data, endianness } => OptionsIter::Raw { data, endianness },
+            Options::List(list) => OptionsIter::List(list.iter()),
+        }
+    }
+
+    /// Gets the value of the first option with the specified code, skipping malformed options
+    pub fn find(&self, code: u16) -> Option<&'a [u8]> {
+        self.iter().filter_map(Result::ok).find(|option| option.code == code).map(|option| option.value)
+    }
+
+    /// Returns an iterator over the comments of the block
+    pub fn comments(&self) -> impl Iterator<Item = &'a str> {
+        self.iter().filter_map(Result::ok).filter(|option| option.code == OptionValue::COMMENT).filter_map(|option| option.as_str())
+    }
+}
+
+impl Default for Options<'_> {
+    fn default() -> Self {
+        Options::EMPTY
+    }
+}
+
+/// An iterator over the options of a block
+#[derive(Clone, Debug)]
+pub enum OptionsIter<'a> {
+    #[doc(hidden)]
+    Raw {
+        data: &'a [u8],
+        endianness: Endianness
+    },
+    #[doc(hidden)]
+    List(core::slice::Iter<'a, OptionValue<'a>>)
+}
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = Result<OptionValue<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (data, endianness) = match self {
+            OptionsIter::Raw { data, endianness } => (data, *endianness),
+            OptionsIter::List(list) => return list.next().copied().map(Ok),
+        };
+        if data.len() < 4 {
+            *data = &[];
+            return None;
+        }
+
+        let code = endianness.u16([data[0], data[1]]);
+        let length = endianness.u16([data[2], data[3]]) as usize;
+        if code == 0 {
+            *data = &[];
+            return None;
+        }
+        let value = match data.get(4..4 + length) {
+            Some(value) => value,
+            None => {
+                *data = &[];
+                return Some(Err(ParseError::Truncated));
+            }
+        };
+        *data = padded_len(length).and_then(|len| data.get(4 + len..)).unwrap_or(&[]);
+        Some(Ok(OptionValue { code, value }))
+    }
+}
+
+/// A block that starts a section and sets the byte order of the blocks in it
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SectionHeader<'a> {
+    /// The byte order of the section
+    pub endianness: Endianness,
+    /// The major version of the section format
+    pub version_major: u16,
+    /// The minor version of the section format
+    pub version_minor: u16,
+    /// The length of the section in bytes after this block, or `None` if it isn't specified
+    pub section_length: Option<u64>,
+    /// The options of the section, such as the hardware and operating system it was captured on
+    pub options: Options<'a>
+}
+
+impl<'a> SectionHeader<'a> {
+    /// The block type of section header blocks
+    pub const BLOCK_TYPE: u32 = 0x0A0D_0D0A;
+    /// The major version of the current section format
+    pub const VERSION_MAJOR: u16 = 1;
+    /// The minor version of the current section format
+    pub const VERSION_MINOR: u16 = 0;
+    /// The code of the option describing the hardware used to create the section
+    pub const HARDWARE: u16 = 2;
+    /// The code of the option describing the operating system used to create the section
+    pub const OS: u16 = 3;
+    /// The code of the option describing the application used to create the section
+    pub const USER_APPLICATION: u16 = 4;
+
+    /// Creates a new section header with the specified byte order and no options
+    pub fn new(endianness: Endianness) -> Self {
+        SectionHeader {
+            endianness,
+            version_major: Self::VERSION_MAJOR,
+            version_minor: Self::VERSION_MINOR,
+            section_length: None,
+            options: Options::EMPTY
+        }
+    }
+}
+
+/// A block that describes an interface packets are captured on
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct InterfaceDescription<'a> {
+    /// The link-layer header type of packets captured on the interface
+    pub link_type: LinkType,
+    /// The maximum number of bytes captured from each packet, or zero if there's no limit
+    pub snaplen: u32,
+    /// The resolution of the timestamps of packets captured on the interface, from the `if_tsresol` option
+    pub resolution: Resolution,
+    /// The options of the interface, such as its name and description
+    pub options: Options<'a>
+}
+
+impl<'a> InterfaceDescription<'a> {
+    /// The block type of interface description blocks
+    pub const BLOCK_TYPE: u32 = 0x0000_0001;
+    /// The code of the option containing the name of the interface
+    pub const NAME: u16 = 2;
+    /// The code of the option containing the description of the interface
+    pub const DESCRIPTION: u16 = 3;
+    /// The code of the option containing the timestamp resolution of the interface
+    pub const TSRESOL: u16 = 9;
+    /// The code of the option containing the length of the frame check sequence of packets captured on the interface
+    pub const FCSLEN: u16 = 13;
+
+    /// Creates a new interface description with the specified link type and snapshot length, microsecond timestamps, and no options
+    pub fn new(link_type: LinkType, snaplen: u32) -> Self {
+        InterfaceDescription { link_type, snaplen, resolution: Resolution::MICROSECONDS, options: Options::EMPTY }
+    }
+
+    /// Gets the name of the interface from the `if_name` option
+    pub fn name(&self) -> Option<&'a str> {
+        self.options.find(Self::NAME).and_then(|name| core::str::from_utf8(name).ok())
+    }
+
+    /// Gets the description of the interface from the `if_description` option
+    pub fn description(&self) -> Option<&'a str> {
+        self.options.find(Self::DESCRIPTION).and_then(|description| core::str::from_utf8(description).ok())
+    }
+}
+
+/// The direction of a packet relative to the interface it was captured on
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Direction {
+    /// The direction isn't known
+    Unknown,
+    /// The packet was received by the interface
+    Inbound,
+    /// The packet was sent by the interface
+    Outbound,
+    /// The direction bits had the reserved value
+    Invalid
+}
+
+/// The flags of an enhanced packet from the `epb_flags` option
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct PacketFlags(pub u32);
+
+impl PacketFlags {
+    /// The packet was received with a CRC error
+    pub const CRC_ERROR: u32 = 1 << 24;
+    /// The packet was longer than the maximum length
+    pub const PACKET_TOO_LONG: u32 = 1 << 25;
+    /// The packet was shorter than the minimum length
+    pub const PACKET_TOO_SHORT: u32 = 1 << 26;
+    /// The packet had a wrong inter-frame gap
+    pub const WRONG_INTER_FRAME_GAP: u32 = 1 << 27;
+    /// The packet had an unaligned frame error
+    pub const UNALIGNED_FRAME: u32 = 1 << 28;
+    /// The packet had a start frame delimiter error
+    pub const START_FRAME_DELIMITER: u32 = 1 << 29;
+    /// The packet had a preamble error
+    pub const PREAMBLE: u32 = 1 << 30;
+    /// The packet had a symbol error
+    pub const SYMBOL: u32 = 1 << 31;
+
+    /// Gets the direction of the packet
+    pub fn direction(self) -> Direction {
+        match self.0 & 0b11 {
+            0 => Direction::Unknown,
+            1 => Direction::Inbound,
+            2 => Direction::Outbound,
+            _ => Direction::Invalid,
+        }
+    }
+
+    /// Gets the reception type of the packet, where 1 is unicast, 2 is multicast, 3 is broadcast, 4 is promiscuous, and 0 is unspecified
+    pub fn reception_type(self) -> u8 {
+        ((self.0 >> 2) & 0b111) as u8
+    }
+
+    /// Gets the length of the frame check sequence at the end of the packet, or `None` if it isn't specified
+    pub fn fcs_length(self) -> Option<u8> {
+        match ((self.0 >> 5) & 0b1111) as u8 {
+            0 => None,
+            len => Some(len),
+        }
+    }
+
+    /// Gets the link-layer error flags of the packet
+    pub fn errors(self) -> u32 {
+        self.0 & 0xFFFF_0000
+    }
+}
+
+/// A block containing a packet captured on an interface
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EnhancedPacket<'a> {
+    /// The index of the interface in the current section the packet was captured on
+    pub interface_id: u32,
+    /// The time the packet was captured, in units of the resolution of the interface
+    pub timestamp: u64,
+    /// The length of the packet on the wire, which may be longer than the captured data
+    pub original_length: u32,
+    /// The captured data of the packet
+    pub data: payload::Unknown<'a>,
+    /// The flags of the packet from the `epb_flags` option
+    pub flags: Option<PacketFlags>,
+    /// The options of the packet, such as comments
+    pub options: Options<'a>
+}
+
+impl<'a> EnhancedPacket<'a> {
+    /// The block type of enhanced packet blocks
+    pub const BLOCK_TYPE: u32 = 0x0000_0006;
+    /// The code of the option containing the flags of the packet
+    pub const FLAGS: u16 = 2;
+
+    /// Creates a new enhanced packet for an entire packet captured on the specified interface at the specified time
+    pub fn new(interface_id: u32, timestamp: u64, data: &'a [u8]) -> Self {
+        EnhancedPacket {
+            interface_id,
+            timestamp,
+            original_length: data.len() as u32,
+            data: payload::Unknown(data),
+            flags: None,
+            options: Options::EMPTY
+        }
+    }
+}
+
+/// A block containing a packet captured on the first interface of the section, with no timestamp
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SimplePacket<'a> {
+    /// The length of the packet on the wire, which may be longer than the captured data
+    pub original_length: u32,
+    /// The captured data of the packet
+    pub data: payload::Unknown<'a>
+}
+
+impl<'a> SimplePacket<'a> {
+    /// The block type of simple packet blocks
+    pub const BLOCK_TYPE: u32 = 0x0000_0003;
+
+    /// Creates a new simple packet for an entire packet
+    pub fn new(data: &'a [u8]) -> Self {
+        SimplePacket { original_length: data.len() as u32, data: payload::Unknown(data) }
+    }
+}
+
+/// The names of an address in a name resolution record, stored as a sequence of zero-terminated UTF-8 strings
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Names<'a>(pub &'a [u8]);
+
+impl<'a> Names<'a> {
+    /// Returns an iterator over the names, skipping names that aren't valid UTF-8
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> {
+        self.0.split(|&b| b == 0).filter(|name| !name.is_empty()).filter_map(|name| core::str::from_utf8(name).ok())
+    }
+}
+
+/// A record mapping an address to names
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NameRecord<'a> {
+    /// A record mapping an IPv4 address to names
+    Ipv4 {
+        /// The IPv4 address
+        address: Ipv4Addr,
+        /// The names of the address
+        names: Names<'a>
+    },
+    /// A record mapping an IPv6 address to names
+    Ipv6 {
+        /// The IPv6 address
+        address: Ipv6Addr,
+        /// The names of the address
+        names: Names<'a>
+    },
+    /// A record of an unknown type, or an IPv4 or IPv6 record too short to contain its address
+    Unknown {
+        /// The type of the record
+        kind: u16,
+        /// The value of the record, excluding padding
+        value: &'a [u8]
+    }
+}
+
+impl NameRecord<'_> {
+    /// The record type of IPv4 records
+    pub const IPV4: u16 = 1;
+    /// The record type of IPv6 records
+    pub const IPV6: u16 = 2;
+
+    fn kind(&self) -> u16 {
+        match self {
+            NameRecord::Ipv4 { .. } => Self::IPV4,
+            NameRecord::Ipv6 { .. } => Self::IPV6,
+            NameRecord::Unknown { kind, .. } => *kind,
+        }
+    }
+
+    fn value_len(&self) -> usize {
+        match self {
+            NameRecord::Ipv4 { names, .. } => 4 + names.0.len(),
+            NameRecord::Ipv6 { names, .. } => 16 + names.0.len(),
+            NameRecord::Unknown { value, .. } => value.len(),
+        }
+    }
+}
+
+/// The records of a name resolution block
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Records<'a> {
+    /// Records parsed from a block, which are read as they're iterated
+    Raw {
+        /// The data of the records
+        data: &'a [u8],
+        /// The byte order of the section the block is in
+        endianness: Endianness
+    },
+    /// A list of records used to build a block
+    List(&'a [NameRecord<'a>])
+}
+
+impl<'a> Records<'a> {
+    /// Returns an iterator over the records
+    pub fn iter(&self) -> RecordsIter<'a> {
+        match *self {
+            Records::Raw { data, endianness } => RecordsIter::Raw { data, endianness },
+            Records::List(list) => RecordsIter::List(list.iter()),
+        }
+    }
+}
+
+/// An iterator over the records of a name resolution block
+#[derive(Clone, Debug)]
+pub enum RecordsIter<'a> {
+    #[doc(hidden)]
+    Raw {
+        data: &'a [u8],
+        endianness: Endianness
+    },
+    #[doc(hidden)]
+    List(core::slice::Iter<'a, NameRecord<'a>>)
+}
+
+impl<'a> Iterator for RecordsIter<'a> {
+    type Item = Result<NameRecord<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (data, endianness) = match self {
+            RecordsIter::Raw { data, endianness } => (data, *endianness),
+            RecordsIter::List(list) => return list.next().copied().map(Ok),
+        };
+        if data.len() < 4 {
+            *data = &[];
+            return None;
+        }
+        let kind = endianness.u16([data[0], data[1]]);
+        let length = endianness.u16([data[2], data[3]]) as usize;
+        let value = match data.get(4..4 + length) {
+            Some(value) => value,
+            None => {
+                *data = &[];
+                return Some(Err(ParseError::Truncated));
+            }
+        };
+        *data = padded_len(length).and_then(|len| data.get(4 + len..)).unwrap_or(&[]);
+
+        Some(Ok(match (kind, value.len()) {
+            (NameRecord::IPV4, 4..) => NameRecord::Ipv4 {
+                address: Ipv4Addr::from(<[u8; 4]>::try_from(&value[..4]).unwrap()),
+                names: Names(&value[4..])
+            },
+            (NameRecord::IPV6, 16..) => NameRecord::Ipv6 {
+                address: Ipv6Addr::from(<[u8; 16]>::try_from(&value[..16]).unwrap()),
+                names: Names(&value[16..])
+            },
+            _ => NameRecord::Unknown { kind, value },
+        }))
+    }
+}
+
+/// A block that maps addresses to names
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct NameResolution<'a> {
+    /// The records mapping addresses to names
+    pub records: Records<'a>,
+    /// The options of the block, such as the DNS server the names were resolved with
+    pub options: Options<'a>
+}
+
+impl NameResolution<'_> {
+    /// The block type of name resolution blocks
+    pub const BLOCK_TYPE: u32 = 0x0000_0004;
+}
+
+/// A block containing data in a format defined by a private enterprise
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Custom<'a> {
+    /// Whether the block can be copied to new files by tools that don't understand it
+    pub copyable: bool,
+    /// The IANA private enterprise number of the organization that defined the block
+    pub enterprise_number: u32,
+    /// The custom data of the block, including any options
+    pub data: &'a [u8]
+}
+
+impl Custom<'_> {
+    /// The block type of custom blocks that can be copied
+    pub const COPYABLE_BLOCK_TYPE: u32 = 0x0000_0BAD;
+    /// The block type of custom blocks that shouldn't be copied
+    pub const NON_COPYABLE_BLOCK_TYPE: u32 = 0x4000_0BAD;
+}
+
+/// A block in a capture file
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Block<'a> {
+    /// A section header block
+    SectionHeader(SectionHeader<'a>),
+    /// An interface description block
+    InterfaceDescription(InterfaceDescription<'a>),
+    /// An enhanced packet block
+    EnhancedPacket(EnhancedPacket<'a>),
+    /// A simple packet block
+    SimplePacket(SimplePacket<'a>),
+    /// A name resolution block
+    NameResolution(NameResolution<'a>),
+    /// A custom block
+    Custom(Custom<'a>),
+    /// A block of an unknown type
+    Unknown {
+        /// The block type
+        kind: u32,
+        /// The body of the block, excluding the block type and lengths, in the byte order of the section
+        body: &'a [u8]
+    }
+}
+
+/// A packet from an enhanced or simple packet block, along with the interface it was captured on
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Packet<'a> {
+    /// The index of the interface in the current section the packet was captured on
+    pub interface_id: u32,
+    /// The interface the packet was captured on
+    pub interface: InterfaceDescription<'a>,
+    /// The time the packet was captured, or `None` for simple packets or timestamps that can't be represented
+    pub timestamp: Option<Timestamp>,
+    /// The length of the packet on the wire, which may be longer than the captured data
+    pub original_length: u32,
+    /// The captured data of the packet
+    pub data: payload::Unknown<'a>,
+    /// The flags of the packet, if it was an enhanced packet with an `epb_flags` option
+    pub flags: Option<PacketFlags>,
+    /// The options of the packet, which are empty for simple packets
+    pub options: Options<'a>
+}
+
+impl Packet<'_> {
+    /// Gets the link type of the interface the packet was captured on
+    pub fn link_type(&self) -> LinkType {
+        self.interface.link_type
+    }
+}
+
+/// A reader over the blocks of a capture file in a slice of bytes
+#[derive(Clone, Debug)]
+pub struct Reader<'a> {
+    remaining: &'a [u8],
+    endianness: Option<Endianness>,
+    interfaces: Vec<InterfaceDescription<'a>>
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new reader over a capture file. The file must start with a section header block.
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { remaining: data, endianness: None, interfaces: Vec::new() }
+    }
+
+    /// Gets the interfaces described so far in the current section, indexed by their interface ID
+    pub fn interfaces(&self) -> &[InterfaceDescription<'a>] {
+        &self.interfaces
+    }
+
+    /// Returns an iterator over the packets in the file and the interfaces they were captured on, skipping other blocks
+    pub fn packets(self) -> Packets<'a> {
+        Packets(self)
+    }
+
+    fn read_block(&mut self) -> Result<Block<'a>, ParseError> {
+        let data = self.remaining;
+        if data.len() < BLOCK_OVERHEAD {
+            return Err(ParseError::Truncated);
+        }
+
+        // the section header block type reads the same in either byte order
+        let endianness = if data[..4] == SectionHeader::BLOCK_TYPE.to_be_bytes() {
+            let magic = <[u8; 4]>::try_from(&data[8..12]).unwrap();
+            match (u32::from_be_bytes(magic), u32::from_le_bytes(magic)) {
+                (BYTE_ORDER_MAGIC, _) => Endianness::Big,
+                (_, BYTE_ORDER_MAGIC) => Endianness::Little,
+                (magic, _) => return Err(ParseError::InvalidMagic(magic)),
+            }
+        } else {
+            self.endianness.ok_or(ParseError::MissingSectionHeader)?
+        };
+
+        let e = endianness;
+        let u32_at = |bytes: &[u8], start: usize| e.u32(<[u8; 4]>::try_from(&bytes[start..start + 4]).unwrap());
+        let kind = u32_at(data, 0);
+        let length = u32_at(data, 4);
+        if (length as usize) < BLOCK_OVERHEAD || length % 4 != 0 {
+            return Err(ParseError::InvalidBlockLength(length));
+        }
+        let block = data.get(..length as usize).ok_or(ParseError::Truncated)?;
+        if u32_at(block, block.len() - 4) != length {
+            return Err(ParseError::InvalidBlockLength(length));
+        }
+        let body = &block[8..block.len() - 4];
+        let options = |start: usize| Options::Raw { data: body.get(start..).unwrap_or(&[]), endianness: e };
+
+        let block = match kind {
+            SectionHeader::BLOCK_TYPE => {
+                if body.len() < 16 {
+                    return Err(ParseError::Truncated);
+                }
+                let version_major = e.u16([body[4], body[5]]);
+                if version_major != SectionHeader::VERSION_MAJOR {
+                    return Err(ParseError::UnsupportedVersion(version_major));
+                }
+                let section_length = <[u8; 8]>::try_from(&body[8..16]).unwrap();
+                let section_length = match e {
+                    Endianness::Big => u64::from_be_bytes(section_length),
+                    Endianness::Little => u64::from_le_bytes(section_length),
+                };
+
+                self.endianness = Some(e);
+                self.interfaces.clear();
+                Block::SectionHeader(SectionHeader {
+                    endianness: e,
+                    version_major,
+                    version_minor: e.u16([body[6], body[7]]),
+                    section_length: if section_length == u64::MAX { None } else { Some(section_length) },
+                    options: options(16)
+                })
+            },
+            InterfaceDescription::BLOCK_TYPE => {
+                if body.len() < 8 {
+                    return Err(ParseError::Truncated);
+                }
+                let options = options(8);
+                let interface = InterfaceDescription {
+                    link_type: LinkType(e.u16([body[0], body[1]]) as u32),
+                    snaplen: u32_at(body, 4),
+                    resolution: match options.find(InterfaceDescription::TSRESOL) {
+                        Some(&[value]) => Resolution::from_option(value),
+                        _ => Resolution::MICROSECONDS,
+                    },
+                    options
+                };
+                self.interfaces.push(interface);
+                Block::InterfaceDescription(interface)
+            },
+            EnhancedPacket::BLOCK_TYPE => {
+                if body.len() < 20 {
+                    return Err(ParseError::Truncated);
+                }
+                let captured = u32_at(body, 12) as usize;
+                let data = captured.checked_add(20).and_then(|end| body.get(20..end)).ok_or(ParseError::Truncated)?;
+                let options = options(padded_len(captured).and_then(|len| len.checked_add(20)).ok_or(ParseError::Truncated)?);
+                Block::EnhancedPacket(EnhancedPacket {
+                    interface_id: u32_at(body, 0),
+                    timestamp: ((u32_at(body, 4) as u64) << 32) | u32_at(body, 8) as u64,
+                    original_length: u32_at(body, 16),
+                    data: payload::Unknown(data),
+                    flags: options.find(EnhancedPacket::FLAGS)
+                        .and_then(|flags| <[u8; 4]>::try_from(flags).ok())
+                        .map(|flags| PacketFlags(e.u32(flags))),
+                    options
+                })
+            },
+            SimplePacket::BLOCK_TYPE => {
+                if body.len() < 4 {
+                    return Err(ParseError::Truncated);
+                }
+                let original_length = u32_at(body, 0);
+                let mut captured = (original_length as usize).min(body.len() - 4);
+                if let Some(interface) = self.interfaces.first().filter(|interface| interface.snaplen != 0) {
+                    captured = captured.min(interface.snaplen as usize);
+                }
+                Block::SimplePacket(SimplePacket { original_length, data: payload::Unknown(&body[4..4 + captured]) })
+            },
+            NameResolution::BLOCK_TYPE => {
+                let mut end = 0;
+                loop {
+                    let header = body.get(end..end + 4).ok_or(ParseError::Truncated)?;
+                    let kind = e.u16([header[0], header[1]]);
+                    let length = e.u16([header[2], header[3]]) as usize;
+                    body.get(end + 4..end + 4 + length).ok_or(ParseError::Truncated)?;
+                    if kind == 0 {
+                        break;
+                    }
+                    end += 4 + padded_len(length).ok_or(ParseError::Truncated)?;
+                }
+                Block::NameResolution(NameResolution {
+                    records: Records::Raw { data: &body[..end], endianness: e },
+                    options: options(end + 4)
+                })
+            },
+            Custom::COPYABLE_BLOCK_TYPE | Custom::NON_COPYABLE_BLOCK_TYPE => {
+                if body.len() < 4 {
+                    return Err(ParseError::Truncated);
+                }
+                Block::Custom(Custom {
+                    copyable: kind == Custom::COPYABLE_BLOCK_TYPE,
+                    enterprise_number: u32_at(body, 0),
+                    data: &body[4..]
+                })
+            },
+            kind => Block::Unknown { kind, body },
+        };
+
+        self.remaining = &data[length as usize..];
+        Ok(block)
+    }
+}
+
+/// Reads each block in the file. The iterator ends after the first error.
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Block<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let block = self.read_block();
+        if block.is_err() {
+            self.remaining = &[];
+        }
+        Some(block)
+    }
+}
+
+/// An iterator over the packets in a capture file, created by [`Reader::packets`](struct.Reader.html#method.packets)
+#[derive(Clone, Debug)]
+pub struct Packets<'a>(Reader<'a>);
+
+impl<'a> Packets<'a> {
+    /// Gets the interfaces described so far in the current section, indexed by their interface ID
+    pub fn interfaces(&self) -> &[InterfaceDescription<'a>] {
+        self.0.interfaces()
+    }
+
+    fn interface(&self, id: u32) -> Result<InterfaceDescription<'a>, ParseError> {
+        self.0.interfaces.get(id as usize).copied().ok_or(ParseError::UnknownInterface(id))
+    }
+}
+
+impl<'a> Iterator for Packets<'a> {
+    type Item = Result<Packet<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let packet = match self.0.next()? {
+                Ok(Block::EnhancedPacket(packet)) => self.interface(packet.interface_id).map(|interface| Packet {
+                    interface_id: packet.interface_id,
+                    interface,
+                    timestamp: interface.resolution.to_timestamp(packet.timestamp),
+                    original_length: packet.original_length,
+                    data: packet.data,
+                    flags: packet.flags,
+                    options: packet.options
+                }),
+                Ok(Block::SimplePacket(packet)) => self.interface(0).map(|interface| Packet {
+                    interface_id: 0,
+                    interface,
+                    timestamp: None,
+                    original_length: packet.original_length,
+                    data: packet.data,
+                    flags: None,
+                    options: Options::EMPTY
+                }),
+                Ok(_) => continue,
+                Err(e) => Err(e),
+            };
+            if packet.is_err() {
+                self.0.remaining = &[];
+            }
+            return Some(packet);
+        }
+    }
+}
+
+/// A helper used to write a block to an output
+struct BlockWriter<'o> {
+    output: &'o mut [u8],
+    written: usize,
+    endianness: Endianness
+}
+
+impl<'o> BlockWriter<'o> {
+    fn new(output: &'o mut [u8], endianness: Endianness, kind: u32) -> Result<Self, physical::Error> {
+        let mut writer = BlockWriter { output, written: 0, endianness };
+        writer.u32(kind)?;
+        writer.u32(0)?;
+        Ok(writer)
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), physical::Error> {
+        let end = self.written + data.len();
+        self.output.get_mut(self.written..end).ok_or(physical::Error)?.copy_from_slice(data);
+        self.written = end;
+        Ok(())
+    }
+
+    fn padded(&mut self, data: &[u8]) -> Result<(), physical::Error> {
+        self.bytes(data)?;
+        let padded = padded_len(data.len()).ok_or(physical::Error)?;
+        self.bytes(&[0; 3][..padded - data.len()])
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), physical::Error> {
+        self.bytes(&self.endianness.u16_bytes(value))
+    }
+
+    fn u32(&mut self, value: u32) -> Result<(), physical::Error> {
+        self.bytes(&self.endianness.u32_bytes(value))
+    }
+
+    fn option(&mut self, code: u16, value: &[u8]) -> Result<(), physical::Error> {
+        let length = u16::try_from(value.len()).map_err(|_| physical::Error)?;
+        self.u16(code)?;
+        self.u16(length)?;
+        self.padded(value)
+    }
+
+    /// Writes the options, skipping options with codes written separately from typed fields, followed by an end of options option
+    fn options(&mut self, options: &Options, typed: &[(u16, Option<&[u8]>)]) -> Result<(), physical::Error> {
+        let mut any = false;
+        for &(code, value) in typed {
+            if let Some(value) = value {
+                self.option(code, value)?;
+                any = true;
+            }
+        }
+        for option in options.iter() {
+            let option = option.map_err(|_| physical::Error)?;
+            if typed.iter().all(|&(code, _)| code != option.code) {
+                self.option(option.code, option.value)?;
+                any = true;
+            }
+        }
+        if any {
+            self.u32(0)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<usize, physical::Error> {
+        let length = u32::try_from(self.written + 4).map_err(|_| physical::Error)?;
+        self.u32(length)?;
+        let length = self.endianness.u32_bytes(length);
+        self.output[4..8].copy_from_slice(&length);
+        Ok(self.written)
+    }
+}
+
+/// A writer of capture file blocks, which tracks the interfaces of the current section
+#[derive(Clone, Debug, Default)]
+pub struct Writer {
+    /// The byte order of the current section, or `None` if no section header has been written
+    endianness: Option<Endianness>,
+    interfaces: Vec<(u32, Resolution)>
+}
+
+impl Writer {
+    /// Creates a new writer. The first block written must be a section header, and writing any other block before it fails.
+    pub fn new() -> Self {
+        Writer::default()
+    }
+
+    /// Gets the byte order of the current section, returning an error if no section header has been written
+    fn section(&self) -> Result<Endianness, physical::Error> {
+        self.endianness.ok_or(physical::Error)
+    }
+
+    /// Converts a timestamp to units of the resolution of the specified interface,
+    /// returning `None` if the interface isn't described or the timestamp can't be represented
+    pub fn timestamp(&self, interface_id: u32, timestamp: Timestamp) -> Option<u64> {
+        let &(_, resolution) = self.interfaces.get(interface_id as usize)?;
+        resolution.from_timestamp(timestamp)
+    }
+
+    /// Writes a block, returning the number of bytes written
+    pub fn write_block(&mut self, block: &Block, output: &mut [u8]) -> Result<usize, physical::Error> {
+        match block {
+            Block::SectionHeader(header) => self.write_section_header(header, output),
+            Block::InterfaceDescription(interface) => self.write_interface_description(interface, output),
+            Block::EnhancedPacket(packet) => self.write_enhanced_packet(packet, output),
+            Block::SimplePacket(packet) => self.write_simple_packet(packet, output),
+            Block::NameResolution(names) => self.write_name_resolution(names, output),
+            Block::Custom(custom) => self.write_custom(custom, output),
+            Block::Unknown { kind, body } => {
+                let mut writer = BlockWriter::new(output, self.section()?, *kind)?;
+                writer.padded(body)?;
+                writer.finish()
+            },
+        }
+    }
+
+    /// Writes a section header, starting a new section with the byte order of the header
+    pub fn write_section_header(&mut self, header: &SectionHeader, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut writer = BlockWriter::new(output, header.endianness, SectionHeader::BLOCK_TYPE)?;
+        let section_length = header.section_length.unwrap_or(u64::MAX);
+        writer.u32(BYTE_ORDER_MAGIC)?;
+        writer.u16(header.version_major)?;
+        writer.u16(header.version_minor)?;
+        match header.endianness {
+            Endianness::Big => writer.bytes(&section_length.to_be_bytes())?,
+            Endianness::Little => writer.bytes(&section_length.to_le_bytes())?,
+        }
+        writer.options(&header.options, &[])?;
+        let written = writer.finish()?;
+
+        self.endianness = Some(header.endianness);
+        self.interfaces.clear();
+        Ok(written)
+    }
+
+    /// Writes an interface description, adding the interface to the current section.
+    /// The `if_tsresol` option is written from the resolution of the interface.
+    pub fn write_interface_description(&mut self, interface: &InterfaceDescription, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let link_type = u16::try_from(interface.link_type.0).map_err(|_| physical::Error)?;
+        let resolution = [interface.resolution.to_option().ok_or(physical::Error)?];
+        let resolution = Some(&resolution[..]).filter(|_| interface.resolution != Resolution::MICROSECONDS);
+
+        let mut writer = BlockWriter::new(output, self.section()?, InterfaceDescription::BLOCK_TYPE)?;
+        writer.u16(link_type)?;
+        writer.u16(0)?;
+        writer.u32(interface.snaplen)?;
+        writer.options(&interface.options, &[(InterfaceDescription::TSRESOL, resolution)])?;
+        let written = writer.finish()?;
+
+        self.interfaces.push((interface.snaplen, interface.resolution));
+        Ok(written)
+    }
+
+    /// Writes an enhanced packet. If the data is longer than the snapshot length of its interface, only the first `snaplen` bytes are written.
+    /// The `epb_flags` option is written from the flags of the packet.
+    pub fn write_enhanced_packet(&self, packet: &EnhancedPacket, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let endianness = self.section()?;
+        let &(snaplen, _) = self.interfaces.get(packet.interface_id as usize).ok_or(physical::Error)?;
+        let data = captured(&packet.data, snaplen);
+        let flags = packet.flags.map(|flags| endianness.u32_bytes(flags.0));
+
+        let mut writer = BlockWriter::new(output, endianness, EnhancedPacket::BLOCK_TYPE)?;
+        writer.u32(packet.interface_id)?;
+        writer.u32((packet.timestamp >> 32) as u32)?;
+        writer.u32(packet.timestamp as u32)?;
+        writer.u32(data.len() as u32)?;
+        writer.u32(packet.original_length)?;
+        writer.padded(data)?;
+        writer.options(&packet.options, &[(EnhancedPacket::FLAGS, flags.as_ref().map(|flags| &flags[..]))])?;
+        writer.finish()
+    }
+
+    /// Writes a simple packet. If the data is longer than the snapshot length of the first interface, only the first `snaplen` bytes are written.
+    pub fn write_simple_packet(&self, packet: &SimplePacket, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let &(snaplen, _) = self.interfaces.first().ok_or(physical::Error)?;
+        let mut writer = BlockWriter::new(output, self.section()?, SimplePacket::BLOCK_TYPE)?;
+        writer.u32(packet.original_length)?;
+        writer.padded(captured(&packet.data, snaplen))?;
+        writer.finish()
+    }
+
+    /// Writes a name resolution block
+    pub fn write_name_resolution(&self, names: &NameResolution, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut writer = BlockWriter::new(output, self.section()?, NameResolution::BLOCK_TYPE)?;
+        for record in names.records.iter() {
+            let record = record.map_err(|_| physical::Error)?;
+            writer.u16(record.kind())?;
+            writer.u16(u16::try_from(record.value_len()).map_err(|_| physical::Error)?)?;
+            let len = match record {
+                NameRecord::Ipv4 { address, names } => {
+                    writer.bytes(&address.octets())?;
+                    writer.bytes(names.0)?;
+                    4 + names.0.len()
+                },
+                NameRecord::Ipv6 { address, names } => {
+                    writer.bytes(&address.octets())?;
+                    writer.bytes(names.0)?;
+                    16 + names.0.len()
+                },
+                NameRecord::Unknown { value, .. } => {
+                    writer.bytes(value)?;
+                    value.len()
+                },
+            };
+            writer.bytes(&[0; 3][..padded_len(len).ok_or(physical::Error)? - len])?;
+        }
+        writer.u32(0)?;
+        writer.options(&names.options, &[])?;
+        writer.finish()
+    }
+
+    /// Writes a custom block
+    pub fn write_custom(&self, custom: &Custom, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let kind = if custom.copyable { Custom::COPYABLE_BLOCK_TYPE } else { Custom::NON_COPYABLE_BLOCK_TYPE };
+        let mut writer = BlockWriter::new(output, self.section()?, kind)?;
+        writer.u32(custom.enterprise_number)?;
+        writer.padded(custom.data)?;
+        writer.finish()
+    }
+}
+
+fn captured<'a>(data: &payload::Unknown<'a>, snaplen: u32) -> &'a [u8] {
+    match snaplen {
+        0 => data.0,
+        snaplen => &data.0[..data.len().min(snaplen as usize)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::link::ethernet::EthernetBase;
+
+    const FRAME: [u8; 16] = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02, 0, 0, 0, 0, 1, 0x08, 0x06, 0xAB, 0xCD];
+
+    // a little endian section with one ethernet interface using nanosecond timestamps and one inbound packet with a comment
+    const LITTLE_NANOS: [u8; 128] = [
+        0x0A, 0x0D, 0x0D, 0x0A, 28, 0, 0, 0, 0x4D, 0x3C, 0x2B, 0x1A, 1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 28, 0, 0, 0,
+        1, 0, 0, 0, 32, 0, 0, 0, 1, 0, 0, 0, 0, 0, 4, 0, 9, 0, 1, 0, 9, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0,
+        6, 0, 0, 0, 68, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x2F, 0x68, 0x59, 16, 0, 0, 0, 16, 0, 0, 0,
+        0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02, 0, 0, 0, 0, 1, 0x08, 0x06, 0xAB, 0xCD,
+        2, 0, 4, 0, 1, 0, 0, 0, 1, 0, 2, 0, b'h', b'i', 0, 0, 0, 0, 0, 0, 68, 0, 0, 0
+    ];
+
+    #[test]
+    fn read_little_endian_nanoseconds() {
+        let mut reader = Reader::new(&LITTLE_NANOS);
+        match reader.next() {
+            Some(Ok(Block::SectionHeader(header))) => {
+                assert_eq!(header.endianness, Endianness::Little);
+                assert_eq!((header.version_major, header.version_minor), (1, 0));
+                assert_eq!(header.section_length, None);
+            },
+            other => panic!("expected a section header, got {:?}", other),
+        }
+        match reader.next() {
+            Some(Ok(Block::InterfaceDescription(interface))) => {
+                assert_eq!(interface.link_type, LinkType::ETHERNET);
+                assert_eq!(interface.snaplen, 0x40000);
+                assert_eq!(interface.resolution, Resolution::NANOSECONDS);
+            },
+            other => panic!("expected an interface description, got {:?}", other),
+        }
+        assert_eq!(reader.interfaces().len(), 1);
+
+        let mut packets = Reader::new(&LITTLE_NANOS).packets();
+        let packet = packets.next().unwrap().unwrap();
+        assert_eq!(packet.link_type(), LinkType::ETHERNET);
+        assert_eq!(packet.timestamp, Some(Timestamp { seconds: 1, nanoseconds: 500_000_000 }));
+        assert_eq!(packet.flags.unwrap().direction(), Direction::Inbound);
+        assert_eq!(packet.options.comments().collect::<Vec<_>>(), ["hi"]);
+        let frame = EthernetBase::parse(packet.data).unwrap();
+        assert_eq!(frame.type_or_length, 0x0806);
+        assert_eq!(packets.next(), None);
+    }
+
+    #[test]
+    fn write_and_read_sections() {
+        let names = [
+            NameRecord::Ipv4 { address: Ipv4Addr::new(192, 0, 2, 1), names: Names(b"router\0gateway\0") },
+            NameRecord::Ipv6 { address: Ipv6Addr::LOCALHOST, names: Names(b"localhost\0") },
+        ];
+        let interface_options = [OptionValue { code: InterfaceDescription::NAME, value: b"eth0" }];
+        let packet_options = [OptionValue::comment("first")];
+
+        let mut writer = Writer::new();
+        let mut buf = [0; 1024];
+        let mut written = 0;
+        for &endianness in &[Endianness::Big, Endianness::Little] {
+            let mut header = SectionHeader::new(endianness);
+            header.options = Options::List(&[OptionValue { code: SectionHeader::USER_APPLICATION, value: b"ips" }]);
+            written += writer.write_section_header(&header, &mut buf[written..]).unwrap();
+
+            let mut raw = InterfaceDescription::new(LinkType::RAW, 0);
+            raw.options = Options::List(&interface_options);
+            written += writer.write_interface_description(&raw, &mut buf[written..]).unwrap();
+            let mut ethernet = InterfaceDescription::new(LinkType::ETHERNET, 14);
+            ethernet.resolution = Resolution::Binary(10);
+            written += writer.write_interface_description(&ethernet, &mut buf[written..]).unwrap();
+
+            let timestamp = Timestamp { seconds: 3, nanoseconds: 500_000_000 };
+            let mut packet = EnhancedPacket::new(1, writer.timestamp(1, timestamp).unwrap(), &FRAME);
+            packet.flags = Some(PacketFlags(2 | PacketFlags::CRC_ERROR));
+            packet.options = Options::List(&packet_options);
+            written += writer.write_enhanced_packet(&packet, &mut buf[written..]).unwrap();
+
+            written += writer.write_simple_packet(&SimplePacket::new(&[0x45, 0, 0]), &mut buf[written..]).unwrap();
+            let names = NameResolution { records: Records::List(&names), options: Options::EMPTY };
+            written += writer.write_name_resolution(&names, &mut buf[written..]).unwrap();
+            let custom = Custom { copyable: false, enterprise_number: 32473, data: b"custom" };
+            written += writer.write_custom(&custom, &mut buf[written..]).unwrap();
+        }
+        let file = &buf[..written];
+
+        let blocks = Reader::new(file).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(blocks.len(), 14);
+        for section in blocks.chunks(7) {
+            match section[0] {
+                Block::SectionHeader(header) => assert_eq!(header.options.find(SectionHeader::USER_APPLICATION), Some(&b"ips"[..])),
+                other => panic!("expected a section header, got {:?}", other),
+            }
+            match section[1] {
+                Block::InterfaceDescription(interface) => assert_eq!(interface.name(), Some("eth0")),
+                other => panic!("expected an interface description, got {:?}", other),
+            }
+            match section[5] {
+                Block::NameResolution(resolution) => {
+                    let records = resolution.records.iter().collect::<Result<Vec<_>, _>>().unwrap();
+                    assert_eq!(records, names);
+                    match records[0] {
+                        NameRecord::Ipv4 { names, .. } => assert_eq!(names.iter().collect::<Vec<_>>(), ["router", "gateway"]),
+                        _ => unreachable!(),
+                    }
+                },
+                other => panic!("expected a name resolution block, got {:?}", other),
+            }
+            assert_eq!(section[6], Block::Custom(Custom { copyable: false, enterprise_number: 32473, data: b"custom\0\0" }));
+        }
+
+        let packets = Reader::new(file).packets().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(packets.len(), 4);
+        for pair in packets.chunks(2) {
+            let (enhanced, simple) = (pair[0], pair[1]);
+            assert_eq!(enhanced.interface_id, 1);
+            assert_eq!(enhanced.link_type(), LinkType::ETHERNET);
+            assert_eq!(enhanced.interface.resolution, Resolution::Binary(10));
+            assert_eq!(enhanced.timestamp, Some(Timestamp { seconds: 3, nanoseconds: 500_000_000 }));
+            assert_eq!(enhanced.original_length, 16);
+            assert_eq!(enhanced.data.0, &FRAME[..14]);
+            let flags = enhanced.flags.unwrap();
+            assert_eq!(flags.direction(), Direction::Outbound);
+            assert_eq!(flags.errors(), PacketFlags::CRC_ERROR);
+            assert_eq!(enhanced.options.comments().collect::<Vec<_>>(), ["first"]);
+
+            assert_eq!(simple.link_type(), LinkType::RAW);
+            assert_eq!(simple.timestamp, None);
+            assert_eq!(simple.data.0, &[0x45, 0, 0]);
+        }
+
+        // blocks read from a file can be written back unchanged
+        let mut copy = [0; 1024];
+        let mut copied = 0;
+        let mut writer = Writer::new();
+        for block in &blocks {
+            copied += writer.write_block(block, &mut copy[copied..]).unwrap();
+        }
+        assert_eq!(&copy[..copied], file);
+    }
+
+    #[test]
+    fn resolution() {
+        assert_eq!(Resolution::from_option(0x8A), Resolution::Binary(10));
+        assert_eq!(Resolution::Binary(10).to_option(), Some(0x8A));
+        assert_eq!(Resolution::Decimal(0x80).to_option(), None);
+        assert_eq!(Resolution::Binary(0xFF).to_option(), None);
+        assert_eq!(Resolution::MICROSECONDS.to_timestamp(1_000_001), Some(Timestamp { seconds: 1, nanoseconds: 1_000 }));
+        assert_eq!(Resolution::Binary(1).to_timestamp(3), Some(Timestamp { seconds: 1, nanoseconds: 500_000_000 }));
+        assert_eq!(Resolution::Decimal(20).to_timestamp(1), None);
+        assert_eq!(Resolution::NANOSECONDS.from_timestamp(Timestamp { seconds: 2, nanoseconds: 7 }), Some(2_000_000_007));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Reader::new(&LITTLE_NANOS[28..]).next(), Some(Err(ParseError::MissingSectionHeader)));
+
+        let mut reader = Reader::new(&LITTLE_NANOS[..127]);
+        assert!(reader.by_ref().take(2).all(|block| block.is_ok()));
+        assert_eq!(reader.next(), Some(Err(ParseError::Truncated)));
+        assert_eq!(reader.next(), None);
+
+        let mut bad_magic = LITTLE_NANOS;
+        bad_magic[8] = 0;
+        assert_eq!(Reader::new(&bad_magic).next(), Some(Err(ParseError::InvalidMagic(0x003C_2B1A))));
+
+        let mut bad_trailer = LITTLE_NANOS;
+        bad_trailer[24] = 32;
+        assert_eq!(Reader::new(&bad_trailer).next(), Some(Err(ParseError::InvalidBlockLength(28))));
+
+        let mut bad_version = LITTLE_NANOS;
+        bad_version[12] = 2;
+        assert_eq!(Reader::new(&bad_version).next(), Some(Err(ParseError::UnsupportedVersion(2))));
+
+        let mut bad_interface = LITTLE_NANOS;
+        bad_interface[68] = 1;
+        assert_eq!(Reader::new(&bad_interface).packets().next(), Some(Err(ParseError::UnknownInterface(1))));
+
+        let mut writer = Writer::new();
+        let mut buf = [0; 64];
+        assert_eq!(writer.write_enhanced_packet(&EnhancedPacket::new(0, 0, &FRAME), &mut buf), Err(physical::Error));
+        assert_eq!(writer.write_interface_description(&InterfaceDescription::new(LinkType::ETHERNET, 0), &mut buf), Err(physical::Error));
+        let names = NameResolution { records: Records::List(&[]), options: Options::EMPTY };
+        assert_eq!(writer.write_name_resolution(&names, &mut buf), Err(physical::Error));
+        assert_eq!(writer.write_section_header(&SectionHeader::new(Endianness::Big), &mut buf[..27]), Err(physical::Error));
+
+        let truncated = Records::Raw { data: &[0xca, 0xf4, 0x05, 0x04, 0x6e, 0x04, 0x2e, 0x44, 0x01], endianness: Endianness::Big };
+        let mut records = truncated.iter();
+        assert_eq!(records.next(), Some(Err(ParseError::Truncated)));
+        assert_eq!(records.next(), None);
+        let names = NameResolution { records: truncated, options: Options::EMPTY };
+        assert_eq!(writer.write_name_resolution(&names, &mut [0; 64]), Err(physical::Error));
+    }
+}
@@ -1,11 +1,197 @@
 //! Contains types and traits for writing and reading data from byte slices
 
+use crate::internal::Sealed;
+
+use core::convert::TryFrom;
+use core::net::{Ipv4Addr, Ipv6Addr};
+use macress::{Address, Eui48};
+
 /// An error struct used to communicate that an error occured while reading our writing a packet value.
 /// This is mostly used to communicate that the output or input is too small to contain a value of a specified type
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Error;
 
 /// A trait used to determine the size of structs when serialized to an output
 pub trait Size {
     /// Gets the size of the value when serialized to an output
     fn size(&self) -> usize;
-}
\ No newline at end of file
+    /// Gets the size of the value without any padding written after it, which is what length fields count.
+    /// For values that aren't padded, this is the same as the [`size`](#tymethod.size).
+    fn unpadded_size(&self) -> usize {
+        self.size()
+    }
+}
+
+/// A trait used to write values to a byte slice.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Write: Size + Sealed {
+    /// Writes the value to the start of the output, returning the number of bytes written.
+    /// The number of bytes written is always equal to the [`size`](trait.Size.html#tymethod.size) of the value.
+    ///
+    /// If the output is smaller than the size of the value, this returns an error and the contents of the output are unspecified.
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error>;
+}
+
+/// A trait used to read values from a byte slice.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Read<'a>: Size + Sized + Sealed {
+    /// Reads a value from the start of the input.
+    /// The number of bytes read is equal to the [`size`](trait.Size.html#tymethod.size) of the returned value.
+    fn read(input: &'a [u8]) -> Result<Self, Error>;
+}
+
+/// A helper used to write a sequence of values to an output
+pub(crate) struct Cursor<'a> {
+    output: &'a mut [u8],
+    written: usize
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(output: &'a mut [u8]) -> Self {
+        Cursor { output, written: 0 }
+    }
+
+    /// Writes a value after the last value written to the cursor
+    pub fn write<W: Write + ?Sized>(&mut self, value: &W) -> Result<(), Error> {
+        let output = self.output.get_mut(self.written..).ok_or(Error)?;
+        self.written += value.write(output)?;
+        Ok(())
+    }
+
+    /// Gets the total number of bytes written to the cursor
+    pub fn finish(self) -> usize {
+        self.written
+    }
+}
+
+impl Sealed for u8 { }
+impl Size for u8 {
+    fn size(&self) -> usize { 1 }
+}
+impl Write for u8 {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        *output.first_mut().ok_or(Error)? = *self;
+        Ok(1)
+    }
+}
+impl Read<'_> for u8 {
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        input.first().copied().ok_or(Error)
+    }
+}
+
+impl Sealed for u16 { }
+impl Size for u16 {
+    fn size(&self) -> usize { 2 }
+}
+/// Writes the value in network byte order
+impl Write for u16 {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        output.get_mut(..2).ok_or(Error)?.copy_from_slice(&self.to_be_bytes());
+        Ok(2)
+    }
+}
+/// Reads the value in network byte order
+impl Read<'_> for u16 {
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        let bytes = input.get(..2).ok_or(Error)?;
+        Ok(u16::from_be_bytes(<[u8; 2]>::try_from(bytes).unwrap()))
+    }
+}
+
+impl Sealed for u32 { }
+impl Size for u32 {
+    fn size(&self) -> usize { 4 }
+}
+/// Writes the value in network byte order
+impl Write for u32 {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        output.get_mut(..4).ok_or(Error)?.copy_from_slice(&self.to_be_bytes());
+        Ok(4)
+    }
+}
+/// Reads the value in network byte order
+impl Read<'_> for u32 {
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        let bytes = input.get(..4).ok_or(Error)?;
+        Ok(u32::from_be_bytes(<[u8; 4]>::try_from(bytes).unwrap()))
+    }
+}
+
+impl Sealed for [u8] { }
+impl Size for [u8] {
+    fn size(&self) -> usize { self.len() }
+}
+impl Write for [u8] {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        output.get_mut(..self.len()).ok_or(Error)?.copy_from_slice(self);
+        Ok(self.len())
+    }
+}
+
+impl<T: Sealed + ?Sized> Sealed for &T { }
+impl<T: Size + ?Sized> Size for &T {
+    fn size(&self) -> usize { (**self).size() }
+    fn unpadded_size(&self) -> usize { (**self).unpadded_size() }
+}
+impl<T: Write + ?Sized> Write for &T {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        (**self).write(output)
+    }
+}
+/// Reads the entire input
+impl<'a> Read<'a> for &'a [u8] {
+    fn read(input: &'a [u8]) -> Result<Self, Error> {
+        Ok(input)
+    }
+}
+
+impl Sealed for Address<Eui48> { }
+impl Size for Address<Eui48> {
+    fn size(&self) -> usize { 6 }
+}
+impl Write for Address<Eui48> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        self.as_ref()[..].write(output)
+    }
+}
+impl Read<'_> for Address<Eui48> {
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        let bytes = input.get(..6).ok_or(Error)?;
+        Ok(Address::new(<[u8; 6]>::try_from(bytes).unwrap()))
+    }
+}
+
+impl Sealed for Ipv4Addr { }
+impl Size for Ipv4Addr {
+    fn size(&self) -> usize { 4 }
+}
+impl Write for Ipv4Addr {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        self.octets()[..].write(output)
+    }
+}
+impl Read<'_> for Ipv4Addr {
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        let bytes = input.get(..4).ok_or(Error)?;
+        Ok(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).unwrap()))
+    }
+}
+
+impl Sealed for Ipv6Addr { }
+impl Size for Ipv6Addr {
+    fn size(&self) -> usize { 16 }
+}
+impl Write for Ipv6Addr {
+    fn write(&self, output: &mut [u8]) -> Result<usize, Error> {
+        self.octets()[..].write(output)
+    }
+}
+impl Read<'_> for Ipv6Addr {
+    fn read(input: &[u8]) -> Result<Self, Error> {
+        let bytes = input.get(..16).ok_or(Error)?;
+        Ok(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).unwrap()))
+    }
+}
@@ -0,0 +1,4 @@
+//! A module containing transport layer types for the internet protocol suite
+
+pub mod tcp;
+pub mod udp;
@@ -0,0 +1,563 @@
+//! Types for reading and writing TCP segments
+
+use crate::checksum::PseudoHeader;
+use crate::internal::Sealed;
+use crate::network::Protocol;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::BitOr;
+
+/// The minimum length of a TCP header
+pub const MIN_HEADER_LEN: usize = 20;
+/// The maximum length of a TCP header
+pub const MAX_HEADER_LEN: usize = 60;
+
+/// A TCP segment parsed from a slice of bytes
+pub type Parsed<'a> = Tcp<'a, payload::Unknown<'a>>;
+
+/// An error that occurs while parsing a TCP segment or its options
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the TCP header or an option
+    Truncated,
+    /// The data offset was less than the minimum header length of 5 words
+    InvalidDataOffset(u8),
+    /// An option had a length that isn't valid for its kind
+    InvalidOptionLength {
+        /// The kind of the option
+        kind: u8,
+        /// The length of the option, including the kind and length octets
+        length: u8
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad TCP segment; ran out of data"),
+            ParseError::InvalidDataOffset(offset) => write!(f, "bad TCP segment; data offset of {} words is too small", offset),
+            ParseError::InvalidOptionLength { kind, length } => write!(f, "bad TCP option; invalid length {} for option kind {}", length, kind),
+        }
+    }
+}
+
+/// The 9-bit control flags of a TCP header
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Flags(u16);
+
+impl Flags {
+    /// No flags set
+    pub const NONE: Flags = Flags(0);
+    /// No more data from the sender
+    pub const FIN: Flags = Flags(0x001);
+    /// Synchronize sequence numbers
+    pub const SYN: Flags = Flags(0x002);
+    /// Reset the connection
+    pub const RST: Flags = Flags(0x004);
+    /// Push buffered data to the receiving application
+    pub const PSH: Flags = Flags(0x008);
+    /// The acknowledgment field is significant
+    pub const ACK: Flags = Flags(0x010);
+    /// The urgent pointer field is significant
+    pub const URG: Flags = Flags(0x020);
+    /// ECN echo
+    pub const ECE: Flags = Flags(0x040);
+    /// Congestion window reduced
+    pub const CWR: Flags = Flags(0x080);
+    /// ECN nonce concealment protection
+    pub const NS: Flags = Flags(0x100);
+
+    /// Returns a new flags value from the low 9 bits of the specified value
+    pub const fn raw(value: u16) -> Flags {
+        Flags(value & 0x1FF)
+    }
+
+    /// Gets whether all of the specified flags are set
+    pub const fn contains(self, other: Flags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Gets whether the FIN flag is set
+    pub const fn fin(self) -> bool { self.contains(Self::FIN) }
+    /// Gets whether the SYN flag is set
+    pub const fn syn(self) -> bool { self.contains(Self::SYN) }
+    /// Gets whether the RST flag is set
+    pub const fn rst(self) -> bool { self.contains(Self::RST) }
+    /// Gets whether the PSH flag is set
+    pub const fn psh(self) -> bool { self.contains(Self::PSH) }
+    /// Gets whether the ACK flag is set
+    pub const fn ack(self) -> bool { self.contains(Self::ACK) }
+    /// Gets whether the URG flag is set
+    pub const fn urg(self) -> bool { self.contains(Self::URG) }
+    /// Gets whether the ECE flag is set
+    pub const fn ece(self) -> bool { self.contains(Self::ECE) }
+    /// Gets whether the CWR flag is set
+    pub const fn cwr(self) -> bool { self.contains(Self::CWR) }
+    /// Gets whether the NS flag is set
+    pub const fn ns(self) -> bool { self.contains(Self::NS) }
+
+    /// Gets the raw underlying value of these flags
+    pub const fn get(self) -> u16 {
+        self.0
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, rhs: Flags) -> Flags {
+        Flags(self.0 | rhs.0)
+    }
+}
+
+/// An iterator over the blocks of a selective acknowledgment option, returning the left and right edges of each block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SackBlocks<'a>(&'a [u8]);
+
+impl Iterator for SackBlocks<'_> {
+    type Item = (u32, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = self.0.get(..8)?;
+        self.0 = &self.0[8..];
+        let left = u32::from_be_bytes(<[u8; 4]>::try_from(&block[..4]).unwrap());
+        let right = u32::from_be_bytes(<[u8; 4]>::try_from(&block[4..]).unwrap());
+        Some((left, right))
+    }
+}
+
+/// A TCP option
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OptionValue<'a> {
+    /// The maximum segment size the sender can receive
+    MaximumSegmentSize(u16),
+    /// The shift count used to scale the window field
+    WindowScale(u8),
+    /// The sender can receive selective acknowledgments
+    SackPermitted,
+    /// Selective acknowledgment blocks
+    Sack(SackBlocks<'a>),
+    /// A timestamp and the echoed timestamp of the peer
+    Timestamps {
+        /// The current value of the timestamp clock of the sender
+        value: u32,
+        /// The most recent timestamp value received from the peer, if ACK is set
+        echo_reply: u32
+    },
+    /// A TCP Fast Open cookie, or a cookie request if the cookie is empty
+    FastOpen(&'a [u8]),
+    /// An option of an unknown kind
+    Unknown {
+        /// The kind of the option
+        kind: u8,
+        /// The contents of the option following the kind and length octets
+        data: &'a [u8]
+    }
+}
+
+impl OptionValue<'_> {
+    /// The end of option list option kind
+    pub const END: u8 = 0;
+    /// The no operation option kind
+    pub const NOP: u8 = 1;
+    /// The maximum segment size option kind
+    pub const MSS: u8 = 2;
+    /// The window scale option kind
+    pub const WINDOW_SCALE: u8 = 3;
+    /// The selective acknowledgment permitted option kind
+    pub const SACK_PERMITTED: u8 = 4;
+    /// The selective acknowledgment option kind
+    pub const SACK: u8 = 5;
+    /// The timestamps option kind
+    pub const TIMESTAMPS: u8 = 8;
+    /// The TCP Fast Open cookie option kind
+    pub const FAST_OPEN: u8 = 34;
+
+    /// Gets the kind of the option
+    pub fn kind(&self) -> u8 {
+        match self {
+            OptionValue::MaximumSegmentSize(_) => Self::MSS,
+            OptionValue::WindowScale(_) => Self::WINDOW_SCALE,
+            OptionValue::SackPermitted => Self::SACK_PERMITTED,
+            OptionValue::Sack(_) => Self::SACK,
+            OptionValue::Timestamps { .. } => Self::TIMESTAMPS,
+            OptionValue::FastOpen(_) => Self::FAST_OPEN,
+            OptionValue::Unknown { kind, .. } => *kind,
+        }
+    }
+}
+
+/// The unparsed options of a TCP header
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Options<'a>(pub &'a [u8]);
+
+impl<'a> Options<'a> {
+    /// An empty options list
+    pub const EMPTY: Options<'static> = Options(&[]);
+
+    /// Returns an iterator over the options in the list, skipping no operation options.
+    /// The iterator ends after an end of option list option, or returns an error if an option is malformed.
+    pub fn iter(&self) -> OptionsIter<'a> {
+        OptionsIter(self.0)
+    }
+}
+
+impl Sealed for Options<'_> { }
+/// The size of the options including the padding used to align the header to a 32-bit boundary
+impl Size for Options<'_> {
+    fn size(&self) -> usize {
+        (self.0.len() + 3) & !3
+    }
+}
+/// Writes the options followed by zeroed padding to align the header to a 32-bit boundary
+impl Write for Options<'_> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let size = self.size();
+        let output = output.get_mut(..size).ok_or(physical::Error)?;
+        let (options, padding) = output.split_at_mut(self.0.len());
+        options.copy_from_slice(self.0);
+        padding.iter_mut().for_each(|b| *b = 0);
+        Ok(size)
+    }
+}
+
+/// An iterator over the options in a TCP header
+#[derive(Clone, Debug)]
+pub struct OptionsIter<'a>(&'a [u8]);
+
+impl<'a> OptionsIter<'a> {
+    fn parse_option(&mut self, kind: u8) -> Result<OptionValue<'a>, ParseError> {
+        let length = *self.0.get(1).ok_or(ParseError::Truncated)?;
+        let invalid = ParseError::InvalidOptionLength { kind, length };
+        if length < 2 {
+            return Err(invalid);
+        }
+        let option = self.0.get(..length as usize).ok_or(ParseError::Truncated)?;
+        self.0 = &self.0[option.len()..];
+
+        let data = &option[2..];
+        let u32_at = |start: usize| u32::from_be_bytes(<[u8; 4]>::try_from(&data[start..start + 4]).unwrap());
+        match (kind, data.len()) {
+            (OptionValue::MSS, 2) => Ok(OptionValue::MaximumSegmentSize(u16::from_be_bytes([data[0], data[1]]))),
+            (OptionValue::WINDOW_SCALE, 1) => Ok(OptionValue::WindowScale(data[0])),
+            (OptionValue::SACK_PERMITTED, 0) => Ok(OptionValue::SackPermitted),
+            (OptionValue::SACK, len) if len % 8 == 0 && len != 0 => Ok(OptionValue::Sack(SackBlocks(data))),
+            (OptionValue::TIMESTAMPS, 8) => Ok(OptionValue::Timestamps { value: u32_at(0), echo_reply: u32_at(4) }),
+            (OptionValue::FAST_OPEN, _) => Ok(OptionValue::FastOpen(data)),
+            (OptionValue::MSS, _) | (OptionValue::WINDOW_SCALE, _) | (OptionValue::SACK_PERMITTED, _) |
+            (OptionValue::SACK, _) | (OptionValue::TIMESTAMPS, _) => Err(invalid),
+            (kind, _) => Ok(OptionValue::Unknown { kind, data }),
+        }
+    }
+}
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = Result<OptionValue<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match *self.0.first()? {
+                OptionValue::END => {
+                    self.0 = &[];
+                    return None;
+                },
+                OptionValue::NOP => self.0 = &self.0[1..],
+                kind => {
+                    let option = self.parse_option(kind);
+                    if option.is_err() {
+                        // the rest of the options can't be found without a valid length
+                        self.0 = &[];
+                    }
+                    return Some(option);
+                }
+            }
+        }
+    }
+}
+
+/// A TCP segment
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Tcp<'a, P> {
+    /// The source port of the segment
+    pub source_port: u16,
+    /// The destination port of the segment
+    pub destination_port: u16,
+    /// The sequence number of the first data octet in this segment, or the initial sequence number if SYN is set
+    pub sequence: u32,
+    /// The next sequence number the sender expects to receive, if ACK is set
+    pub acknowledgment: u32,
+    /// The control flags of the segment
+    pub flags: Flags,
+    /// The number of octets the sender is willing to receive
+    pub window: u16,
+    /// The checksum of the segment
+    pub checksum: u16,
+    /// The offset from the sequence number of the last urgent data octet, if URG is set
+    pub urgent_pointer: u16,
+    /// The options of the segment header
+    pub options: Options<'a>,
+    payload: P
+}
+
+impl<'a, P> Tcp<'a, P> {
+    /// Creates a new segment with no options and the specified ports, sequence numbers, flags, window, and payload.
+    /// The checksum and urgent pointer are zero.
+    pub fn new(source_port: u16, destination_port: u16, sequence: u32, acknowledgment: u32, flags: Flags, window: u16, payload: P) -> Self {
+        Tcp {
+            source_port,
+            destination_port,
+            sequence,
+            acknowledgment,
+            flags,
+            window,
+            checksum: 0,
+            urgent_pointer: 0,
+            options: Options::EMPTY,
+            payload
+        }
+    }
+    /// Gets the length of the header in 32-bit words
+    pub fn data_offset(&self) -> u8 {
+        (self.header_len() / 4) as u8
+    }
+    /// Gets the length of the header in octets
+    pub fn header_len(&self) -> usize {
+        MIN_HEADER_LEN + self.options.size()
+    }
+    /// Gets a reference to the payload of this segment
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Tcp<'a, Q> {
+        let Tcp {
+            source_port, destination_port, sequence, acknowledgment, flags, window, checksum, urgent_pointer, options,
+            payload
+        } = self;
+
+        let payload = f(payload);
+
+        Tcp { source_port, destination_port, sequence, acknowledgment, flags, window, checksum, urgent_pointer, options, payload }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Tcp<'a, Q>, E> {
+        let Tcp {
+            source_port, destination_port, sequence, acknowledgment, flags, window, checksum, urgent_pointer, options,
+            payload
+        } = self;
+
+        let payload = f(payload)?;
+
+        Ok(Tcp { source_port, destination_port, sequence, acknowledgment, flags, window, checksum, urgent_pointer, options, payload })
+    }
+    /// Writes the segment to the output, replacing the [`checksum`](#structfield.checksum) field with a checksum
+    /// computed using the specified pseudo-header and the data written, excluding any padding after the payload.
+    pub fn write_with_checksum(&self, pseudo_header: PseudoHeader, output: &mut [u8]) -> Result<usize, physical::Error> where P: Write {
+        let written = self.write(output)?;
+        output[16..18].copy_from_slice(&[0, 0]);
+
+        let length = self.header_len() + self.payload.unpadded_size();
+        let mut checksum = pseudo_header.checksum(Protocol::TCP, length as u32);
+        checksum.add_bytes(&output[..length]);
+        output[16..18].copy_from_slice(&checksum.finish().to_be_bytes());
+        Ok(written)
+    }
+    fn offset_and_flags(&self) -> u16 {
+        ((self.data_offset() as u16) << 12) | self.flags.get()
+    }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses a TCP segment from a slice of bytes. The payload of the segment is all data following the header.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < MIN_HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let offset_and_flags = u16::from_be_bytes([bytes[12], bytes[13]]);
+        let data_offset = (offset_and_flags >> 12) as u8;
+        let header_len = data_offset as usize * 4;
+        if header_len < MIN_HEADER_LEN {
+            return Err(ParseError::InvalidDataOffset(data_offset));
+        }
+        if bytes.len() < header_len {
+            return Err(ParseError::Truncated);
+        }
+
+        let u32_at = |start: usize| u32::from_be_bytes(<[u8; 4]>::try_from(&bytes[start..start + 4]).unwrap());
+
+        Ok(Tcp {
+            source_port: u16::from_be_bytes([bytes[0], bytes[1]]),
+            destination_port: u16::from_be_bytes([bytes[2], bytes[3]]),
+            sequence: u32_at(4),
+            acknowledgment: u32_at(8),
+            flags: Flags::raw(offset_and_flags),
+            window: u16::from_be_bytes([bytes[14], bytes[15]]),
+            checksum: u16::from_be_bytes([bytes[16], bytes[17]]),
+            urgent_pointer: u16::from_be_bytes([bytes[18], bytes[19]]),
+            options: Options(&bytes.0[MIN_HEADER_LEN..header_len]),
+            payload: bytes.consume(header_len)
+        })
+    }
+
+    /// Computes the checksum of the segment using the specified pseudo-header, ignoring the current value of the [`checksum`](#structfield.checksum) field
+    pub fn compute_checksum(&self, pseudo_header: PseudoHeader) -> u16 {
+        let mut checksum = pseudo_header.checksum(Protocol::TCP, (self.header_len() + self.payload.len()) as u32);
+        checksum
+            .add_u16(self.source_port)
+            .add_u16(self.destination_port)
+            .add_u32(self.sequence)
+            .add_u32(self.acknowledgment)
+            .add_u16(self.offset_and_flags())
+            .add_u16(self.window)
+            .add_u16(self.urgent_pointer)
+            .add_bytes(self.options.0)
+            .add_bytes(self.payload.0);
+        checksum.finish()
+    }
+
+    /// Returns whether the [`checksum`](#structfield.checksum) field matches the checksum of the segment
+    pub fn verify_checksum(&self, pseudo_header: PseudoHeader) -> bool {
+        self.checksum == self.compute_checksum(pseudo_header)
+    }
+}
+
+impl<P> Sealed for Tcp<'_, P> { }
+impl<P: Size> Size for Tcp<'_, P> {
+    fn size(&self) -> usize {
+        self.header_len() + self.payload.size()
+    }
+}
+/// Writes the segment using the checksum field as-is
+impl<P: Write> Write for Tcp<'_, P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        if self.header_len() > MAX_HEADER_LEN {
+            return Err(physical::Error);
+        }
+
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.source_port)?;
+        cursor.write(&self.destination_port)?;
+        cursor.write(&self.sequence)?;
+        cursor.write(&self.acknowledgment)?;
+        cursor.write(&self.offset_and_flags())?;
+        cursor.write(&self.window)?;
+        cursor.write(&self.checksum)?;
+        cursor.write(&self.urgent_pointer)?;
+        cursor.write(&self.options)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+
+    use core::net::Ipv4Addr;
+
+    const V4: PseudoHeader = PseudoHeader::V4 { source: Ipv4Addr::new(10, 0, 0, 1), destination: Ipv4Addr::new(10, 0, 0, 2) };
+
+    // a SYN with MSS, SACK permitted, timestamps, NOP, and window scale options
+    const SYN: [u8; 40] = [
+        0xC3, 0x50, 0x00, 0x50, 0x12, 0x34, 0x56, 0x78, 0x00, 0x00, 0x00, 0x00,
+        0xA0, 0x02, 0xFA, 0xF0, 0x00, 0x00, 0x00, 0x00,
+        0x02, 0x04, 0x05, 0xB4, 0x04, 0x02, 0x08, 0x0A, 0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x01, 0x03, 0x03, 0x07
+    ];
+
+    #[test]
+    fn parse() {
+        let segment = Parsed::parse(&SYN[..]).unwrap();
+        assert_eq!(segment.source_port, 50000);
+        assert_eq!(segment.destination_port, 80);
+        assert_eq!(segment.sequence, 0x12345678);
+        assert_eq!(segment.acknowledgment, 0);
+        assert_eq!(segment.flags, Flags::SYN);
+        assert!(segment.flags.syn() && !segment.flags.ack());
+        assert_eq!(segment.window, 64240);
+        assert_eq!(segment.data_offset(), 10);
+        assert!(segment.payload().is_empty());
+
+        let mut options = segment.options.iter();
+        assert_eq!(options.next(), Some(Ok(OptionValue::MaximumSegmentSize(1460))));
+        assert_eq!(options.next(), Some(Ok(OptionValue::SackPermitted)));
+        assert_eq!(options.next(), Some(Ok(OptionValue::Timestamps { value: 1, echo_reply: 0 })));
+        assert_eq!(options.next(), Some(Ok(OptionValue::WindowScale(7))));
+        assert_eq!(options.next(), None);
+
+        let mut buf = [0; 40];
+        assert_eq!(segment.write(&mut buf), Ok(40));
+        assert_eq!(buf, SYN);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Parsed::parse(&SYN[..19]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&SYN[..39]), Err(ParseError::Truncated));
+
+        let mut bad = SYN;
+        bad[12] = 0x40;
+        assert_eq!(Parsed::parse(&bad[..]), Err(ParseError::InvalidDataOffset(4)));
+    }
+
+    #[test]
+    fn flags() {
+        let flags = Flags::raw(0x1D2);
+        assert!(flags.ns() && flags.cwr() && flags.ece() && flags.ack() && flags.syn());
+        assert!(!flags.urg() && !flags.psh() && !flags.rst() && !flags.fin());
+        assert!(flags.contains(Flags::SYN | Flags::ACK));
+        assert_eq!(Flags::raw(0xFFFF).get(), 0x1FF);
+    }
+
+    #[test]
+    fn options() {
+        let options = Options(&[
+            OptionValue::NOP, OptionValue::NOP, 0x05, 0x12, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4,
+            0x22, 0x04, 0xAB, 0xCD, 0xFD, 0x03, 0xEE, OptionValue::END, 0x02
+        ]);
+        let mut iter = options.iter();
+        match iter.next() {
+            Some(Ok(OptionValue::Sack(blocks))) => assert!(blocks.eq([(1, 2), (3, 4)].iter().copied())),
+            other => panic!("expected SACK blocks, found {:?}", other),
+        }
+        assert_eq!(iter.next(), Some(Ok(OptionValue::FastOpen(&[0xAB, 0xCD]))));
+        assert_eq!(iter.next(), Some(Ok(OptionValue::Unknown { kind: 0xFD, data: &[0xEE] })));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = Options(&[0x02, 0x03, 0x05]).iter();
+        assert_eq!(iter.next(), Some(Err(ParseError::InvalidOptionLength { kind: 2, length: 3 })));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = Options(&[0x08, 0x0A, 0x00]).iter();
+        assert_eq!(iter.next(), Some(Err(ParseError::Truncated)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn checksum() {
+        let mut segment = Tcp::new(80, 50000, 1, 0x12345679, Flags::PSH | Flags::ACK, 512, payload::Any(b"hello"));
+        segment.options = Options(&[OptionValue::NOP, OptionValue::NOP, 0x08, 0x0A, 0, 0, 0, 2, 0, 0, 0, 1]);
+        assert_eq!(segment.data_offset(), 8);
+
+        let mut buf = [0; 37];
+        assert_eq!(segment.write_with_checksum(V4, &mut buf), Ok(37));
+
+        let mut expected = checksum::Checksum::new();
+        expected.add_bytes(&[10, 0, 0, 1, 10, 0, 0, 2, 0, 6, 0, 37]).add_bytes(&buf);
+        assert_eq!(expected.finish(), 0);
+
+        let parsed = Parsed::parse(&buf[..]).unwrap();
+        assert_eq!(parsed.payload().0, b"hello");
+        assert!(parsed.verify_checksum(V4));
+        assert_eq!(parsed.compute_checksum(V4), parsed.checksum);
+
+        let padded = Tcp::new(80, 50000, 1, 0x12345679, Flags::ACK, 512, payload::Padded { payload: payload::Any(b"hello"), padding: &[0xFF; 3][..] });
+        let mut buf = [0; 28];
+        assert_eq!(padded.write_with_checksum(V4, &mut buf), Ok(28));
+        assert!(Parsed::parse(&buf[..25]).unwrap().verify_checksum(V4));
+    }
+}
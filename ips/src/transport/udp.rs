@@ -0,0 +1,264 @@
+//! Types for reading and writing UDP datagrams
+
+use crate::checksum::PseudoHeader;
+use crate::internal::Sealed;
+use crate::network::Protocol;
+use crate::payload;
+use crate::physical::{self, Cursor, Size, Write};
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// The length of a UDP header
+pub const HEADER_LEN: usize = 8;
+
+/// A UDP datagram parsed from a slice of bytes, where any data after the length of the datagram is padding
+pub type Parsed<'a> = Udp<payload::Padded<&'a [u8], payload::Any<'a>>>;
+
+/// An error that occurs while parsing a UDP datagram
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The payload ended before the end of the UDP header or the end of the datagram specified by the length
+    Truncated,
+    /// The length was less than the length of the header
+    InvalidLength(u16)
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated => f.write_str("bad UDP datagram; ran out of data"),
+            ParseError::InvalidLength(len) => write!(f, "bad UDP datagram; length of {} is smaller than the header", len),
+        }
+    }
+}
+
+/// A UDP datagram
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Udp<P> {
+    /// The source port of the datagram
+    pub source_port: u16,
+    /// The destination port of the datagram
+    pub destination_port: u16,
+    /// The length of the entire datagram, including the header and payload
+    pub length: u16,
+    /// The checksum of the datagram, or zero if no checksum was computed
+    pub checksum: u16,
+    payload: P
+}
+
+impl<P> Udp<P> {
+    /// Creates a new datagram with the specified ports and payload.
+    /// The length is set to the length of the header and payload without any padding, and the checksum is zero.
+    ///
+    /// Returns an error if the size of the datagram is too large for the length field.
+    pub fn new(source_port: u16, destination_port: u16, payload: P) -> Result<Self, physical::Error> where P: Size {
+        let length = u16::try_from(HEADER_LEN + payload.unpadded_size()).map_err(|_| physical::Error)?;
+        Ok(Udp { source_port, destination_port, length, checksum: 0, payload })
+    }
+    /// Gets a reference to the payload of this datagram
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn map_payload<Q, F: FnOnce(P) -> Q>(self, f: F) -> Udp<Q> {
+        let Udp { source_port, destination_port, length, checksum, payload } = self;
+
+        let payload = f(payload);
+
+        Udp { source_port, destination_port, length, checksum, payload }
+    }
+    pub fn try_map_payload<Q, E, F: FnOnce(P) -> Result<Q, E>>(self, f: F) -> Result<Udp<Q>, E> {
+        let Udp { source_port, destination_port, length, checksum, payload } = self;
+
+        let payload = f(payload)?;
+
+        Ok(Udp { source_port, destination_port, length, checksum, payload })
+    }
+    /// Writes the datagram to the output, replacing the [`checksum`](#structfield.checksum) field with a checksum
+    /// computed using the specified pseudo-header and the data written.
+    ///
+    /// The checksum covers the first [`length`](#structfield.length) bytes written, so any padding after the datagram isn't included.
+    pub fn write_with_checksum(&self, pseudo_header: PseudoHeader, output: &mut [u8]) -> Result<usize, physical::Error> where P: Write {
+        let written = self.write(output)?;
+        output[6..8].copy_from_slice(&[0, 0]);
+
+        let covered = written.min(self.length as usize);
+        let mut checksum = pseudo_header.checksum(Protocol::UDP, self.length as u32);
+        checksum.add_bytes(&output[..covered]);
+        output[6..8].copy_from_slice(&transmitted(checksum.finish()).to_be_bytes());
+        Ok(written)
+    }
+}
+
+/// A computed checksum of zero is transmitted as all ones, since zero means no checksum was computed
+fn transmitted(checksum: u16) -> u16 {
+    if checksum == 0 { 0xFFFF } else { checksum }
+}
+
+impl<'a> Parsed<'a> {
+    /// Parses a UDP datagram from a slice of bytes.
+    /// The payload of the datagram is the data following the header up to the length of the datagram,
+    /// and any data after the length is padding.
+    pub fn parse<P: Into<payload::Unknown<'a>>>(payload: P) -> Result<Self, ParseError> {
+        let bytes = payload.into();
+        if bytes.len() < HEADER_LEN {
+            return Err(ParseError::Truncated);
+        }
+
+        let length = u16::from_be_bytes([bytes[4], bytes[5]]);
+        if (length as usize) < HEADER_LEN {
+            return Err(ParseError::InvalidLength(length));
+        }
+        if bytes.len() < length as usize {
+            return Err(ParseError::Truncated);
+        }
+
+        Ok(Udp {
+            source_port: u16::from_be_bytes([bytes[0], bytes[1]]),
+            destination_port: u16::from_be_bytes([bytes[2], bytes[3]]),
+            length,
+            checksum: u16::from_be_bytes([bytes[6], bytes[7]]),
+            payload: bytes.consume(HEADER_LEN).as_padded_any(length as usize - HEADER_LEN)
+        })
+    }
+
+    /// Computes the checksum of the datagram using the specified pseudo-header, ignoring the current value of the [`checksum`](#structfield.checksum) field
+    pub fn compute_checksum(&self, pseudo_header: PseudoHeader) -> u16 {
+        let mut checksum = pseudo_header.checksum(Protocol::UDP, self.length as u32);
+        checksum
+            .add_u16(self.source_port)
+            .add_u16(self.destination_port)
+            .add_u16(self.length)
+            .add_bytes(self.payload.payload.0);
+        transmitted(checksum.finish())
+    }
+
+    /// Returns whether the [`checksum`](#structfield.checksum) field matches the checksum of the datagram.
+    ///
+    /// A checksum of zero means no checksum was computed, which is allowed for IPv4 but not for IPv6.
+    pub fn verify_checksum(&self, pseudo_header: PseudoHeader) -> bool {
+        match (self.checksum, pseudo_header) {
+            (0, PseudoHeader::V4 { .. }) => true,
+            (0, PseudoHeader::V6 { .. }) => false,
+            (checksum, _) => checksum == self.compute_checksum(pseudo_header)
+        }
+    }
+}
+
+impl<P> Sealed for Udp<P> { }
+impl<P: Size> Size for Udp<P> {
+    fn size(&self) -> usize {
+        HEADER_LEN + self.payload.size()
+    }
+}
+/// Writes the datagram using the length and checksum fields as-is
+impl<P: Write> Write for Udp<P> {
+    fn write(&self, output: &mut [u8]) -> Result<usize, physical::Error> {
+        let mut cursor = Cursor::new(output);
+        cursor.write(&self.source_port)?;
+        cursor.write(&self.destination_port)?;
+        cursor.write(&self.length)?;
+        cursor.write(&self.checksum)?;
+        cursor.write(&self.payload)?;
+        Ok(cursor.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum;
+    use crate::network::ipv4;
+
+    use core::net::{Ipv4Addr, Ipv6Addr};
+
+    const V4: PseudoHeader = PseudoHeader::V4 { source: Ipv4Addr::new(192, 168, 0, 1), destination: Ipv4Addr::new(192, 168, 0, 2) };
+    const V6: PseudoHeader = PseudoHeader::V6 { source: Ipv6Addr::LOCALHOST, destination: Ipv6Addr::UNSPECIFIED };
+
+    #[test]
+    fn parse() {
+        let bytes = [0x30, 0x39, 0x00, 0x35, 0x00, 0x0A, 0x12, 0x34, 0xAB, 0xCD, 0x00, 0x00];
+        let datagram = Parsed::parse(&bytes[..]).unwrap();
+        assert_eq!(datagram.source_port, 12345);
+        assert_eq!(datagram.destination_port, 53);
+        assert_eq!(datagram.length, 10);
+        assert_eq!(datagram.checksum, 0x1234);
+        assert_eq!(datagram.payload().payload.0, &[0xAB, 0xCD]);
+        assert_eq!(datagram.payload().padding, &[0, 0]);
+
+        let mut buf = [0; 12];
+        assert_eq!(datagram.write(&mut buf), Ok(12));
+        assert_eq!(buf, bytes);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let bytes = [0x30, 0x39, 0x00, 0x35, 0x00, 0x0A, 0x12, 0x34, 0xAB, 0xCD];
+        assert_eq!(Parsed::parse(&bytes[..7]), Err(ParseError::Truncated));
+        assert_eq!(Parsed::parse(&bytes[..9]), Err(ParseError::Truncated));
+
+        let mut bad = bytes;
+        bad[5] = 0x07;
+        assert_eq!(Parsed::parse(&bad[..]), Err(ParseError::InvalidLength(7)));
+    }
+
+    #[test]
+    fn checksum_ipv4() {
+        let datagram = Udp::new(12345, 53, payload::Any(b"hello")).unwrap();
+        assert_eq!(datagram.length, 13);
+
+        let mut buf = [0; 13];
+        assert_eq!(datagram.write_with_checksum(V4, &mut buf), Ok(13));
+
+        let mut expected = checksum::Checksum::new();
+        expected.add_bytes(&[192, 168, 0, 1, 192, 168, 0, 2, 0, 17, 0, 13]).add_bytes(&buf);
+        assert_eq!(expected.finish(), 0);
+
+        let parsed = Parsed::parse(&buf[..]).unwrap();
+        assert!(parsed.verify_checksum(V4));
+        assert!(!parsed.verify_checksum(V6));
+        assert_eq!(parsed.compute_checksum(V4), parsed.checksum);
+    }
+
+    #[test]
+    fn checksum_ipv6() {
+        let datagram = Udp::new(546, 547, payload::Padded { payload: payload::Any(&[1, 2, 3]), padding: &[0xFF; 3][..] }).unwrap();
+        assert_eq!(datagram.length, 11);
+
+        let mut buf = [0; 14];
+        assert_eq!(datagram.write_with_checksum(V6, &mut buf), Ok(14));
+
+        let parsed = Parsed::parse(&buf[..]).unwrap();
+        assert_eq!(parsed.payload().payload.0, &[1, 2, 3]);
+        assert_eq!(parsed.payload().padding, &[0xFF; 3]);
+        assert!(parsed.verify_checksum(V6));
+
+        let mut unchecked = parsed;
+        unchecked.checksum = 0;
+        assert!(unchecked.verify_checksum(V4));
+        assert!(!unchecked.verify_checksum(V6));
+    }
+
+    #[test]
+    fn new_too_large() {
+        let data = [0; 65535 - HEADER_LEN];
+        assert!(Udp::new(1, 2, payload::Any(&data)).is_ok());
+        assert_eq!(Udp::new(1, 2, payload::Any(&[0; 65536 - HEADER_LEN])), Err(physical::Error));
+    }
+
+    #[test]
+    fn from_ipv4() {
+        let packet = [
+            0x45, 0x00, 0x00, 0x1C, 0x00, 0x01, 0x40, 0x00, 0x40, 0x11, 0xB9, 0x7C,
+            0xC0, 0xA8, 0x00, 0x01, 0xC0, 0xA8, 0x00, 0x02,
+            0x30, 0x39, 0x00, 0x35, 0x00, 0x08, 0x00, 0x00
+        ];
+        let packet = ipv4::Parsed::parse(&packet[..]).unwrap();
+        let pseudo_header = packet.pseudo_header();
+        assert_eq!(pseudo_header, V4);
+
+        let packet = packet.try_map_payload(|p| Parsed::parse(p.payload)).unwrap();
+        assert_eq!(packet.payload().destination_port, 53);
+        assert!(packet.payload().verify_checksum(pseudo_header));
+    }
+}
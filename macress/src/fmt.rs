@@ -11,7 +11,7 @@ impl<'a> UpperHex for Hyphen<'a, Address<Eui48>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:X}-{:X}-{:X}-{:X}-{:X}-{:X}", value[0], value[1], value[2], value[3], value[4], value[5])
+        write!(f, "{:02X}-{:02X}-{:02X}-{:02X}-{:02X}-{:02X}", value[0], value[1], value[2], value[3], value[4], value[5])
     }
 }
 
@@ -21,7 +21,7 @@ impl<'a> UpperHex for Hyphen<'a, Address<Eui64>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:X}-{:X}-{:X}-{:X}-{:X}-{:X}-{:X}-{:X}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
+        write!(f, "{:02X}-{:02X}-{:02X}-{:02X}-{:02X}-{:02X}-{:02X}-{:02X}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
     }
 }
 
@@ -31,7 +31,7 @@ impl<'a> LowerHex for Hyphen<'a, Address<Eui48>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:x}-{:x}-{:x}-{:x}-{:x}-{:x}", value[0], value[1], value[2], value[3], value[4], value[5])
+        write!(f, "{:02x}-{:02x}-{:02x}-{:02x}-{:02x}-{:02x}", value[0], value[1], value[2], value[3], value[4], value[5])
     }
 }
 
@@ -41,7 +41,7 @@ impl<'a> LowerHex for Hyphen<'a, Address<Eui64>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:x}-{:x}-{:x}-{:x}-{:x}-{:x}-{:x}-{:x}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
+        write!(f, "{:02x}-{:02x}-{:02x}-{:02x}-{:02x}-{:02x}-{:02x}-{:02x}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
     }
 }
 
@@ -78,7 +78,7 @@ impl<'a> UpperHex for Colon<'a, Address<Eui48>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:X}:{:X}:{:X}:{:X}:{:X}:{:X}", value[0], value[1], value[2], value[3], value[4], value[5])
+        write!(f, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}", value[0], value[1], value[2], value[3], value[4], value[5])
     }
 }
 
@@ -88,7 +88,7 @@ impl<'a> UpperHex for Colon<'a, Address<Eui64>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:X}:{:X}:{:X}:{:X}:{:X}:{:X}:{:X}:{:X}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
+        write!(f, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
     }
 }
 
@@ -98,7 +98,7 @@ impl<'a> LowerHex for Colon<'a, Address<Eui48>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:x}:{:x}:{:x}:{:x}:{:x}:{:x}", value[0], value[1], value[2], value[3], value[4], value[5])
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", value[0], value[1], value[2], value[3], value[4], value[5])
     }
 }
 
@@ -108,7 +108,7 @@ impl<'a> LowerHex for Colon<'a, Address<Eui64>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
     }
 }
 
@@ -145,7 +145,7 @@ impl<'a> UpperHex for Dot<'a, Address<Eui48>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:X}{:X}.{:X}{:X}.{:X}{:X}", value[0], value[1], value[2], value[3], value[4], value[5])
+        write!(f, "{:02X}{:02X}.{:02X}{:02X}.{:02X}{:02X}", value[0], value[1], value[2], value[3], value[4], value[5])
     }
 }
 
@@ -155,7 +155,7 @@ impl<'a> UpperHex for Dot<'a, Address<Eui64>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:X}{:X}.{:X}{:X}.{:X}{:X}.{:X}{:X}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
+        write!(f, "{:02X}{:02X}.{:02X}{:02X}.{:02X}{:02X}.{:02X}{:02X}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
     }
 }
 
@@ -165,7 +165,7 @@ impl<'a> LowerHex for Dot<'a, Address<Eui48>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:x}{:x}.{:x}{:x}.{:x}{:x}", value[0], value[1], value[2], value[3], value[4], value[5])
+        write!(f, "{:02x}{:02x}.{:02x}{:02x}.{:02x}{:02x}", value[0], value[1], value[2], value[3], value[4], value[5])
     }
 }
 
@@ -175,7 +175,7 @@ impl<'a> LowerHex for Dot<'a, Address<Eui64>> {
     /// The alternate flag '#' has no effect on this implementation.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let value = self.0.as_ref();
-        write!(f, "{:x}{:x}.{:x}{:x}.{:x}{:x}.{:x}{:x}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
+        write!(f, "{:02x}{:02x}.{:02x}{:02x}.{:02x}{:02x}.{:02x}{:02x}", value[0], value[1], value[2], value[3], value[4], value[5], value[6], value[7])
     }
 }
 
@@ -1,7 +1,10 @@
-use crate::{Eui48, Eui64, Transmission, Admin};
+use crate::{Eui48, Eui64, Transmission, Admin, AddressParseError, AddressParseErrorKind};
 
 pub trait Sealed { }
 pub trait Eui: Sealed {
+    /// The number of octets in the address
+    const OCTETS: usize;
+
     fn transmission(&self) -> Transmission;
     fn admin(&self) -> Admin;
     fn reverse_bits(&mut self);
@@ -9,6 +12,8 @@ pub trait Eui: Sealed {
 
 impl Sealed for Eui48 { }
 impl Eui for Eui48 {
+    const OCTETS: usize = 6;
+
     fn transmission(&self) -> Transmission {
         match self[0] & 0x01 {
             0 => Transmission::Unicast,
@@ -30,6 +35,8 @@ impl Eui for Eui48 {
 }
 impl Sealed for Eui64 { }
 impl Eui for Eui64 {
+    const OCTETS: usize = 8;
+
     fn transmission(&self) -> Transmission {
         match self[0] & 0x01 {
             0 => Transmission::Unicast,
@@ -50,31 +57,223 @@ impl Eui for Eui64 {
     }
 }
 
+/// Parses an address in hyphen, colon, dot, or bare notation into the output buffer.
+/// The length of the output determines the number of octets expected in the string.
+pub fn parse(s: &str, out: &mut [u8]) -> Result<(), AddressParseError> {
+    let bytes = s.as_bytes();
+    if bytes.is_empty() {
+        return Err(AddressParseError::new(AddressParseErrorKind::Empty));
+    }
+
+    // the seperator is the first non-hex character in the string, if there is one
+    let (seperator, group_len) = match bytes.iter().position(|b| !b.is_ascii_hexdigit()) {
+        None if bytes.len() == out.len() * 2 => {
+            parse_digits(bytes, out);
+            return Ok(());
+        },
+        None => return Err(AddressParseError::new(AddressParseErrorKind::InvalidLength)),
+        Some(index) => match bytes[index] {
+            b'-' | b':' => (bytes[index], 2),
+            b'.' => (b'.', 4),
+            _ => return Err(invalid_character(s, index)),
+        }
+    };
+
+    let octets_per_group = group_len / 2;
+    let mut groups = out.chunks_exact_mut(octets_per_group);
+    let mut index = 0;
+    for (group, digits) in bytes.split(|&b| b == seperator).enumerate() {
+        if let Some(offset) = digits.iter().position(|b| !b.is_ascii_hexdigit()) {
+            return Err(invalid_character(s, index + offset));
+        }
+        match groups.next() {
+            Some(octets) if digits.len() == group_len => parse_digits(digits, octets),
+            _ => return Err(AddressParseError::new(AddressParseErrorKind::InvalidGroup { group })),
+        }
+        index += digits.len() + 1;
+    }
+
+    match groups.len() {
+        0 => Ok(()),
+        // we ran out of groups before filling the address
+        remaining => Err(AddressParseError::new(AddressParseErrorKind::InvalidGroup {
+            group: out.len() / octets_per_group - remaining
+        }))
+    }
+}
+
+fn parse_digits(digits: &[u8], out: &mut [u8]) {
+    for (octet, pair) in out.iter_mut().zip(digits.chunks_exact(2)) {
+        *octet = (hex_value(pair[0]) << 4) | hex_value(pair[1]);
+    }
+}
+
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        b'A'..=b'F' => digit - b'A' + 10,
+        _ => unreachable!()
+    }
+}
+
+fn invalid_character(s: &str, index: usize) -> AddressParseError {
+    // everything before the index is an ascii hex digit or seperator, so the index is always on a char boundary
+    let character = s[index..].chars().next().unwrap();
+    AddressParseError::new(AddressParseErrorKind::InvalidCharacter { character, index })
+}
+
 #[cfg(feature = "serde")]
 pub mod serde {
-    use crate::{Address, Eui48, Eui64};
+    use super::Eui;
+    use crate::{Address, AddressParseError};
+
+    use core::fmt::{self, Display, Formatter, Write};
+    use core::marker::PhantomData;
+    use core::str::FromStr;
+    use serde::de::{self, SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserializer, Serializer};
+
+    /// The length of the longest string produced when formatting an address (a hyphenated EUI-64 address)
+    const MAX_STR_LEN: usize = 23;
 
-    use core::fmt::{self, Formatter};
-    use serde::de::Visitor;
+    /// A stack buffer used to format addresses without allocating
+    struct StrBuf {
+        buf: [u8; MAX_STR_LEN],
+        len: usize
+    }
+
+    impl Write for StrBuf {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            if end > MAX_STR_LEN {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    /// Serializes an address as a string for human readable formats, or as a tuple of octets for binary formats
+    pub fn serialize<T, S>(address: &Address<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where T: Eui + AsRef<[u8]>, Address<T>: Display, S: Serializer
+    {
+        if serializer.is_human_readable() {
+            let mut buf = StrBuf { buf: [0; MAX_STR_LEN], len: 0 };
+            write!(buf, "{}", address).map_err(|_| serde::ser::Error::custom("address too long to format"))?;
+            // the buffer only contains the hex digits and seperators written by the formatter
+            serializer.serialize_str(core::str::from_utf8(&buf.buf[..buf.len]).unwrap())
+        } else {
+            let mut tuple = serializer.serialize_tuple(T::OCTETS)?;
+            for octet in address.as_ref().as_ref() {
+                tuple.serialize_element(octet)?;
+            }
+            tuple.end()
+        }
+    }
+
+    /// Deserializes an address from a string for human readable formats, or from a tuple of octets for binary formats
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Address<T>, D::Error>
+        where T: Eui + Default + AsMut<[u8]>, Address<T>: FromStr<Err = AddressParseError>, D: Deserializer<'de>
+    {
+        let visitor = AddressVisitor::<T>::default();
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(visitor)
+        } else {
+            deserializer.deserialize_tuple(T::OCTETS, visitor)
+        }
+    }
 
     #[derive(Default)]
     pub struct AddressVisitor<T> {
-        t: core::marker::PhantomData<T>
+        t: PhantomData<T>
     }
 
-    impl<'de> Visitor<'de> for AddressVisitor<Eui48> {
-        type Value = Address<Eui48>;
+    impl<'de, T> Visitor<'de> for AddressVisitor<T>
+        where T: Eui + Default + AsMut<[u8]>, Address<T>: FromStr<Err = AddressParseError>
+    {
+        type Value = Address<T>;
 
         fn expecting(&self, f: &mut Formatter) -> fmt::Result {
-            unimplemented!()
+            write!(f, "a {}-octet MAC address", T::OCTETS)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            v.parse().map_err(E::custom)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            if v.len() != T::OCTETS {
+                return Err(E::invalid_length(v.len(), &self));
+            }
+            let mut value = T::default();
+            value.as_mut().copy_from_slice(v);
+            Ok(Address::new(value))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut value = T::default();
+            for (i, octet) in value.as_mut().iter_mut().enumerate() {
+                *octet = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            Ok(Address::new(value))
         }
     }
 
-    impl<'de> Visitor<'de> for AddressVisitor<Eui64> {
-        type Value = Address<Eui48>;
+    #[cfg(test)]
+    mod tests {
+        use crate::{Address, Eui48, Eui64};
 
-        fn expecting(&self, f: &mut Formatter) -> fmt::Result {
-            unimplemented!()
+        const EUI48: Address<Eui48> = Address::new([0x01, 0x23, 0x45, 0x67, 0x89, 0xAB]);
+        const EUI64: Address<Eui64> = Address::new([0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]);
+
+        #[test]
+        fn json_round_trip() {
+            let json = serde_json::to_string(&EUI48).unwrap();
+            assert_eq!(json, "\"01-23-45-67-89-AB\"");
+            assert_eq!(serde_json::from_str::<Address<Eui48>>(&json).unwrap(), EUI48);
+
+            let json = serde_json::to_string(&EUI64).unwrap();
+            assert_eq!(json, "\"01-23-45-67-89-AB-CD-EF\"");
+            assert_eq!(serde_json::from_str::<Address<Eui64>>(&json).unwrap(), EUI64);
+        }
+
+        #[test]
+        fn json_notations() {
+            for s in &["\"01:23:45:67:89:ab\"", "\"0123.4567.89ab\"", "\"0123456789AB\""] {
+                assert_eq!(serde_json::from_str::<Address<Eui48>>(s).unwrap(), EUI48, "{}", s);
+            }
+            for s in &["\"01:23:45:67:89:ab:cd:ef\"", "\"0123.4567.89ab.cdef\"", "\"0123456789ABCDEF\""] {
+                assert_eq!(serde_json::from_str::<Address<Eui64>>(s).unwrap(), EUI64, "{}", s);
+            }
+        }
+
+        #[test]
+        fn json_errors() {
+            assert!(serde_json::from_str::<Address<Eui48>>("\"01-23-45-67-89\"").is_err());
+            assert!(serde_json::from_str::<Address<Eui48>>("\"01-23-45-67-89-AB-CD-EF\"").is_err());
+            assert!(serde_json::from_str::<Address<Eui64>>("\"01-23-45-67-89-AB\"").is_err());
+            assert!(serde_json::from_str::<Address<Eui48>>("[1, 35, 69, 103, 137, 171]").is_err());
+        }
+
+        #[test]
+        fn bincode_round_trip() {
+            let bytes = bincode::serialize(&EUI48).unwrap();
+            assert_eq!(bytes, EUI48.get());
+            assert_eq!(bincode::deserialize::<Address<Eui48>>(&bytes).unwrap(), EUI48);
+
+            let bytes = bincode::serialize(&EUI64).unwrap();
+            assert_eq!(bytes, EUI64.get());
+            assert_eq!(bincode::deserialize::<Address<Eui64>>(&bytes).unwrap(), EUI64);
+        }
+
+        #[test]
+        fn bincode_truncated() {
+            let bytes = bincode::serialize(&EUI48).unwrap();
+            assert!(bincode::deserialize::<Address<Eui64>>(&bytes).is_err());
+            assert!(bincode::deserialize::<Address<Eui48>>(&bytes[..5]).is_err());
         }
     }
-}
\ No newline at end of file
+}
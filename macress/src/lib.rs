@@ -1,4 +1,7 @@
 //! An idiomatic, easy to use, no-std compatible implementation of EUI-48 and EUI-64 addresses.
+//! 
+//! With the `serde` feature enabled, addresses are serialized as strings in human readable formats
+//! and as 6 or 8 raw octets in binary formats.
 
 #![no_std]
 
@@ -28,7 +31,56 @@ pub enum Admin {
 }
 
 /// An error that occurs while parsing an address from a string
-pub struct AddressParseError(());
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AddressParseError {
+    kind: AddressParseErrorKind
+}
+
+impl AddressParseError {
+    pub(crate) const fn new(kind: AddressParseErrorKind) -> Self {
+        AddressParseError { kind }
+    }
+
+    /// Gets the kind of error that occured while parsing the address
+    pub fn kind(&self) -> AddressParseErrorKind {
+        self.kind
+    }
+}
+
+impl Display for AddressParseError {
+    fn fmt(&self, f: &mut Formatter) -> corefmt::Result {
+        match self.kind {
+            AddressParseErrorKind::Empty => f.write_str("cannot parse address from empty string"),
+            AddressParseErrorKind::InvalidLength => f.write_str("invalid number of hex digits in address"),
+            AddressParseErrorKind::InvalidCharacter { character, index } =>
+                write!(f, "invalid character {:?} at index {} in address", character, index),
+            AddressParseErrorKind::InvalidGroup { group } => write!(f, "invalid digit group {} in address", group),
+        }
+    }
+}
+
+/// The kinds of errors that can occur while parsing an address from a string
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressParseErrorKind {
+    /// The string was empty
+    Empty,
+    /// The string contains no seperators and has the wrong number of hex digits for the address
+    InvalidLength,
+    /// The string contains a character that isn't a hex digit or the seperator used by the address' notation
+    InvalidCharacter {
+        /// The invalid character
+        character: char,
+        /// The byte index of the character in the string
+        index: usize
+    },
+    /// A seperated group of hex digits has the wrong number of digits, or the string has the wrong number of groups.
+    /// 
+    /// Groups are indexed from zero. If the address has too many groups, this is the index of the first extra group.
+    InvalidGroup {
+        /// The index of the invalid group
+        group: usize
+    }
+}
 
 /// The type used to represent a 6-octet MAC address value
 pub type Eui48 = [u8; 6];
@@ -142,7 +194,7 @@ impl<'de> serde::Deserialize<'de> for Address<Eui48> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: serde::Deserializer<'de>
     {
-        deserializer.deserialize_str(internal::serde::AddressVisitor::<Eui48>::default())
+        internal::serde::deserialize(deserializer)
     }
 }
 
@@ -151,7 +203,7 @@ impl serde::Serialize for Address<Eui48> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where S: serde::Serializer
     {
-        serializer.collect_str(self)
+        internal::serde::serialize(self, serializer)
     }
 }
 
@@ -160,7 +212,7 @@ impl<'de> serde::Deserialize<'de> for Address<Eui64> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: serde::Deserializer<'de>
     {
-        deserializer.deserialize_str(internal::serde::AddressVisitor::<Eui64>::default())
+        internal::serde::deserialize(deserializer)
     }
 }
 
@@ -169,7 +221,7 @@ impl serde::Serialize for Address<Eui64> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where S: serde::Serializer
     {
-        serializer.collect_str(self)
+        internal::serde::serialize(self, serializer)
     }
 }
 
@@ -212,15 +264,93 @@ impl Display for Address<Eui64> {
 impl FromStr for Address<Eui48> {
     type Err = AddressParseError;
 
+    /// Parses an address from a string in hyphen ("01-23-45-67-89-AB"), colon ("01:23:45:67:89:AB"), 
+    /// dot ("0123.4567.89AB") or bare ("0123456789AB") notation. Hex digits may be upper or lower case.
     fn from_str(s: &str) -> Result<Self, AddressParseError> {
-        unimplemented!()
+        let mut value = [0; 6];
+        internal::parse(s, &mut value)?;
+        Ok(Address::new(value))
     }
 }
 
 impl FromStr for Address<Eui64> {
     type Err = AddressParseError;
 
+    /// Parses an address from a string in hyphen ("01-23-45-67-89-AB-CD-EF"), colon ("01:23:45:67:89:AB:CD:EF"), 
+    /// dot ("0123.4567.89AB.CDEF") or bare ("0123456789ABCDEF") notation. Hex digits may be upper or lower case.
     fn from_str(s: &str) -> Result<Self, AddressParseError> {
-        unimplemented!()
+        let mut value = [0; 8];
+        internal::parse(s, &mut value)?;
+        Ok(Address::new(value))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    const EUI48: Address<Eui48> = Address::new([0x01, 0x23, 0x45, 0x67, 0x89, 0xAB]);
+    const EUI64: Address<Eui64> = Address::new([0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]);
+
+    fn kind<T: FromStr<Err = AddressParseError>>(s: &str) -> AddressParseErrorKind {
+        match s.parse::<T>() {
+            Ok(_) => panic!("expected {:?} to fail to parse", s),
+            Err(e) => e.kind(),
+        }
+    }
+
+    #[test]
+    fn parse_eui48_notations() {
+        for s in &["01-23-45-67-89-AB", "01:23:45:67:89:ab", "0123.4567.89AB", "0123456789ab"] {
+            assert_eq!(s.parse::<Address<Eui48>>(), Ok(EUI48), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_eui64_notations() {
+        for s in &["01-23-45-67-89-AB-CD-EF", "01:23:45:67:89:ab:cd:ef", "0123.4567.89AB.cdef", "0123456789ABCDEF"] {
+            assert_eq!(s.parse::<Address<Eui64>>(), Ok(EUI64), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_formatted() {
+        let formatted = [
+            format!("{}", EUI48), format!("{:?}", EUI48),
+            format!("{:x}", fmt::Hyphen(&EUI48)), format!("{:X}", fmt::Colon(&EUI48)),
+            format!("{:x}", fmt::Dot(&EUI48)), format!("{:X}", fmt::Dot(&EUI48)),
+        ];
+        for s in &formatted {
+            assert_eq!(s.parse::<Address<Eui48>>(), Ok(EUI48), "{}", s);
+        }
+
+        let formatted = [
+            format!("{}", EUI64), format!("{:?}", EUI64),
+            format!("{:x}", fmt::Hyphen(&EUI64)), format!("{:x}", fmt::Colon(&EUI64)),
+            format!("{:X}", fmt::Dot(&EUI64)),
+        ];
+        for s in &formatted {
+            assert_eq!(s.parse::<Address<Eui64>>(), Ok(EUI64), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(kind::<Address<Eui48>>(""), AddressParseErrorKind::Empty);
+        assert_eq!(kind::<Address<Eui48>>("0123456789"), AddressParseErrorKind::InvalidLength);
+        assert_eq!(kind::<Address<Eui48>>("0123456789ABCDEF"), AddressParseErrorKind::InvalidLength);
+        assert_eq!(kind::<Address<Eui48>>("01-23-45-67-8G-AB"), AddressParseErrorKind::InvalidCharacter { character: 'G', index: 13 });
+        assert_eq!(kind::<Address<Eui48>>("01-23:45-67-89-AB"), AddressParseErrorKind::InvalidCharacter { character: ':', index: 5 });
+        assert_eq!(kind::<Address<Eui48>>("01_23_45_67_89_AB"), AddressParseErrorKind::InvalidCharacter { character: '_', index: 2 });
+        assert_eq!(kind::<Address<Eui48>>("01-23-45-67-89-Aé"), AddressParseErrorKind::InvalidCharacter { character: 'é', index: 16 });
+        assert_eq!(kind::<Address<Eui48>>("01-23-4-67-89-AB"), AddressParseErrorKind::InvalidGroup { group: 2 });
+        assert_eq!(kind::<Address<Eui48>>("01-23-45-67-89"), AddressParseErrorKind::InvalidGroup { group: 5 });
+        assert_eq!(kind::<Address<Eui48>>("01-23-45-67-89-AB-CD"), AddressParseErrorKind::InvalidGroup { group: 6 });
+        assert_eq!(kind::<Address<Eui48>>("01-23-45-67-89-AB-"), AddressParseErrorKind::InvalidGroup { group: 6 });
+        assert_eq!(kind::<Address<Eui48>>("0123.4567.89AB.CDEF"), AddressParseErrorKind::InvalidGroup { group: 3 });
+        assert_eq!(kind::<Address<Eui64>>("0123.4567.89AB"), AddressParseErrorKind::InvalidGroup { group: 3 });
+    }
+}